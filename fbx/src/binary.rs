@@ -1,4 +1,5 @@
 pub(crate) use reader::BinaryReader;
+pub(crate) use writer::BinaryWriter;
 
 mod reader;
 mod writer;
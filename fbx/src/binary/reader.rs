@@ -1,7 +1,4 @@
-use std::fs::File;
-use std::io::{BufReader, Read};
-
-type Reader = BufReader<File>;
+use std::io::Read;
 
 pub struct BinaryReader {
     reader: Box<dyn Read>,
@@ -21,7 +18,7 @@ impl BinaryReader {
         let mut bytes = vec![0; len];
         let _ = self.reader.read_exact(&mut bytes);
 
-        self.cursor = self.cursor + len;
+        self.cursor += len;
         bytes
     }
 
@@ -40,11 +37,11 @@ impl BinaryReader {
     pub(crate) fn read_boolean(&mut self) -> bool {
         let c = self.read_char();
 
-        if (c == 'Y' /* 0x59 */) {
+        if c == 'Y' /* 0x59 */ {
             return true;
         }
 
-        if (c == 'T' /* 0x54 */) {
+        if c == 'T' /* 0x54 */ {
             return false;
         }
 
@@ -55,13 +52,6 @@ impl BinaryReader {
         self.read_bytes_exact(1)[0]
     }
 
-    pub(crate) fn read_i16_be(&mut self) -> i16 {
-        let number = self.read_bytes_exact(2);
-        let bytes: [u8; 2] = number.try_into().unwrap();
-
-        i16::from_be_bytes(bytes)
-    }
-
     pub(crate) fn read_i16_le(&mut self) -> i16 {
         let number = self.read_bytes_exact(2);
         let bytes: [u8; 2] = number.try_into().unwrap();
@@ -69,27 +59,6 @@ impl BinaryReader {
         i16::from_le_bytes(bytes)
     }
 
-    pub(crate) fn read_u16_be(&mut self) -> u16 {
-        let number = self.read_bytes_exact(2);
-        let bytes: [u8; 2] = number.try_into().unwrap();
-
-        u16::from_be_bytes(bytes)
-    }
-
-    pub(crate) fn read_u16_le(&mut self) -> u16 {
-        let number = self.read_bytes_exact(2);
-        let bytes: [u8; 2] = number.try_into().unwrap();
-
-        u16::from_le_bytes(bytes)
-    }
-
-    pub(crate) fn read_i32_be(&mut self) -> i32 {
-        let number = self.read_bytes_exact(4);
-        let bytes: [u8; 4] = number.try_into().unwrap();
-
-        i32::from_be_bytes(bytes)
-    }
-
     pub(crate) fn read_i32_le(&mut self) -> i32 {
         let number = self.read_bytes_exact(4);
         let bytes: [u8; 4] = number.try_into().unwrap();
@@ -97,13 +66,6 @@ impl BinaryReader {
         i32::from_le_bytes(bytes)
     }
 
-    pub(crate) fn read_u32_be(&mut self) -> u32 {
-        let number = self.read_bytes_exact(4);
-        let bytes: [u8; 4] = number.try_into().unwrap();
-
-        u32::from_be_bytes(bytes)
-    }
-
     pub(crate) fn read_u32_le(&mut self) -> u32 {
         let number = self.read_bytes_exact(4);
         let bytes: [u8; 4] = number.try_into().unwrap();
@@ -111,13 +73,6 @@ impl BinaryReader {
         u32::from_le_bytes(bytes)
     }
 
-    pub(crate) fn read_u64_be(&mut self) -> u64 {
-        let number = self.read_bytes_exact(8);
-        let bytes: [u8; 8] = number.try_into().unwrap();
-
-        u64::from_be_bytes(bytes)
-    }
-
     pub(crate) fn read_u64_le(&mut self) -> u64 {
         let number = self.read_bytes_exact(8);
         let bytes: [u8; 8] = number.try_into().unwrap();
@@ -125,13 +80,6 @@ impl BinaryReader {
         u64::from_le_bytes(bytes)
     }
 
-    pub(crate) fn read_i64_be(&mut self) -> i64 {
-        let number = self.read_bytes_exact(8);
-        let bytes: [u8; 8] = number.try_into().unwrap();
-
-        i64::from_be_bytes(bytes)
-    }
-
     pub(crate) fn read_i64_le(&mut self) -> i64 {
         let number = self.read_bytes_exact(8);
         let bytes: [u8; 8] = number.try_into().unwrap();
@@ -139,13 +87,6 @@ impl BinaryReader {
         i64::from_le_bytes(bytes)
     }
 
-    pub(crate) fn read_f32_be(&mut self) -> f32 {
-        let number = self.read_bytes_exact(4);
-        let bytes: [u8; 4] = number.try_into().unwrap();
-
-        f32::from_be_bytes(bytes)
-    }
-
     pub(crate) fn read_f32_le(&mut self) -> f32 {
         let number = self.read_bytes_exact(4);
         let bytes: [u8; 4] = number.try_into().unwrap();
@@ -153,13 +94,6 @@ impl BinaryReader {
         f32::from_le_bytes(bytes)
     }
 
-    pub(crate) fn read_f64_be(&mut self) -> f64 {
-        let number = self.read_bytes_exact(8);
-        let bytes: [u8; 8] = number.try_into().unwrap();
-
-        f64::from_be_bytes(bytes)
-    }
-
     pub(crate) fn read_f64_le(&mut self) -> f64 {
         let number = self.read_bytes_exact(8);
         let bytes: [u8; 8] = number.try_into().unwrap();
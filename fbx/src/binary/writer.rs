@@ -1 +1,75 @@
-pub struct BinaryWriter {}
+pub struct BinaryWriter {
+    buffer: Vec<u8>,
+}
+
+impl BinaryWriter {
+    pub(crate) fn new() -> Self {
+        BinaryWriter { buffer: vec![] }
+    }
+
+    pub(crate) fn current_cursor(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    pub(crate) fn write_char(&mut self, c: char) {
+        self.buffer.push(c as u8);
+    }
+
+    pub(crate) fn write_string(&mut self, s: &str) {
+        self.write_bytes(s.as_bytes());
+    }
+
+    // booleans are written as 0x01 or 0x00, as Blender does
+    pub(crate) fn write_boolean(&mut self, b: bool) {
+        self.write_u8(b as u8);
+    }
+
+    pub(crate) fn write_u8(&mut self, n: u8) {
+        self.buffer.push(n);
+    }
+
+    pub(crate) fn write_i16_le(&mut self, n: i16) {
+        self.write_bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn write_i32_le(&mut self, n: i32) {
+        self.write_bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn write_u32_le(&mut self, n: u32) {
+        self.write_bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn write_i64_le(&mut self, n: i64) {
+        self.write_bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn write_u64_le(&mut self, n: u64) {
+        self.write_bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn write_f32_le(&mut self, n: f32) {
+        self.write_bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn write_f64_le(&mut self, n: f64) {
+        self.write_bytes(&n.to_le_bytes());
+    }
+
+    // overwrites a value written earlier, used for offsets only known after the node is written
+    pub(crate) fn patch_u32_le(&mut self, at: usize, n: u32) {
+        self.buffer[at..at + 4].copy_from_slice(&n.to_le_bytes());
+    }
+
+    pub(crate) fn patch_u64_le(&mut self, at: usize, n: u64) {
+        self.buffer[at..at + 8].copy_from_slice(&n.to_le_bytes());
+    }
+}
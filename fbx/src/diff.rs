@@ -0,0 +1,512 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::format::{Node, Object, Type};
+use crate::visitor::{occurrences, NodePath, PathSegment};
+
+#[derive(Debug, Clone, Copy)]
+pub struct DiffOptions {
+    tolerance: f64,
+}
+
+impl DiffOptions {
+    pub fn new(tolerance: f64) -> Self {
+        DiffOptions { tolerance }
+    }
+
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions { tolerance: 1e-6 }
+    }
+}
+
+/// Summary of a numeric array attribute that differs between both trees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrayDiff {
+    left_len: usize,
+    right_len: usize,
+    differing: usize,
+    max_delta: f64,
+}
+
+impl ArrayDiff {
+    pub fn left_len(&self) -> usize {
+        self.left_len
+    }
+
+    pub fn right_len(&self) -> usize {
+        self.right_len
+    }
+
+    // values beyond the tolerance, counting length differences as differing values
+    pub fn differing(&self) -> usize {
+        self.differing
+    }
+
+    pub fn max_delta(&self) -> f64 {
+        self.max_delta
+    }
+}
+
+impl Display for ArrayDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let total = self.left_len.max(self.right_len);
+        write!(f, "{} of {} values differ, max delta {:e}", self.differing, total, self.max_delta)?;
+
+        if self.left_len != self.right_len {
+            write!(f, " (length {} -> {})", self.left_len, self.right_len)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Change {
+    NodeAdded { path: NodePath },
+    NodeRemoved { path: NodePath },
+    NodeMoved { from: NodePath, to: NodePath },
+    AttributeAdded { path: NodePath, index: usize, value: Type },
+    AttributeRemoved { path: NodePath, index: usize, value: Type },
+    AttributeChanged { path: NodePath, index: usize, left: Type, right: Type },
+    ArrayChanged { path: NodePath, index: usize, summary: ArrayDiff },
+    // raw bytes are compared exactly, `offset` is the first byte that differs
+    RawChanged { path: NodePath, index: usize, offset: usize, left_len: usize, right_len: usize },
+}
+
+fn attribute_label(path: &NodePath, index: usize) -> String {
+    if index == 0 {
+        path.to_string()
+    } else {
+        format!("{}#{}", path, index)
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::NodeAdded { path } => write!(f, "added: {}", path),
+            Change::NodeRemoved { path } => write!(f, "removed: {}", path),
+            Change::NodeMoved { from, to } => write!(f, "moved: {} -> {}", from, to),
+            Change::AttributeAdded { path, index, value } => {
+                write!(f, "attribute added: {}: {}", attribute_label(path, *index), value)
+            }
+            Change::AttributeRemoved { path, index, value } => {
+                write!(f, "attribute removed: {}: {}", attribute_label(path, *index), value)
+            }
+            Change::AttributeChanged { path, index, left, right } => {
+                write!(f, "changed: {}: {} -> {}", attribute_label(path, *index), left, right)
+            }
+            Change::ArrayChanged { path, index, summary } => {
+                write!(f, "changed: {}: {}", attribute_label(path, *index), summary)
+            }
+            Change::RawChanged { path, index, offset, left_len, right_len } => {
+                write!(f, "changed: {}: bytes differ from offset {}", attribute_label(path, *index), offset)?;
+
+                if left_len != right_len {
+                    write!(f, " (length {} -> {})", left_len, right_len)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Diff {
+    changes: Vec<Change>,
+}
+
+impl Diff {
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn report(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for change in self.changes.iter() {
+            writeln!(f, "{}", change)?;
+        }
+
+        Ok(())
+    }
+}
+
+// identifies a node among its siblings so that reordered nodes are still paired
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum NodeKey {
+    Id(String, i64),
+    Property(String),
+    Connection(String),
+    Occurrence(String, usize),
+}
+
+fn node_keys(nodes: &[Node]) -> Vec<(NodeKey, usize)> {
+    let mut anonymous: HashMap<&str, usize> = HashMap::new();
+    let mut duplicates: HashMap<NodeKey, usize> = HashMap::new();
+
+    nodes
+        .iter()
+        .map(|node| {
+            let key = match (node.name_str(), node.attributes_slice().first()) {
+                (name, Some(Type::Int64(id))) => NodeKey::Id(name.to_owned(), *id),
+                ("P", Some(Type::String(name))) => NodeKey::Property(name.to_owned()),
+                ("C", _) => NodeKey::Connection(format!("{:?}", node.attributes_slice())),
+                (name, _) => {
+                    let count = anonymous.entry(name).or_insert(0);
+                    *count += 1;
+                    NodeKey::Occurrence(name.to_owned(), *count - 1)
+                }
+            };
+
+            let count = duplicates.entry(key.clone()).or_insert(0);
+            *count += 1;
+            (key, *count - 1)
+        })
+        .collect()
+}
+
+// positions (into `sequence`) of a longest strictly increasing subsequence
+fn longest_increasing_subsequence(sequence: &[usize]) -> Vec<usize> {
+    let mut tails: Vec<usize> = vec![];
+    let mut previous: Vec<Option<usize>> = vec![None; sequence.len()];
+
+    for (i, value) in sequence.iter().enumerate() {
+        let at = tails.partition_point(|&t| sequence[t] < *value);
+        previous[i] = if at > 0 { Some(tails[at - 1]) } else { None };
+
+        if at == tails.len() {
+            tails.push(i);
+        } else {
+            tails[at] = i;
+        }
+    }
+
+    let mut result = vec![];
+    let mut cursor = tails.last().copied();
+
+    while let Some(i) = cursor {
+        result.push(i);
+        cursor = previous[i];
+    }
+
+    result.reverse();
+    result
+}
+
+// numeric array attributes borrowed from the tree, compared element by element as `f64`
+#[derive(Debug, Clone, Copy)]
+enum Numbers<'a> {
+    Bool(&'a [bool]),
+    Int32(&'a [i32]),
+    Int64(&'a [i64]),
+    Float32(&'a [f32]),
+    Float64(&'a [f64]),
+}
+
+impl Numbers<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Numbers::Bool(v) => v.len(),
+            Numbers::Int32(v) => v.len(),
+            Numbers::Int64(v) => v.len(),
+            Numbers::Float32(v) => v.len(),
+            Numbers::Float64(v) => v.len(),
+        }
+    }
+
+    fn get(&self, index: usize) -> f64 {
+        match self {
+            Numbers::Bool(v) => if v[index] { 1.0 } else { 0.0 },
+            Numbers::Int32(v) => v[index] as f64,
+            Numbers::Int64(v) => v[index] as f64,
+            Numbers::Float32(v) => v[index] as f64,
+            Numbers::Float64(v) => v[index],
+        }
+    }
+}
+
+fn as_numbers(value: &Type) -> Option<Numbers<'_>> {
+    value
+        .as_bool_slice()
+        .map(Numbers::Bool)
+        .or_else(|| value.as_i32_slice().map(Numbers::Int32))
+        .or_else(|| value.as_i64_slice().map(Numbers::Int64))
+        .or_else(|| value.as_f32_slice().map(Numbers::Float32))
+        .or_else(|| value.as_f64_slice().map(Numbers::Float64))
+}
+
+fn compare_arrays(left: Numbers, right: Numbers, tolerance: f64) -> ArrayDiff {
+    let mut differing = left.len().abs_diff(right.len());
+    let mut max_delta: f64 = 0.0;
+
+    for i in 0..left.len().min(right.len()) {
+        let (l, r) = (left.get(i), right.get(i));
+        let delta = (l - r).abs();
+
+        if delta > tolerance || (delta.is_nan() && l.to_bits() != r.to_bits()) {
+            differing += 1;
+            max_delta = max_delta.max(delta);
+        }
+    }
+
+    ArrayDiff { left_len: left.len(), right_len: right.len(), differing, max_delta }
+}
+
+// offset of the first byte that differs, the shorter length when one is a prefix of the other
+fn first_difference(left: &[u8], right: &[u8]) -> Option<usize> {
+    match left.iter().zip(right.iter()).position(|(l, r)| l != r) {
+        Some(offset) => Some(offset),
+        None if left.len() != right.len() => Some(left.len().min(right.len())),
+        None => None,
+    }
+}
+
+fn scalars_equal(left: &Type, right: &Type, tolerance: f64) -> bool {
+    match (left, right) {
+        (Type::Bool(l), Type::Bool(r)) => l == r,
+        (Type::Int16(l), Type::Int16(r)) => l == r,
+        (Type::Int32(l), Type::Int32(r)) => l == r,
+        (Type::Int64(l), Type::Int64(r)) => l == r,
+        (Type::Float32(l), Type::Float32(r)) => l == r || ((l - r).abs() as f64) <= tolerance,
+        (Type::Float64(l), Type::Float64(r)) => l == r || (l - r).abs() <= tolerance,
+        (Type::String(l), Type::String(r)) => l == r,
+        _ => false,
+    }
+}
+
+struct Differ<'a> {
+    options: DiffOptions,
+    changes: Vec<Change>,
+    removed: Vec<(NodePath, &'a Node)>,
+    added: Vec<(NodePath, &'a Node)>,
+}
+
+impl<'a> Differ<'a> {
+    fn diff_attributes(&mut self, path: &NodePath, left: &[Type], right: &[Type]) {
+        for (index, (l, r)) in left.iter().zip(right.iter()).enumerate() {
+            if let (Some(l), Some(r)) = (l.as_binary_slice(), r.as_binary_slice()) {
+                if let Some(offset) = first_difference(l, r) {
+                    self.changes.push(Change::RawChanged { path: path.clone(), index, offset, left_len: l.len(), right_len: r.len() });
+                }
+                continue;
+            }
+
+            match (as_numbers(l), as_numbers(r)) {
+                (Some(l), Some(r)) => {
+                    let summary = compare_arrays(l, r, self.options.tolerance);
+                    if summary.differing > 0 {
+                        self.changes.push(Change::ArrayChanged { path: path.clone(), index, summary });
+                    }
+                }
+                _ => {
+                    if !scalars_equal(l, r, self.options.tolerance) {
+                        self.changes.push(Change::AttributeChanged { path: path.clone(), index, left: l.clone(), right: r.clone() });
+                    }
+                }
+            }
+        }
+
+        for (index, value) in left.iter().enumerate().skip(right.len()) {
+            self.changes.push(Change::AttributeRemoved { path: path.clone(), index, value: value.clone() });
+        }
+
+        for (index, value) in right.iter().enumerate().skip(left.len()) {
+            self.changes.push(Change::AttributeAdded { path: path.clone(), index, value: value.clone() });
+        }
+    }
+
+    fn diff_node(&mut self, left_path: &NodePath, left: &'a Node, right_path: &NodePath, right: &'a Node) {
+        self.diff_attributes(right_path, left.attributes_slice(), right.attributes_slice());
+        self.diff_children(left_path, left.children_slice(), right_path, right.children_slice());
+    }
+
+    fn diff_children(&mut self, left_path: &NodePath, left: &'a [Node], right_path: &NodePath, right: &'a [Node]) {
+        let left_occurrences = occurrences(left);
+        let right_occurrences = occurrences(right);
+        let left_segment = |i: usize| PathSegment::new(left[i].name(), i, left_occurrences[i]);
+        let right_segment = |i: usize| PathSegment::new(right[i].name(), i, right_occurrences[i]);
+
+        let mut left_positions: HashMap<(NodeKey, usize), usize> = HashMap::new();
+        for (i, key) in node_keys(left).into_iter().enumerate() {
+            left_positions.insert(key, i);
+        }
+
+        // pairs of (left position, right position) in right order
+        let mut pairs: Vec<(usize, usize)> = vec![];
+        let mut matched_left = vec![false; left.len()];
+
+        for (j, key) in node_keys(right).into_iter().enumerate() {
+            match left_positions.get(&key) {
+                Some(&i) => {
+                    pairs.push((i, j));
+                    matched_left[i] = true;
+                }
+                None => self.added.push((right_path.join(right_segment(j)), &right[j])),
+            }
+        }
+
+        for (i, matched) in matched_left.iter().enumerate() {
+            if !matched {
+                self.removed.push((left_path.join(left_segment(i)), &left[i]));
+            }
+        }
+
+        let sequence: Vec<usize> = pairs.iter().map(|(i, _)| *i).collect();
+        let mut in_order = vec![false; pairs.len()];
+        for k in longest_increasing_subsequence(&sequence) {
+            in_order[k] = true;
+        }
+
+        for (k, (i, j)) in pairs.into_iter().enumerate() {
+            let from = left_path.join(left_segment(i));
+            let to = right_path.join(right_segment(j));
+
+            if !in_order[k] {
+                self.changes.push(Change::NodeMoved { from: from.clone(), to: to.clone() });
+            }
+
+            self.diff_node(&from, &left[i], &to, &right[j]);
+        }
+    }
+
+    // nodes with an object ID that were removed in one place and added in another were moved
+    fn resolve_moves(&mut self) {
+        let mut removed_ids: HashMap<(&str, i64), usize> = HashMap::new();
+        for (k, (_, node)) in self.removed.iter().enumerate() {
+            if let Some(Type::Int64(id)) = node.attributes_slice().first() {
+                removed_ids.insert((node.name_str(), *id), k);
+            }
+        }
+
+        let mut moved_removed = vec![false; self.removed.len()];
+        let mut moved_added = vec![false; self.added.len()];
+        let mut moves: Vec<(usize, usize)> = vec![];
+
+        for (k, (_, node)) in self.added.iter().enumerate() {
+            if let Some(Type::Int64(id)) = node.attributes_slice().first() {
+                if let Some(&r) = removed_ids.get(&(node.name_str(), *id)) {
+                    if !moved_removed[r] {
+                        moved_removed[r] = true;
+                        moved_added[k] = true;
+                        moves.push((r, k));
+                    }
+                }
+            }
+        }
+
+        let removed = std::mem::take(&mut self.removed);
+        let added = std::mem::take(&mut self.added);
+
+        for (r, a) in moves {
+            let (from, left) = &removed[r];
+            let (to, right) = &added[a];
+            self.changes.push(Change::NodeMoved { from: from.clone(), to: to.clone() });
+            self.diff_node(from, left, to, right);
+        }
+
+        for (k, (path, _)) in removed.into_iter().enumerate() {
+            if !moved_removed[k] {
+                self.changes.push(Change::NodeRemoved { path });
+            }
+        }
+
+        for (k, (path, _)) in added.into_iter().enumerate() {
+            if !moved_added[k] {
+                self.changes.push(Change::NodeAdded { path });
+            }
+        }
+    }
+}
+
+pub fn diff(left: &Object, right: &Object, options: &DiffOptions) -> Diff {
+    diff_nodes(left.children_slice(), right.children_slice(), options)
+}
+
+pub fn diff_nodes(left: &[Node], right: &[Node], options: &DiffOptions) -> Diff {
+    let mut differ = Differ { options: *options, changes: vec![], removed: vec![], added: vec![] };
+    let root = NodePath::new();
+
+    differ.diff_children(&root, left, &root, right);
+
+    // moved subtrees are diffed once more and may report further moves of their own
+    while !differ.removed.is_empty() || !differ.added.is_empty() {
+        differ.resolve_moves();
+    }
+
+    Diff { changes: differ.changes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, attributes: Vec<Type>, children: Vec<Node>) -> Node {
+        Node::new(name.to_owned(), attributes, children)
+    }
+
+    fn model(id: i64) -> Node {
+        node("Model", vec![Type::Int64(id)], vec![])
+    }
+
+    fn report(left: &[Node], right: &[Node]) -> Vec<String> {
+        diff_nodes(left, right, &DiffOptions::default()).changes().iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn reordered_siblings_are_moves() {
+        let left = [node("Objects", vec![], vec![model(1), model(2), model(3)])];
+        let right = [node("Objects", vec![], vec![model(2), model(3), model(1)])];
+
+        assert_eq!(report(&left, &right), ["moved: Objects/Model -> Objects/Model[2]"]);
+    }
+
+    #[test]
+    fn nodes_with_an_id_move_between_parents() {
+        let left = [node("A", vec![], vec![node("Model", vec![Type::Int64(7)], vec![node("Version", vec![Type::Int32(1)], vec![])])]), node("B", vec![], vec![])];
+        let right = [node("A", vec![], vec![]), node("B", vec![], vec![node("Model", vec![Type::Int64(7)], vec![node("Version", vec![Type::Int32(2)], vec![])])])];
+
+        assert_eq!(report(&left, &right), ["moved: A/Model -> B/Model", "changed: B/Model/Version: 1 -> 2"]);
+    }
+
+    #[test]
+    fn nodes_without_an_id_are_removed_and_added() {
+        let left = [node("A", vec![], vec![node("Name", vec![], vec![])]), node("B", vec![], vec![])];
+        let right = [node("A", vec![], vec![]), node("B", vec![], vec![node("Name", vec![], vec![])])];
+
+        assert_eq!(report(&left, &right), ["removed: A/Name", "added: B/Name"]);
+    }
+
+    #[test]
+    fn arrays_compare_across_types_within_tolerance() {
+        let left = [node("V", vec![Type::VecFloat32(vec![0.5, 1.0, 2.0])], vec![])];
+        let right = [node("V", vec![Type::VecFloat64(vec![0.5, 1.0000000001, 2.5, 3.0])], vec![])];
+
+        assert_eq!(report(&left, &right), ["changed: V: 2 of 4 values differ, max delta 5e-1 (length 3 -> 4)"]);
+    }
+
+    #[test]
+    fn raw_bytes_compare_exactly() {
+        let left = [node("R", vec![Type::VecRaw(vec![1, 2, 3])], vec![])];
+        let same = [node("R", vec![Type::VecRaw(vec![1, 2, 3])], vec![])];
+        let right = [node("R", vec![Type::VecRaw(vec![1, 9, 3, 4])], vec![])];
+
+        assert!(report(&left, &same).is_empty());
+        assert_eq!(report(&left, &right), ["changed: R: bytes differ from offset 1 (length 3 -> 4)"]);
+    }
+}
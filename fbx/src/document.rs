@@ -0,0 +1,433 @@
+use crate::error::{DocumentError, Result};
+use crate::format::{Node, NodeSpan, Object, Type, Version};
+use crate::visitor::{NodePath, PathSegment};
+
+/// Cheap handle to a node stored in a `Document`.
+///
+/// Handles stay valid for the lifetime of the document, also after the node has been detached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    attributes: Vec<Type>,
+    span: Option<NodeSpan>,
+    parent: Option<NodeId>,
+    first_child: Option<NodeId>,
+    last_child: Option<NodeId>,
+    prev_sibling: Option<NodeId>,
+    next_sibling: Option<NodeId>,
+    attached: bool,
+}
+
+/// Arena based representation of an FBX tree with parent and sibling links.
+#[derive(Debug, Clone)]
+pub struct Document {
+    version: Version,
+    footer: Option<Vec<u8>>,
+    entries: Vec<Entry>,
+    first_root: Option<NodeId>,
+    last_root: Option<NodeId>,
+}
+
+impl Document {
+    pub fn new(version: Version) -> Self {
+        Document { version, footer: None, entries: vec![], first_root: None, last_root: None }
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn footer(&self) -> Option<Vec<u8>> {
+        self.footer.clone()
+    }
+
+    // number of nodes in the arena, including detached ones
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn entry(&self, id: NodeId) -> &Entry {
+        &self.entries[id.0]
+    }
+
+    fn entry_mut(&mut self, id: NodeId) -> &mut Entry {
+        &mut self.entries[id.0]
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.entry(id).name
+    }
+
+    pub fn set_name(&mut self, id: NodeId, name: String) {
+        self.entry_mut(id).name = name;
+    }
+
+    pub fn attributes(&self, id: NodeId) -> &[Type] {
+        &self.entry(id).attributes
+    }
+
+    pub fn attributes_mut(&mut self, id: NodeId) -> &mut Vec<Type> {
+        &mut self.entry_mut(id).attributes
+    }
+
+    // see `Node::span`, binary files only
+    pub fn span(&self, id: NodeId) -> Option<&NodeSpan> {
+        self.entry(id).span.as_ref()
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.entry(id).parent
+    }
+
+    pub fn first_child(&self, id: NodeId) -> Option<NodeId> {
+        self.entry(id).first_child
+    }
+
+    pub fn last_child(&self, id: NodeId) -> Option<NodeId> {
+        self.entry(id).last_child
+    }
+
+    pub fn next_sibling(&self, id: NodeId) -> Option<NodeId> {
+        self.entry(id).next_sibling
+    }
+
+    pub fn prev_sibling(&self, id: NodeId) -> Option<NodeId> {
+        self.entry(id).prev_sibling
+    }
+
+    pub fn is_attached(&self, id: NodeId) -> bool {
+        self.entry(id).attached
+    }
+
+    pub fn roots(&self) -> Siblings<'_> {
+        Siblings { document: self, next: self.first_root }
+    }
+
+    pub fn children(&self, id: NodeId) -> Siblings<'_> {
+        Siblings { document: self, next: self.entry(id).first_child }
+    }
+
+    pub fn ancestors(&self, id: NodeId) -> Ancestors<'_> {
+        Ancestors { document: self, next: self.entry(id).parent }
+    }
+
+    // depth-first, pre-order traversal of the subtree below `id` (excluding `id` itself)
+    pub fn descendants(&self, id: NodeId) -> Descendants<'_> {
+        Descendants { document: self, root: id, next: self.entry(id).first_child }
+    }
+
+    pub fn find_root(&self, name: &str) -> Option<NodeId> {
+        self.roots().find(|w| self.name(*w) == name)
+    }
+
+    pub fn find_child(&self, id: NodeId, name: &str) -> Option<NodeId> {
+        self.children(id).find(|w| self.name(*w) == name)
+    }
+
+    pub fn depth(&self, id: NodeId) -> usize {
+        self.ancestors(id).count()
+    }
+
+    pub fn path(&self, id: NodeId) -> NodePath {
+        let mut ids: Vec<NodeId> = self.ancestors(id).collect();
+        ids.reverse();
+        ids.push(id);
+
+        let mut path = NodePath::new();
+        for id in ids {
+            let (mut position, mut occurrence) = (0, 0);
+            let mut cursor = self.prev_sibling(id);
+
+            while let Some(sibling) = cursor {
+                position += 1;
+                if self.name(sibling) == self.name(id) {
+                    occurrence += 1;
+                }
+                cursor = self.prev_sibling(sibling);
+            }
+
+            path.push(PathSegment::new(self.name(id).to_owned(), position, occurrence));
+        }
+
+        path
+    }
+
+    fn allocate(&mut self, name: String, attributes: Vec<Type>) -> NodeId {
+        let id = NodeId(self.entries.len());
+        self.entries.push(Entry {
+            name,
+            attributes,
+            span: None,
+            parent: None,
+            first_child: None,
+            last_child: None,
+            prev_sibling: None,
+            next_sibling: None,
+            attached: false,
+        });
+
+        id
+    }
+
+    // appends a new node as the last child of `parent`, or as the last root node if `parent` is `None`
+    pub fn append(&mut self, parent: Option<NodeId>, name: String, attributes: Vec<Type>) -> NodeId {
+        let id = self.allocate(name, attributes);
+        self.link_last(parent, id);
+        id
+    }
+
+    // moves an existing (possibly detached) node to the end of `parent`'s children, fails when `parent` is
+    // the node itself or one of its descendants and leaves the document unchanged
+    pub fn append_existing(&mut self, parent: Option<NodeId>, id: NodeId) -> Result<()> {
+        if parent.is_some_and(|p| p == id || self.ancestors(p).any(|w| w == id)) {
+            return Err(DocumentError::AttachBelowItself(id.0).into());
+        }
+
+        self.detach(id);
+        self.link_last(parent, id);
+        Ok(())
+    }
+
+    fn link_last(&mut self, parent: Option<NodeId>, id: NodeId) {
+        let last = match parent {
+            Some(p) => self.entry(p).last_child,
+            None => self.last_root,
+        };
+
+        let attached = match parent {
+            Some(p) => self.entry(p).attached,
+            None => true,
+        };
+
+        {
+            let entry = self.entry_mut(id);
+            entry.parent = parent;
+            entry.prev_sibling = last;
+            entry.next_sibling = None;
+        }
+
+        match last {
+            Some(l) => self.entry_mut(l).next_sibling = Some(id),
+            None => match parent {
+                Some(p) => self.entry_mut(p).first_child = Some(id),
+                None => self.first_root = Some(id),
+            },
+        }
+
+        match parent {
+            Some(p) => self.entry_mut(p).last_child = Some(id),
+            None => self.last_root = Some(id),
+        }
+
+        self.set_attached(id, attached);
+    }
+
+    // unlinks the node (and its subtree) from the tree, the handle stays valid
+    pub fn detach(&mut self, id: NodeId) {
+        if !self.is_attached(id) && self.parent(id).is_none() && self.prev_sibling(id).is_none() && self.next_sibling(id).is_none() {
+            return;
+        }
+
+        let (parent, prev_sibling, next_sibling) = (self.parent(id), self.prev_sibling(id), self.next_sibling(id));
+
+        match prev_sibling {
+            Some(prev) => self.entry_mut(prev).next_sibling = next_sibling,
+            None => match parent {
+                Some(p) => self.entry_mut(p).first_child = next_sibling,
+                None if self.first_root == Some(id) => self.first_root = next_sibling,
+                None => {}
+            },
+        }
+
+        match next_sibling {
+            Some(next) => self.entry_mut(next).prev_sibling = prev_sibling,
+            None => match parent {
+                Some(p) => self.entry_mut(p).last_child = prev_sibling,
+                None if self.last_root == Some(id) => self.last_root = prev_sibling,
+                None => {}
+            },
+        }
+
+        let entry = self.entry_mut(id);
+        entry.parent = None;
+        entry.prev_sibling = None;
+        entry.next_sibling = None;
+
+        self.set_attached(id, false);
+    }
+
+    fn set_attached(&mut self, id: NodeId, attached: bool) {
+        let mut stack = vec![id];
+
+        while let Some(id) = stack.pop() {
+            self.entry_mut(id).attached = attached;
+            stack.extend(self.children(id));
+        }
+    }
+
+    fn insert_node(&mut self, parent: Option<NodeId>, node: &Node) -> NodeId {
+        let id = self.append(parent, node.name(), node.attributes());
+        self.entry_mut(id).span = node.span().cloned();
+
+        for child in node.children_slice() {
+            self.insert_node(Some(id), child);
+        }
+
+        id
+    }
+
+    pub fn to_node(&self, id: NodeId) -> Node {
+        let children = self.children(id).map(|w| self.to_node(w)).collect();
+        Node::new(self.name(id).to_owned(), self.attributes(id).to_vec(), children).with_span(self.entry(id).span.clone())
+    }
+
+    pub fn to_object(&self) -> Object {
+        let children = self.roots().map(|w| self.to_node(w)).collect();
+        Object::new(self.version, children, self.footer.clone())
+    }
+}
+
+impl From<&Object> for Document {
+    fn from(object: &Object) -> Self {
+        let mut document = Document::new(object.version());
+        document.footer = object.footer();
+
+        for node in object.children_slice() {
+            document.insert_node(None, node);
+        }
+
+        document
+    }
+}
+
+impl From<&Document> for Object {
+    fn from(document: &Document) -> Self {
+        document.to_object()
+    }
+}
+
+pub struct Siblings<'a> {
+    document: &'a Document,
+    next: Option<NodeId>,
+}
+
+impl Iterator for Siblings<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.next = self.document.next_sibling(current);
+        Some(current)
+    }
+}
+
+pub struct Ancestors<'a> {
+    document: &'a Document,
+    next: Option<NodeId>,
+}
+
+impl Iterator for Ancestors<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.next = self.document.parent(current);
+        Some(current)
+    }
+}
+
+pub struct Descendants<'a> {
+    document: &'a Document,
+    root: NodeId,
+    next: Option<NodeId>,
+}
+
+impl Iterator for Descendants<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+
+        self.next = match self.document.first_child(current) {
+            Some(child) => Some(child),
+            None => {
+                let mut cursor = Some(current);
+                let mut next = None;
+
+                while let Some(node) = cursor {
+                    if node == self.root {
+                        break;
+                    }
+
+                    if let Some(sibling) = self.document.next_sibling(node) {
+                        next = Some(sibling);
+                        break;
+                    }
+
+                    cursor = self.document.parent(node);
+                }
+
+                next
+            }
+        };
+
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(document: &Document, ids: impl Iterator<Item = NodeId>) -> Vec<String> {
+        ids.map(|w| document.name(w).to_owned()).collect()
+    }
+
+    #[test]
+    fn append_existing_moves_subtrees() {
+        let mut document = Document::new(Version::from(7, 4));
+        let a = document.append(None, "A".to_owned(), vec![]);
+        let b = document.append(None, "B".to_owned(), vec![]);
+        let c = document.append(Some(a), "C".to_owned(), vec![]);
+        document.append(Some(c), "D".to_owned(), vec![]);
+
+        document.append_existing(Some(b), c).unwrap();
+
+        assert_eq!(names(&document, document.children(a)), Vec::<String>::new());
+        assert_eq!(names(&document, document.descendants(b)), ["C", "D"]);
+        assert_eq!(document.path(c).to_string(), "B/C");
+    }
+
+    #[test]
+    fn append_existing_rejects_cycles() {
+        let mut document = Document::new(Version::from(7, 4));
+        let a = document.append(None, "A".to_owned(), vec![]);
+        let b = document.append(Some(a), "B".to_owned(), vec![]);
+
+        assert!(document.append_existing(Some(b), a).is_err());
+        assert!(document.append_existing(Some(a), a).is_err());
+        assert_eq!(document.parent(b), Some(a));
+        assert!(document.is_attached(a) && document.is_attached(b));
+    }
+
+    #[test]
+    fn round_trips_objects() {
+        let object = Object::new(Version::from(7, 4), vec![Node::new("A".to_owned(), vec![Type::Int32(1)], vec![Node::new("B".to_owned(), vec![], vec![])])], None);
+        let back = Document::from(&object).to_object();
+
+        assert!(crate::diff::diff(&object, &back, &Default::default()).is_empty());
+    }
+}
@@ -25,5 +25,23 @@ pub enum ReadError {
 }
 
 #[derive(Error, Debug)]
-pub enum WriteError {}
+pub enum WriteError {
+    #[error("failed to create the file: `{0}`")]
+    FailedToCreateFile(String),
+
+    #[error("node name is longer than 255 bytes: `{0}`")]
+    NameTooLong(String),
+}
+
+#[derive(Error, Debug)]
+pub enum DocumentError {
+    #[error("cannot attach node `{0}` below itself")]
+    AttachBelowItself(usize),
+}
+
+#[derive(Error, Debug)]
+pub enum ConversionError {
+    #[error("cannot convert attribute of type `{0}` into `{1}`")]
+    IncompatibleType(char, &'static str),
+}
 
@@ -1,6 +1,6 @@
 pub(crate) use ascii::AsciiFBX;
-pub use base::{Attribute, BaseFBXReader, BaseFBXWriter, Node, Object, Type};
-pub(crate) use binary::BinaryFBX;
+pub use base::{Attribute, AttributeSpan, BaseFBXReader, BaseFBXWriter, Node, NodeSpan, Object, ReadOptions, Type, Version};
+pub(crate) use binary::{BinaryFBX, BinaryFBXWriter};
 
 mod ascii;
 mod base;
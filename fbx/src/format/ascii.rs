@@ -4,6 +4,8 @@ use crate::error::Result;
 use crate::format::{base, Object};
 
 pub struct AsciiFBX {
+    // unused until the ASCII parser exists, `read` is still a `todo!()`
+    #[allow(dead_code)]
     reader: BufReader<File>,
 }
 
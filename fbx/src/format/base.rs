@@ -1,45 +1,30 @@
-use std::cmp::Ordering;
-use std::fmt::Debug;
-use std::mem::transmute;
+use std::fmt::{Debug, Display, Formatter};
 
-use crate::error::Result;
+use crate::error::{ConversionError, Result};
 
-#[derive(Debug, Eq, PartialEq, Ord, Clone, Copy)]
+// ordered by major, then minor version
+#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Clone, Copy)]
 pub struct Version {
     major: u16,
     minor: u16,
 }
 
-impl PartialOrd<Self> for Version {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        if self.major > other.major {
-            return Some(Ordering::Greater);
-        }
-
-        if self.major < other.major {
-            return Some(Ordering::Less);
-        }
-
-        if self.minor > other.minor {
-            return Some(Ordering::Greater);
-        }
-
-        if self.minor < other.minor {
-            return Some(Ordering::Less);
-        }
-
-        return Some(Ordering::Equal);
-    }
-}
-
 impl Version {
     pub fn from(major: u16, minor: u16) -> Self {
         Version { major, minor }
     }
 
+    pub fn major(&self) -> u16 {
+        self.major
+    }
+
+    pub fn minor(&self) -> u16 {
+        self.minor
+    }
+
     pub fn to_u8_le(self) -> [u8; 4] {
         let version: u32 = ((self.major as u32) * 1000) + ((self.minor as u32) * 100);
-        unsafe { transmute::<u32, [u8; 4]>(version.to_le()) }
+        version.to_le_bytes()
     }
 }
 
@@ -47,11 +32,13 @@ pub trait BaseFBXReader: Debug {
     fn read(&mut self) -> Result<Object>;
 }
 
-pub trait BaseFBXWriter: Debug {}
+pub trait BaseFBXWriter: Debug {
+    fn write(&mut self, object: &Object) -> Result<()>;
+}
 
 pub trait Attribute: Debug {}
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Object {
     version: Version,
     children: Vec<Node>,
@@ -63,13 +50,104 @@ impl Object {
         Object { version, children, footer }
     }
 
-    pub fn version(self) -> Version {
+    pub fn version(&self) -> Version {
         self.version
     }
 
+    pub fn footer(&self) -> Option<Vec<u8>> {
+        self.footer.clone()
+    }
+
     pub fn children(&self) -> Vec<Node> {
         self.children.clone()
     }
+
+    pub fn children_slice(&self) -> &[Node] {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut Vec<Node> {
+        &mut self.children
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReadOptions {
+    // binary files only, the ASCII reader ignores it
+    pub record_spans: bool,
+}
+
+/// Location of an attribute in the source file, starting at its type code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeSpan {
+    start: u64,
+    end: u64,
+    compressed_size: Option<u64>,
+}
+
+impl AttributeSpan {
+    pub fn new(start: u64, end: u64, compressed_size: Option<u64>) -> Self {
+        AttributeSpan { start, end, compressed_size }
+    }
+
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.compressed_size.is_some()
+    }
+
+    // size of the zlib payload for compressed arrays
+    pub fn compressed_size(&self) -> Option<u64> {
+        self.compressed_size
+    }
+}
+
+/// Location of a node record in the source file, including its nested children.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeSpan {
+    start: u64,
+    end: u64,
+    attributes: Vec<AttributeSpan>,
+}
+
+impl NodeSpan {
+    pub fn new(start: u64, end: u64, attributes: Vec<AttributeSpan>) -> Self {
+        NodeSpan { start, end, attributes }
+    }
+
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn attributes(&self) -> &[AttributeSpan] {
+        &self.attributes
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -77,11 +155,22 @@ pub struct Node {
     name: String,
     attributes: Vec<Type>,
     children: Vec<Node>,
+    span: Option<NodeSpan>,
 }
 
 impl Node {
     pub fn new(name: String, attributes: Vec<Type>, children: Vec<Node>) -> Self {
-        Node { name, attributes, children }
+        Node { name, attributes, children, span: None }
+    }
+
+    pub fn with_span(mut self, span: Option<NodeSpan>) -> Self {
+        self.span = span;
+        self
+    }
+
+    // only recorded when reading a binary file with `ReadOptions::record_spans`, always `None` for ASCII files
+    pub fn span(&self) -> Option<&NodeSpan> {
+        self.span.as_ref()
     }
 
     pub fn name(&self) -> String {
@@ -89,11 +178,35 @@ impl Node {
     }
 
     pub fn attributes(&self) -> Vec<Type> {
-        return self.attributes.clone();
+        self.attributes.clone()
     }
 
     pub fn children(&self) -> Vec<Node> {
-        return self.children.clone();
+        self.children.clone()
+    }
+
+    pub fn name_str(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn attributes_slice(&self) -> &[Type] {
+        &self.attributes
+    }
+
+    pub fn attributes_mut(&mut self) -> &mut Vec<Type> {
+        &mut self.attributes
+    }
+
+    pub fn children_slice(&self) -> &[Node] {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut Vec<Node> {
+        &mut self.children
     }
 }
 
@@ -199,4 +312,251 @@ impl Type {
             _ => None
         }
     }
-}
\ No newline at end of file
+
+    pub fn as_bool_array(&self) -> Option<Vec<bool>> {
+        match self {
+            Type::VecBool(b) => Some(b.to_owned()),
+            _ => None
+        }
+    }
+
+    pub fn as_bool_slice(&self) -> Option<&[bool]> {
+        match self {
+            Type::VecBool(b) => Some(b),
+            _ => None
+        }
+    }
+
+    pub fn as_i32_slice(&self) -> Option<&[i32]> {
+        match self {
+            Type::VecInt32(i) => Some(i),
+            _ => None
+        }
+    }
+
+    pub fn as_i64_slice(&self) -> Option<&[i64]> {
+        match self {
+            Type::VecInt64(i) => Some(i),
+            _ => None
+        }
+    }
+
+    pub fn as_f32_slice(&self) -> Option<&[f32]> {
+        match self {
+            Type::VecFloat32(f) => Some(f),
+            _ => None
+        }
+    }
+
+    pub fn as_f64_slice(&self) -> Option<&[f64]> {
+        match self {
+            Type::VecFloat64(f) => Some(f),
+            _ => None
+        }
+    }
+
+    pub fn as_binary_slice(&self) -> Option<&[u8]> {
+        match self {
+            Type::VecRaw(b) => Some(b),
+            _ => None
+        }
+    }
+
+    pub fn as_str_ref(&self) -> Option<&str> {
+        match self {
+            Type::String(s) => Some(s),
+            _ => None
+        }
+    }
+
+    // lossless widening helpers, e.g. an `Int32` property read as `i64`
+
+    pub fn to_i32(&self) -> Option<i32> {
+        match self {
+            Type::Int16(i) => Some(*i as i32),
+            Type::Int32(i) => Some(*i),
+            _ => None
+        }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        match self {
+            Type::Int16(i) => Some(*i as i64),
+            Type::Int32(i) => Some(*i as i64),
+            Type::Int64(i) => Some(*i),
+            _ => None
+        }
+    }
+
+    pub fn to_f64(&self) -> Option<f64> {
+        match self {
+            Type::Int16(i) => Some(*i as f64),
+            Type::Int32(i) => Some(*i as f64),
+            Type::Float32(f) => Some(*f as f64),
+            Type::Float64(f) => Some(*f),
+            _ => None
+        }
+    }
+
+    pub fn to_i64_vec(&self) -> Option<Vec<i64>> {
+        match self {
+            Type::VecInt32(i) => Some(i.iter().map(|w| *w as i64).collect()),
+            Type::VecInt64(i) => Some(i.to_owned()),
+            _ => None
+        }
+    }
+
+    pub fn to_f64_vec(&self) -> Option<Vec<f64>> {
+        match self {
+            Type::VecInt32(i) => Some(i.iter().map(|w| *w as f64).collect()),
+            Type::VecFloat32(f) => Some(f.iter().map(|w| *w as f64).collect()),
+            Type::VecFloat64(f) => Some(f.to_owned()),
+            _ => None
+        }
+    }
+
+    // the binary FBX type code of the attribute
+    pub fn type_code(&self) -> char {
+        match self {
+            Type::Bool(_) => 'C',
+            Type::Int16(_) => 'Y',
+            Type::Int32(_) => 'I',
+            Type::Int64(_) => 'L',
+            Type::Float32(_) => 'F',
+            Type::Float64(_) => 'D',
+            Type::VecBool(_) => 'b',
+            Type::VecInt32(_) => 'i',
+            Type::VecInt64(_) => 'l',
+            Type::VecFloat32(_) => 'f',
+            Type::VecFloat64(_) => 'd',
+            Type::VecRaw(_) => 'R',
+            Type::String(_) => 'S',
+        }
+    }
+}
+
+macro_rules! impl_try_from_type {
+    ($target:ty, $name:literal, $convert:expr) => {
+        impl TryFrom<&Type> for $target {
+            type Error = ConversionError;
+
+            fn try_from(value: &Type) -> std::result::Result<Self, Self::Error> {
+                let convert: fn(&Type) -> Option<$target> = $convert;
+                convert(value).ok_or(ConversionError::IncompatibleType(value.type_code(), $name))
+            }
+        }
+    };
+}
+
+impl_try_from_type!(bool, "bool", Type::as_bool);
+impl_try_from_type!(i16, "i16", Type::as_int16);
+impl_try_from_type!(i32, "i32", Type::to_i32);
+impl_try_from_type!(i64, "i64", Type::to_i64);
+impl_try_from_type!(f32, "f32", Type::as_float);
+impl_try_from_type!(f64, "f64", Type::to_f64);
+impl_try_from_type!(String, "String", Type::as_str);
+impl_try_from_type!(Vec<bool>, "Vec<bool>", Type::as_bool_array);
+impl_try_from_type!(Vec<i32>, "Vec<i32>", Type::as_int32_array);
+impl_try_from_type!(Vec<i64>, "Vec<i64>", Type::to_i64_vec);
+impl_try_from_type!(Vec<f32>, "Vec<f32>", Type::as_float_array);
+impl_try_from_type!(Vec<f64>, "Vec<f64>", Type::to_f64_vec);
+impl_try_from_type!(Vec<u8>, "Vec<u8>", Type::as_binary);
+
+// `value` is formatted in its own precision, so an `f32` prints the shortest literal that reads back as the same `f32`
+fn write_float<T: Copy + Display + std::fmt::LowerExp + Into<f64>>(f: &mut Formatter<'_>, value: T) -> std::fmt::Result {
+    let wide: f64 = value.into();
+
+    if wide != 0.0 && wide.is_finite() && (wide.abs() < 1e-5 || wide.abs() >= 1e16) {
+        write!(f, "{:e}", value)
+    } else {
+        write!(f, "{}", value)
+    }
+}
+
+fn write_array<T>(f: &mut Formatter<'_>, values: &[T], write: impl Fn(&mut Formatter<'_>, &T) -> std::fmt::Result) -> std::fmt::Result {
+    write!(f, "*{} {{ a: ", values.len())?;
+
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            write!(f, ",")?;
+        }
+
+        write(f, value)?;
+    }
+
+    write!(f, " }}")
+}
+
+fn write_base64(f: &mut Formatter<'_>, bytes: &[u8]) -> std::fmt::Result {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | (b[2] as u32);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                write!(f, "{}", TABLE[((n >> (18 - i * 6)) & 0x3f) as usize] as char)?;
+            } else {
+                write!(f, "=")?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// formats the attribute as it appears in ASCII FBX
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Bool(b) => write!(f, "{}", if *b { "T" } else { "F" }),
+            Type::Int16(i) => write!(f, "{}", i),
+            Type::Int32(i) => write!(f, "{}", i),
+            Type::Int64(i) => write!(f, "{}", i),
+            Type::Float32(v) => write_float(f, *v),
+            Type::Float64(v) => write_float(f, *v),
+            Type::VecBool(v) => write_array(f, v, |f, w| write!(f, "{}", *w as u8)),
+            Type::VecInt32(v) => write_array(f, v, |f, w| write!(f, "{}", w)),
+            Type::VecInt64(v) => write_array(f, v, |f, w| write!(f, "{}", w)),
+            Type::VecFloat32(v) => write_array(f, v, |f, w| write_float(f, *w)),
+            Type::VecFloat64(v) => write_array(f, v, |f, w| write_float(f, *w)),
+            Type::VecRaw(v) => {
+                write!(f, "\"")?;
+                write_base64(f, v)?;
+                write!(f, "\"")
+            }
+            Type::String(s) => {
+                // binary "Name\x00\x01Class" is written as "Class::Name" in ASCII
+                let s = match s.split_once("\x00\x01") {
+                    Some((name, class)) => format!("{}::{}", class, name),
+                    None => s.to_owned(),
+                };
+
+                write!(f, "\"{}\"", s.replace('"', "&quot;"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floats_display_in_their_own_precision() {
+        assert_eq!(Type::Float32(0.1).to_string(), "0.1");
+        assert_eq!(Type::Float64(0.1).to_string(), "0.1");
+        assert_eq!(Type::Float32(1e-7).to_string(), "1e-7");
+        assert_eq!(Type::VecFloat32(vec![0.1, 2.5]).to_string(), "*2 { a: 0.1,2.5 }");
+    }
+
+    #[test]
+    fn widening_conversions() {
+        assert_eq!(Type::Int16(-3).to_i64(), Some(-3));
+        assert_eq!(Type::Float32(0.5).to_f64(), Some(0.5));
+        assert_eq!(Type::VecInt32(vec![1, 2]).to_f64_vec(), Some(vec![1.0, 2.0]));
+        assert_eq!(Type::String("a".to_owned()).to_f64(), None);
+        assert!(f64::try_from(&Type::Bool(true)).is_err());
+    }
+}
@@ -1,13 +1,15 @@
 use std::{fmt::Debug, fs::File, io::BufReader};
-use std::io::Cursor;
+use std::io::{BufWriter, Cursor};
 use std::io::prelude::*;
 
-use flate2::read::GzDecoder;
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
 
-use crate::binary::BinaryReader;
-use crate::error::{ReadError, Result};
-use crate::format::{BaseFBXReader, Object, Type};
-use crate::format::base::{Node, Version};
+use crate::binary::{BinaryReader, BinaryWriter};
+use crate::error::{ReadError, Result, WriteError};
+use crate::format::{BaseFBXReader, BaseFBXWriter, Object, Type};
+use crate::format::base::{AttributeSpan, Node, NodeSpan, ReadOptions, Version};
 use crate::format::Type::{Bool, Float32, Float64, Int16, Int32, Int64, String, VecBool, VecFloat32, VecFloat64, VecInt32, VecInt64, VecRaw};
 
 const FBX_FOOTER_MAGIC_BYTES_2: [u8; 4] = [
@@ -27,19 +29,31 @@ const FBX_FOOTER_MAGIC_BYTES_4: [u8; 16] = [
     0xf8, 0x5a, 0x8c, 0x6a, 0xde, 0xf5, 0xd9, 0x7e, 0xec, 0xe9, 0x0c, 0xe3, 0x75, 0x8f, 0x29, 0x0b
 ];
 
+// the 4 zero bytes after the footer id plus up to 16 bytes of alignment, as Blender writes them
+const FBX_FOOTER_MAX_PADDING: usize = 16 + 4;
+
+// footer id written by Blender, used when the object was not read from a file
+const FBX_FOOTER_ID: [u8; 16] = [
+    0xfa, 0xbc, 0xab, 0x09, 0xd0, 0xc8, 0xd4, 0x66, 0xb1, 0x76, 0xfb, 0x83, 0x1c, 0xf7, 0x26, 0x7e
+];
+
+// arrays larger than this many bytes are written zlib compressed
+const ARRAY_COMPRESSION_THRESHOLD: usize = 128;
+
 fn times(len: usize) -> impl Iterator {
-    std::iter::repeat(()).take(len)
+    std::iter::repeat_n((), len)
 }
 
 pub struct BinaryFBX {
     reader: BinaryReader,
     version: Option<Version>,
     children: Option<Vec<Node>>,
+    options: ReadOptions,
 }
 
 impl BinaryFBX {
-    pub fn new(reader: BufReader<File>) -> Self {
-        BinaryFBX { reader: BinaryReader::new(Box::new(reader), 23), version: None, children: None }
+    pub fn with_options(reader: BufReader<File>, options: ReadOptions) -> Self {
+        BinaryFBX { reader: BinaryReader::new(Box::new(reader), 23), version: None, children: None, options }
     }
 
     fn is_new_format(&self) -> bool {
@@ -61,7 +75,8 @@ impl BinaryFBX {
         Version::from(major as u16, minor as u16)
     }
 
-    fn read_vector<T>(&mut self, reader: impl Fn(&mut BinaryReader) -> T) -> Vec<T> {
+    // returns the values and, for compressed arrays, the size of the compressed payload
+    fn read_vector<T>(&mut self, reader: impl Fn(&mut BinaryReader) -> T) -> (Vec<T>, Option<u64>) {
         let length = self.reader.read_u32_le();
         let encoding = self.reader.read_u32_le();
         let bytes_length = self.reader.read_u32_le();
@@ -75,21 +90,24 @@ impl BinaryFBX {
         } else if encoding == 1 {
             // zlib compressed
             let v = self.reader.read_bytes_exact(bytes_length as usize);
-            let decoder = GzDecoder::new(Cursor::new(v));
+            let decoder = ZlibDecoder::new(Cursor::new(v));
             let mut br = BinaryReader::new(Box::new(decoder), 0);
 
             for _ in times(length as usize) {
                 vec.push(reader(&mut br))
             }
+
+            return (vec, Some(bytes_length as u64));
         }
 
-        vec
+        (vec, None)
     }
 
     fn read_nodes(&mut self) -> Vec<Node> {
         let mut vec: Vec<Node> = vec![];
 
         loop {
+            let start = self.reader.current_cursor() as u64;
             let offset: u64 = if self.is_new_format() { self.reader.read_u64_le() } else { self.reader.read_u32_le() as u64 };
             let attribute_length: u64 = if self.is_new_format() { self.reader.read_u64_le() } else { self.reader.read_u32_le() as u64 };
             let total_bytes: u64 = if self.is_new_format() { self.reader.read_u64_le() } else { self.reader.read_u32_le() as u64 };
@@ -101,8 +119,11 @@ impl BinaryFBX {
 
             let name = self.reader.read_string(name_length as usize);
             let mut attributes: Vec<Type> = vec![];
+            let mut attribute_spans: Vec<AttributeSpan> = vec![];
 
             for _ in times(attribute_length as usize) {
+                let attribute_start = self.reader.current_cursor() as u64;
+                let mut compressed_size: Option<u64> = None;
                 let c = self.reader.read_char();
                 let t = match c {
                     'C' => Ok(Bool(self.reader.read_boolean())),
@@ -111,11 +132,31 @@ impl BinaryFBX {
                     'L' => Ok(Int64(self.reader.read_i64_le())),
                     'F' => Ok(Float32(self.reader.read_f32_le())),
                     'D' => Ok(Float64(self.reader.read_f64_le())),
-                    'b' => Ok(VecBool(self.read_vector(|w| w.read_boolean()))),
-                    'i' => Ok(VecInt32(self.read_vector(|w| w.read_i32_le()))),
-                    'l' => Ok(VecInt64(self.read_vector(|w| w.read_i64_le()))),
-                    'f' => Ok(VecFloat32(self.read_vector(|w| w.read_f32_le()))),
-                    'd' => Ok(VecFloat64(self.read_vector(|w| w.read_f64_le()))),
+                    'b' => {
+                        let (vec, size) = self.read_vector(|w| w.read_boolean());
+                        compressed_size = size;
+                        Ok(VecBool(vec))
+                    }
+                    'i' => {
+                        let (vec, size) = self.read_vector(|w| w.read_i32_le());
+                        compressed_size = size;
+                        Ok(VecInt32(vec))
+                    }
+                    'l' => {
+                        let (vec, size) = self.read_vector(|w| w.read_i64_le());
+                        compressed_size = size;
+                        Ok(VecInt64(vec))
+                    }
+                    'f' => {
+                        let (vec, size) = self.read_vector(|w| w.read_f32_le());
+                        compressed_size = size;
+                        Ok(VecFloat32(vec))
+                    }
+                    'd' => {
+                        let (vec, size) = self.read_vector(|w| w.read_f64_le());
+                        compressed_size = size;
+                        Ok(VecFloat64(vec))
+                    }
                     'R' => {
                         let bytes_read = self.reader.read_u32_le();
                         Ok(VecRaw(self.reader.read_bytes_exact(bytes_read as usize)))
@@ -128,6 +169,11 @@ impl BinaryFBX {
                 };
 
                 attributes.push(t.unwrap());
+
+                if self.options.record_spans {
+                    let attribute_end = self.reader.current_cursor() as u64;
+                    attribute_spans.push(AttributeSpan::new(attribute_start, attribute_end, compressed_size));
+                }
             }
 
             let mut children: Vec<Node> = vec![];
@@ -141,11 +187,13 @@ impl BinaryFBX {
                 children.extend(self.read_nodes());
             }
 
+            let span = if self.options.record_spans { Some(NodeSpan::new(start, offset, attribute_spans)) } else { None };
+
             vec.push(Node::new(
                 name,
                 attributes,
                 children,
-            ))
+            ).with_span(span))
         }
 
         vec
@@ -160,7 +208,7 @@ impl BinaryFBX {
         }
 
         let cursor = self.reader.current_cursor() + 1;
-        let validation = if cursor % 16 == 0 { Ok(()) } else { Err(ReadError::InvalidOperation) };
+        let validation = if cursor.is_multiple_of(16) { Ok(()) } else { Err(ReadError::InvalidOperation) };
         validation.unwrap()
     }
 
@@ -168,10 +216,21 @@ impl BinaryFBX {
         self.reader.read_bytes_exact(16)
     }
 
-    fn read_footer2(&mut self) -> Option<usize> {
-        let bytes = self.reader.read_bytes_exact(4);
+    fn read_footer2(&mut self) -> Result<Option<usize>> {
+        let mut bytes = self.reader.read_bytes_exact(4);
         if bytes == FBX_FOOTER_MAGIC_BYTES_2 {
-            return None;
+            // padding may be longer than the bytes skipped so far, e.g. the 4 zero bytes plus up to 16 that Blender writes
+            let mut padding = bytes.len();
+            bytes = self.reader.read_bytes_exact(4);
+
+            while bytes == FBX_FOOTER_MAGIC_BYTES_2 && padding < FBX_FOOTER_MAX_PADDING {
+                bytes = self.reader.read_bytes_exact(4);
+                padding += bytes.len();
+            }
+
+            if bytes == self.version.unwrap().to_u8_le() {
+                return Ok(None);
+            }
         }
 
         // invalid length of padding bytes
@@ -179,22 +238,16 @@ impl BinaryFBX {
         // note: the lower 2 bytes of the version always indicate 0x00.
         let version: [u8; 4] = self.version.unwrap().to_u8_le();
         let version: [u8; 2] = [version[0], version[1]];
-        let mut correction: usize = 0;
-
         for i in 0..3 {
             let b: [u8; 2] = [bytes[i], bytes[i + 1]];
 
             if b == version {
-                correction = i;
-                break;
-            }
-
-            if i == 3 {
-                return Err(ReadError::InvalidFooter2BytePattern).unwrap();
+                return Ok(Some(i));
             }
         }
 
-        Some(correction)
+        // zero padding longer than Blender writes, e.g. a truncated file read back as zeros
+        Err(Box::new(ReadError::InvalidFooter2BytePattern))
     }
 
     fn read_footer3(&mut self) {
@@ -212,18 +265,16 @@ impl BinaryFBX {
 
 impl BaseFBXReader for BinaryFBX {
     fn read(&mut self) -> Result<Object> {
-        let version = (self.read_version()); // 4 bytes
+        let version = self.read_version(); // 4 bytes
         self.version = Some(version);
 
-        let children = (self.read_nodes());  // unknown bytes
+        let children = self.read_nodes(); // unknown bytes
         let footer = self.read_footer1(); // 16 bytes
         self.read_padding(); // 0 - 15 bytes
 
-        let correction = self.read_footer2(); // 4 bytes
-        if correction.is_none() {
-            self.read_version(); // 4 bytes
-        } else {
-            let _ = self.reader.read_bytes_exact(correction.unwrap());
+        let correction = self.read_footer2()?; // 4 bytes or more, including the version
+        if let Some(correction) = correction {
+            let _ = self.reader.read_bytes_exact(correction);
         }
 
         self.read_footer3(); // 120 bytes
@@ -241,3 +292,169 @@ impl Debug for BinaryFBX {
             .finish()
     }
 }
+
+pub struct BinaryFBXWriter {
+    writer: BufWriter<File>,
+    buffer: BinaryWriter,
+    version: Option<Version>,
+}
+
+impl BinaryFBXWriter {
+    pub fn new(writer: BufWriter<File>) -> Self {
+        BinaryFBXWriter { writer, buffer: BinaryWriter::new(), version: None }
+    }
+
+    fn is_new_format(&self) -> bool {
+        self.version.map(|w| w >= Version::from(7, 5)).unwrap_or(false)
+    }
+
+    fn write_offset(&mut self, value: u64) {
+        if self.is_new_format() { self.buffer.write_u64_le(value) } else { self.buffer.write_u32_le(value as u32) }
+    }
+
+    fn patch_offset(&mut self, at: usize, value: u64) {
+        if self.is_new_format() { self.buffer.patch_u64_le(at, value) } else { self.buffer.patch_u32_le(at, value as u32) }
+    }
+
+    fn write_null_record(&mut self) {
+        let length = if self.is_new_format() { 25 } else { 13 };
+        self.buffer.write_bytes(&vec![0; length]);
+    }
+
+    fn write_vector<T>(&mut self, values: &[T], writer: impl Fn(&mut BinaryWriter, &T)) -> Result<()> {
+        let mut raw = BinaryWriter::new();
+        for value in values.iter() {
+            writer(&mut raw, value);
+        }
+
+        let raw = raw.into_bytes();
+        self.buffer.write_u32_le(values.len() as u32);
+
+        if raw.len() > ARRAY_COMPRESSION_THRESHOLD {
+            let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+            encoder.write_all(&raw)?;
+            let compressed = encoder.finish()?;
+
+            self.buffer.write_u32_le(1);
+            self.buffer.write_u32_le(compressed.len() as u32);
+            self.buffer.write_bytes(&compressed);
+        } else {
+            self.buffer.write_u32_le(0);
+            self.buffer.write_u32_le(raw.len() as u32);
+            self.buffer.write_bytes(&raw);
+        }
+
+        Ok(())
+    }
+
+    fn write_attribute(&mut self, attribute: &Type) -> Result<()> {
+        self.buffer.write_char(attribute.type_code());
+
+        match attribute {
+            Bool(b) => self.buffer.write_boolean(*b),
+            Int16(n) => self.buffer.write_i16_le(*n),
+            Int32(n) => self.buffer.write_i32_le(*n),
+            Int64(n) => self.buffer.write_i64_le(*n),
+            Float32(n) => self.buffer.write_f32_le(*n),
+            Float64(n) => self.buffer.write_f64_le(*n),
+            VecBool(v) => self.write_vector(v, |w, b| w.write_boolean(*b))?,
+            VecInt32(v) => self.write_vector(v, |w, n| w.write_i32_le(*n))?,
+            VecInt64(v) => self.write_vector(v, |w, n| w.write_i64_le(*n))?,
+            VecFloat32(v) => self.write_vector(v, |w, n| w.write_f32_le(*n))?,
+            VecFloat64(v) => self.write_vector(v, |w, n| w.write_f64_le(*n))?,
+            VecRaw(bytes) => {
+                self.buffer.write_u32_le(bytes.len() as u32);
+                self.buffer.write_bytes(bytes);
+            }
+            String(s) => {
+                self.buffer.write_u32_le(s.len() as u32);
+                self.buffer.write_string(s);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_node(&mut self, node: &Node) -> Result<()> {
+        let name = node.name_str();
+        if name.len() > u8::MAX as usize {
+            return Err(Box::new(WriteError::NameTooLong(name.to_owned())));
+        }
+
+        // the end offset and the attribute length are patched once they are known
+        let start = self.buffer.current_cursor();
+        self.write_offset(0);
+        self.write_offset(node.attributes_slice().len() as u64);
+        let attribute_length_at = self.buffer.current_cursor();
+        self.write_offset(0);
+        self.buffer.write_u8(name.len() as u8);
+        self.buffer.write_string(name);
+
+        let attributes_start = self.buffer.current_cursor();
+        for attribute in node.attributes_slice().iter() {
+            self.write_attribute(attribute)?;
+        }
+
+        let attribute_length = (self.buffer.current_cursor() - attributes_start) as u64;
+        self.patch_offset(attribute_length_at, attribute_length);
+
+        for child in node.children_slice().iter() {
+            self.write_node(child)?;
+        }
+
+        // nodes without attributes get a null record too, like the SDK writes them
+        if !node.children_slice().is_empty() || node.attributes_slice().is_empty() {
+            self.write_null_record();
+        }
+
+        let end = self.buffer.current_cursor() as u64;
+        self.patch_offset(start, end);
+
+        Ok(())
+    }
+
+    fn write_footer(&mut self, object: &Object) {
+        let footer = object.footer().filter(|w| w.len() == 16).unwrap_or(FBX_FOOTER_ID.to_vec());
+        self.buffer.write_bytes(&footer); // 16 bytes
+        self.buffer.write_bytes(&FBX_FOOTER_MAGIC_BYTES_2); // 4 bytes
+
+        // pads to a multiple of 16, with a full 16 bytes if already aligned
+        let cursor = self.buffer.current_cursor();
+        let padding = 16 - cursor % 16;
+        self.buffer.write_bytes(&vec![0; padding]);
+
+        self.buffer.write_bytes(&object.version().to_u8_le()); // 4 bytes
+        self.buffer.write_bytes(&FBX_FOOTER_MAGIC_BYTES_3); // 120 bytes
+        self.buffer.write_bytes(&FBX_FOOTER_MAGIC_BYTES_4); // 16 bytes
+    }
+}
+
+impl BaseFBXWriter for BinaryFBXWriter {
+    fn write(&mut self, object: &Object) -> Result<()> {
+        self.version = Some(object.version());
+
+        self.buffer.write_bytes(&crate::FBX_MAGIC_BINARY_BYTES); // 23 bytes
+        self.buffer.write_bytes(&object.version().to_u8_le()); // 4 bytes
+
+        for node in object.children_slice().iter() {
+            self.write_node(node)?;
+        }
+
+        self.write_null_record();
+        self.write_footer(object);
+
+        let buffer = std::mem::replace(&mut self.buffer, BinaryWriter::new());
+        self.writer.write_all(&buffer.into_bytes())?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+impl Debug for BinaryFBXWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BinaryFBXWriter")
+            .field("version", &self.version)
+            .finish()
+    }
+}
@@ -1,16 +1,19 @@
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{BufReader, BufWriter, Read};
 use std::path::Path;
 
-use crate::error::{ReadError, Result};
-use crate::format::{BaseFBXReader, Object};
+use crate::error::{ReadError, Result, WriteError};
+use crate::format::{BaseFBXReader, BaseFBXWriter, Object, ReadOptions};
 
 mod binary;
 pub mod format;
+pub mod diff;
+pub mod document;
 pub mod error;
+pub mod visitor;
 
 
-const FBX_MAGIC_BINARY_BYTES: [u8; 23] = [
+pub(crate) const FBX_MAGIC_BINARY_BYTES: [u8; 23] = [
     0x4b, 0x61, 0x79, 0x64, 0x61, 0x72, 0x61, 0x20, 0x46, 0x42, 0x58, 0x20, 0x42, 0x69, 0x6e, 0x61,
     0x72, 0x79, 0x20, 0x20, 0x00, 0x1a, 0x00,
 ];
@@ -23,16 +26,119 @@ fn has_fbx_magic_bytes(reader: &mut BufReader<File>) -> bool {
 }
 
 pub fn read_fbx(path: &Path) -> Result<Object> {
+    read_fbx_with_options(path, ReadOptions::default())
+}
+
+pub fn read_fbx_with_options(path: &Path, options: ReadOptions) -> Result<Object> {
     let file = std::fs::File::open(path).map_err(|_| {
         ReadError::FailedToOpenFile(path.to_owned().into_os_string().into_string().unwrap())
     })?;
 
     let mut reader = BufReader::new(file);
     let mut fbx: Box<dyn BaseFBXReader> = if has_fbx_magic_bytes(&mut reader) {
-        Box::new(format::BinaryFBX::new(reader))
+        Box::new(format::BinaryFBX::with_options(reader, options))
     } else {
         Box::new(format::AsciiFBX::new(reader))
     };
 
     fbx.read()
 }
+
+// always writes the binary format, arrays above a small size are compressed
+pub fn write_fbx(path: &Path, object: &Object) -> Result<()> {
+    let file = std::fs::File::create(path).map_err(|_| {
+        WriteError::FailedToCreateFile(path.to_owned().into_os_string().into_string().unwrap())
+    })?;
+
+    let mut fbx = format::BinaryFBXWriter::new(BufWriter::new(file));
+    fbx.write(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::{diff, DiffOptions};
+    use crate::format::{Node, Type, Version};
+
+    fn node(name: &str, attributes: Vec<Type>, children: Vec<Node>) -> Node {
+        Node::new(name.to_owned(), attributes, children)
+    }
+
+    // every attribute type, an array large enough to be compressed and nodes with and without children
+    fn sample(version: Version, padding: usize) -> Object {
+        let attributes = vec![
+            Type::Bool(true),
+            Type::Int16(-2),
+            Type::Int32(-3),
+            Type::Int64(1 << 40),
+            Type::Float32(0.1),
+            Type::Float64(-0.25),
+            Type::String("Name\x00\x01Model".to_owned()),
+            Type::VecRaw(vec![0, 1, 2, 255]),
+            Type::VecBool(vec![true, false, true]),
+            Type::VecInt32(vec![0, 1, -3]),
+            Type::VecInt64(vec![-1, 1 << 40]),
+            Type::VecFloat32(vec![0.5, -1.5]),
+        ];
+
+        let large = Type::VecFloat64((0..1000).map(|w| w as f64 / 3.0).collect());
+        let children = vec![
+            node("Header", vec![], vec![node("Creator", vec![Type::String("x".repeat(padding))], vec![])]),
+            node("Objects", vec![], vec![node("Model", attributes, vec![node("Vertices", vec![large], vec![]), node("Empty", vec![], vec![])])]),
+        ];
+
+        Object::new(version, children, None)
+    }
+
+    fn round_trip(object: &Object, options: ReadOptions) -> Object {
+        let path = std::env::temp_dir().join(format!("fbx-round-trip-{}-{}.fbx", std::process::id(), object.version().minor()));
+        write_fbx(&path, object).unwrap();
+        let read = read_fbx_with_options(&path, options);
+        let _ = std::fs::remove_file(&path);
+
+        read.unwrap()
+    }
+
+    #[test]
+    fn written_files_read_back_to_the_same_tree() {
+        for version in [Version::from(7, 4), Version::from(7, 5)] {
+            // each length of the last string moves the footer id to another offset within 16 bytes
+            for padding in 0..16 {
+                let object = sample(version, padding);
+                let read = round_trip(&object, ReadOptions::default());
+
+                assert_eq!(read.version(), version);
+                assert!(diff(&object, &read, &DiffOptions::default()).is_empty(), "{:?} with {} bytes: {}", version, padding, diff(&object, &read, &DiffOptions::default()).report());
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_footers_fail_to_read() {
+        let path = std::env::temp_dir().join(format!("fbx-truncated-{}.fbx", std::process::id()));
+        write_fbx(&path, &sample(Version::from(7, 4), 0)).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        // cut off after the footer padding, then with the version and the magic bytes zeroed instead
+        let tail = 4 + 120 + 16;
+        let mut zeroed = bytes.clone();
+        zeroed[bytes.len() - tail..].fill(0);
+
+        for broken in [&bytes[..bytes.len() - tail], &zeroed[..]] {
+            std::fs::write(&path, broken).unwrap();
+            assert!(read_fbx(&path).is_err());
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn written_arrays_record_compressed_spans() {
+        let read = round_trip(&sample(Version::from(7, 4), 0), ReadOptions { record_spans: true });
+        let objects = &read.children_slice()[1].children_slice()[0];
+        let spans = |node: &Node| node.span().unwrap().attributes().to_vec();
+
+        assert!(spans(objects).iter().all(|w| !w.is_compressed()));
+        assert!(spans(&objects.children_slice()[0])[0].is_compressed());
+    }
+}
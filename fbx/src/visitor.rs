@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::format::{Node, Object};
+
+/// Returned from `Visitor` hooks to steer the walker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitFlow {
+    Continue,
+    SkipChildren,
+    Stop,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PathSegment {
+    name: String,
+    position: usize,
+    occurrence: usize,
+}
+
+impl PathSegment {
+    pub fn new(name: String, position: usize, occurrence: usize) -> Self {
+        PathSegment { name, position, occurrence }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // index of the node in its parent's children
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // index of the node among siblings with the same name
+    pub fn occurrence(&self) -> usize {
+        self.occurrence
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NodePath {
+    segments: Vec<PathSegment>,
+}
+
+impl NodePath {
+    pub fn new() -> Self {
+        NodePath { segments: vec![] }
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    pub fn depth(&self) -> usize {
+        self.segments.len().saturating_sub(1)
+    }
+
+    pub fn last(&self) -> Option<&PathSegment> {
+        self.segments.last()
+    }
+
+    pub fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.segments.pop()
+    }
+
+    pub fn join(&self, segment: PathSegment) -> NodePath {
+        let mut path = self.clone();
+        path.push(segment);
+        path
+    }
+
+    pub fn resolve<'a>(&self, object: &'a Object) -> Option<&'a Node> {
+        let (first, rest) = self.segments.split_first()?;
+        let mut node = object.children_slice().get(first.position)?;
+
+        for segment in rest {
+            node = node.children_slice().get(segment.position)?;
+        }
+
+        Some(node)
+    }
+}
+
+impl Display for NodePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+
+            write!(f, "{}", segment.name)?;
+
+            if segment.occurrence > 0 {
+                write!(f, "[{}]", segment.occurrence)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub trait Visitor {
+    fn enter(&mut self, _node: &Node, _path: &NodePath, _depth: usize) -> VisitFlow {
+        VisitFlow::Continue
+    }
+
+    fn leave(&mut self, _node: &Node, _path: &NodePath, _depth: usize) -> VisitFlow {
+        VisitFlow::Continue
+    }
+}
+
+pub trait VisitorMut {
+    fn enter(&mut self, _node: &mut Node, _path: &NodePath, _depth: usize) -> VisitFlow {
+        VisitFlow::Continue
+    }
+
+    fn leave(&mut self, _node: &mut Node, _path: &NodePath, _depth: usize) -> VisitFlow {
+        VisitFlow::Continue
+    }
+}
+
+// for each node, the number of preceding siblings sharing its name
+pub(crate) fn occurrences(nodes: &[Node]) -> Vec<usize> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+
+    nodes
+        .iter()
+        .map(|w| {
+            let count = seen.entry(w.name_str()).or_insert(0);
+            *count += 1;
+            *count - 1
+        })
+        .collect()
+}
+
+pub fn walk<V: Visitor + ?Sized>(object: &Object, visitor: &mut V) -> VisitFlow {
+    walk_nodes(object.children_slice(), &mut NodePath::new(), visitor)
+}
+
+pub fn walk_nodes<V: Visitor + ?Sized>(nodes: &[Node], path: &mut NodePath, visitor: &mut V) -> VisitFlow {
+    let occurrences = occurrences(nodes);
+
+    for (position, node) in nodes.iter().enumerate() {
+        path.push(PathSegment::new(node.name(), position, occurrences[position]));
+
+        let depth = path.depth();
+        let flow = match visitor.enter(node, path, depth) {
+            VisitFlow::Continue => walk_nodes(node.children_slice(), path, visitor),
+            flow => flow,
+        };
+
+        if flow == VisitFlow::Stop || visitor.leave(node, path, depth) == VisitFlow::Stop {
+            path.pop();
+            return VisitFlow::Stop;
+        }
+
+        path.pop();
+    }
+
+    VisitFlow::Continue
+}
+
+pub fn walk_mut<V: VisitorMut + ?Sized>(object: &mut Object, visitor: &mut V) -> VisitFlow {
+    walk_nodes_mut(object.children_mut(), &mut NodePath::new(), visitor)
+}
+
+// occurrences count the preceding siblings under the names they had after their visit, so a node renamed by
+// `enter` shows its new name in its own path once `enter` returns, in its children's paths and in later siblings' counts
+pub fn walk_nodes_mut<V: VisitorMut + ?Sized>(nodes: &mut [Node], path: &mut NodePath, visitor: &mut V) -> VisitFlow {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for (position, node) in nodes.iter_mut().enumerate() {
+        let occurrence = |seen: &HashMap<String, usize>, node: &Node| seen.get(node.name_str()).copied().unwrap_or(0);
+        path.push(PathSegment::new(node.name(), position, occurrence(&seen, node)));
+
+        let depth = path.depth();
+        let flow = visitor.enter(node, path, depth);
+
+        if path.last().map(|w| w.name() != node.name_str()).unwrap_or(false) {
+            path.pop();
+            path.push(PathSegment::new(node.name(), position, occurrence(&seen, node)));
+        }
+
+        let flow = match flow {
+            VisitFlow::Continue => walk_nodes_mut(node.children_mut(), path, visitor),
+            flow => flow,
+        };
+
+        let stop = flow == VisitFlow::Stop || visitor.leave(node, path, depth) == VisitFlow::Stop;
+        *seen.entry(node.name()).or_insert(0) += 1;
+        path.pop();
+
+        if stop {
+            return VisitFlow::Stop;
+        }
+    }
+
+    VisitFlow::Continue
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Version;
+
+    fn node(name: &str, children: Vec<Node>) -> Node {
+        Node::new(name.to_owned(), vec![], children)
+    }
+
+    struct Recorder {
+        paths: Vec<String>,
+        skip: &'static str,
+    }
+
+    impl Visitor for Recorder {
+        fn enter(&mut self, node: &Node, path: &NodePath, _depth: usize) -> VisitFlow {
+            self.paths.push(path.to_string());
+            if node.name_str() == self.skip { VisitFlow::SkipChildren } else { VisitFlow::Continue }
+        }
+    }
+
+    #[test]
+    fn walk_numbers_same_name_siblings_and_skips_children() {
+        let object = Object::new(Version::from(7, 4), vec![node("A", vec![node("P", vec![]), node("P", vec![])]), node("B", vec![node("C", vec![])]), node("A", vec![])], None);
+        let mut recorder = Recorder { paths: vec![], skip: "B" };
+
+        assert_eq!(walk(&object, &mut recorder), VisitFlow::Continue);
+        assert_eq!(recorder.paths, ["A", "A/P", "A/P[1]", "B", "A[1]"]);
+    }
+
+    struct Renamer {
+        paths: Vec<String>,
+    }
+
+    impl VisitorMut for Renamer {
+        fn enter(&mut self, node: &mut Node, _path: &NodePath, _depth: usize) -> VisitFlow {
+            if node.name_str() == "Old" {
+                node.set_name("New".to_owned());
+            }
+            VisitFlow::Continue
+        }
+
+        fn leave(&mut self, _node: &mut Node, path: &NodePath, _depth: usize) -> VisitFlow {
+            self.paths.push(path.to_string());
+            VisitFlow::Continue
+        }
+    }
+
+    #[test]
+    fn walk_mut_paths_follow_renames() {
+        let mut object = Object::new(Version::from(7, 4), vec![node("New", vec![]), node("Old", vec![node("C", vec![])]), node("New", vec![])], None);
+        let mut renamer = Renamer { paths: vec![] };
+
+        walk_mut(&mut object, &mut renamer);
+        assert_eq!(renamer.paths, ["New", "New[1]/C", "New[1]", "New[2]"]);
+    }
+}
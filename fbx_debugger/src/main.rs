@@ -1,12 +1,28 @@
 extern crate fbx;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use fbx::read_fbx;
+use fbx::{read_fbx, write_fbx};
+use fbx_reader::hierarchy::SceneHierarchy;
+use fbx_reader::humanoid::detect_humanoid;
+use fbx_reader::media::{embed_media, extract_embedded_media, list_embedded_media};
+use fbx_reader::paths::{make_paths_relative, TextureResolver};
 use fbx_reader::FBXReader;
 
-fn main() {
-    let fbx = read_fbx(Path::new("./MANUKA.fbx")).unwrap();
+// the file checked when no command is given, as before the commands were added
+const DEFAULT_INPUT: &str = "./MANUKA.fbx";
+
+const USAGE: &str = "usage:
+  fbx_debugger [check [input.fbx]]
+  fbx_debugger media list <input.fbx>
+  fbx_debugger media extract <input.fbx> <directory>
+  fbx_debugger media embed <input.fbx> <output.fbx>
+  fbx_debugger textures check <input.fbx> [search directories...]
+  fbx_debugger textures relative <input.fbx> <output.fbx> [search directories...]
+  fbx_debugger humanoid <input.fbx>";
+
+fn check_manuka(input: &Path) {
+    let fbx = read_fbx(input).unwrap();
     let reader = FBXReader::from(fbx);
 
     let extension = reader.get("FBXHeaderExtension").unwrap();
@@ -16,3 +32,164 @@ fn main() {
     let creator = extension.get_node("Creator").unwrap().get_value().unwrap().as_str();
     assert_eq!(creator, Some("Blender (stable FBX IO) - 2.83.20 - 4.20.5".to_owned()));
 }
+
+// directory of the input file, `.` for bare file names
+fn directory_of(path: &Path) -> &Path {
+    path.parent().filter(|w| !w.as_os_str().is_empty()).unwrap_or(Path::new("."))
+}
+
+fn media(args: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match args {
+        [command, input] if command == "list" => {
+            let fbx = read_fbx(Path::new(input))?;
+
+            for media in list_embedded_media(&fbx) {
+                println!("{}\t{}\t{} bytes\t{:?}", media.video, media.output_name(), media.size, media.image_type);
+            }
+        }
+        [command, input, directory] if command == "extract" => {
+            let fbx = read_fbx(Path::new(input))?;
+
+            for (media, path) in extract_embedded_media(&fbx, Path::new(directory))? {
+                println!("{}\t{}", media.video, path.display());
+            }
+        }
+        [command, input, output] if command == "embed" => {
+            let input = Path::new(input);
+            let mut fbx = read_fbx(input)?;
+            let report = embed_media(&mut fbx, directory_of(input))?;
+
+            for (video, path) in report.embedded.iter() {
+                println!("embedded {}\t{}", video, path.display());
+            }
+
+            for (video, path) in report.missing.iter() {
+                eprintln!("missing {}\t{}", video, path);
+            }
+
+            for (removed, kept) in report.deduplicated.iter() {
+                println!("deduplicated {} into {}", removed, kept);
+            }
+
+            write_fbx(Path::new(output), &fbx)?;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    Ok(())
+}
+
+fn textures(args: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let (command, input, rest) = match args {
+        [command, input, rest @ ..] => (command.as_str(), Path::new(input), rest),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let mut fbx = read_fbx(input)?;
+    let directory = directory_of(input);
+
+    match (command, rest) {
+        ("check", search) => {
+            let search: Vec<PathBuf> = search.iter().map(PathBuf::from).collect();
+            let resolutions = TextureResolver::new(directory, &search).resolve_scene(&fbx);
+
+            for resolution in resolutions.iter() {
+                match &resolution.resolved {
+                    Some((path, method)) => println!("{} {}\t{:?}\t{}", resolution.class, resolution.id, method, path.display()),
+                    None => println!("{} {}\tmissing\t{}", resolution.class, resolution.id, resolution.paths.file_name),
+                }
+            }
+
+            if resolutions.iter().any(|w| w.is_missing()) {
+                std::process::exit(1);
+            }
+        }
+        ("relative", [output, search @ ..]) => {
+            let search: Vec<PathBuf> = search.iter().map(PathBuf::from).collect();
+            let resolutions = TextureResolver::new(directory, &search).resolve_scene(&fbx);
+            let changed = make_paths_relative(&mut fbx, &resolutions, directory);
+
+            println!("rewrote {} paths", changed);
+            write_fbx(Path::new(output), &fbx)?;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    Ok(())
+}
+
+fn humanoid(args: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let input = match args {
+        [input] => Path::new(input),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let graph = FBXReader::from(read_fbx(input)?).graph();
+    let mapping = detect_humanoid(&graph, &SceneHierarchy::from_graph(&graph)?)?;
+
+    for bone in mapping.matches() {
+        println!("{}\t{}\t{:.2}", bone.bone.name(), bone.name, bone.confidence);
+    }
+
+    for bone in mapping.unmapped_required() {
+        eprintln!("missing {}", bone.name());
+    }
+
+    println!("confidence {:.2}", mapping.confidence());
+
+    if !mapping.is_complete() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.split_first() {
+        None => check_manuka(Path::new(DEFAULT_INPUT)),
+        Some((command, rest)) if command == "check" => match rest {
+            [] => check_manuka(Path::new(DEFAULT_INPUT)),
+            [input] => check_manuka(Path::new(input)),
+            _ => {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+        },
+        Some((command, rest)) if command == "media" => {
+            if let Err(e) = media(rest) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some((command, rest)) if command == "textures" => {
+            if let Err(e) = textures(rest) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some((command, rest)) if command == "humanoid" => {
+            if let Err(e) = humanoid(rest) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(_) => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,830 @@
+use crate::error::SceneError;
+use crate::graph::{ConnectionKind, ObjectGraph, ObjectId};
+use crate::mesh::{child, child_i32_array};
+
+// KTime units per second
+pub const TICKS_PER_SECOND: i64 = 46_186_158_000;
+
+pub fn ticks_to_seconds(ticks: i64) -> f64 {
+    ticks as f64 / TICKS_PER_SECOND as f64
+}
+
+pub fn seconds_to_ticks(seconds: f64) -> i64 {
+    (seconds * TICKS_PER_SECOND as f64).round() as i64
+}
+
+fn of_class(graph: &ObjectGraph, id: ObjectId, class: &str) -> Result<(), SceneError> {
+    match graph.object(id) {
+        Some(object) if object.class() == class => Ok(()),
+        Some(_) => Err(SceneError::UnexpectedObject(id, class.to_owned())),
+        None => Err(SceneError::MissingObject(id)),
+    }
+}
+
+fn children_of_class<'a>(graph: &'a ObjectGraph, id: ObjectId, class: &'a str) -> impl Iterator<Item = ObjectId> + 'a {
+    graph.children(id).into_iter().filter(move |w| graph.object(*w).map(|o| o.class() == class).unwrap_or(false))
+}
+
+/// The attributes shared by a run of keys: flags plus four floats (slopes, weights or velocities).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyAttribute {
+    pub flags: i32,
+    pub data: [f32; 4],
+}
+
+// `KeyAttrFlags` bits, as defined by the SDK's `FbxAnimCurveDef`
+const INTERPOLATION_CONSTANT: i32 = 0x0000_0002;
+const INTERPOLATION_LINEAR: i32 = 0x0000_0004;
+const INTERPOLATION_CUBIC: i32 = 0x0000_0008;
+const CONSTANT_NEXT: i32 = 0x0000_0100;
+const TANGENT_TCB: i32 = 0x0000_0200;
+const TANGENT_USER: i32 = 0x0000_0400;
+const TANGENT_BREAK: i32 = 0x0000_0800;
+const WEIGHTED_RIGHT: i32 = 0x0100_0000;
+const WEIGHTED_NEXT_LEFT: i32 = 0x0200_0000;
+const VELOCITY_RIGHT: i32 = 0x1000_0000;
+const VELOCITY_NEXT_LEFT: i32 = 0x2000_0000;
+
+// weight of tangents that are not weighted, which makes the segment a plain Hermite spline
+pub const DEFAULT_WEIGHT: f64 = 1.0 / 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Constant,
+    // holds the value of the next key for the whole segment
+    ConstantNext,
+    Linear,
+    Cubic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TangentMode {
+    Auto,
+    Tcb,
+    User,
+    Break,
+}
+
+/// A key with its `KeyAttrFlags` and `KeyAttrDataFloat` decoded; the tangents describe the segment up to the next key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Key {
+    pub time: i64,
+    pub value: f64,
+    pub interpolation: Interpolation,
+    pub tangent_mode: TangentMode,
+    // slopes in value per second
+    pub right_slope: f64,
+    pub next_left_slope: f64,
+    // fraction of the segment the tangent handles reach, 1/3 when not weighted
+    pub right_weight: f64,
+    pub next_left_weight: f64,
+    // relative change of the handle lengths along their tangents, 0 leaves the handles where the weights put them
+    pub right_velocity: f64,
+    pub next_left_velocity: f64,
+}
+
+// two signed 16 bit values stored in the bits of a float, scaled so that 9999 stands for 1
+fn unpack(data: f32) -> (f64, f64) {
+    let bits = data.to_bits();
+    ((bits & 0xffff) as i16 as f64 / 9999.0, (bits >> 16) as i16 as f64 / 9999.0)
+}
+
+impl Key {
+    fn decode(time: i64, value: f32, attribute: Option<&KeyAttribute>) -> Self {
+        let flags = attribute.map(|w| w.flags).unwrap_or(INTERPOLATION_LINEAR);
+        let data = attribute.map(|w| w.data).unwrap_or_default();
+
+        let interpolation = if flags & INTERPOLATION_CONSTANT != 0 {
+            if flags & CONSTANT_NEXT != 0 { Interpolation::ConstantNext } else { Interpolation::Constant }
+        } else if flags & INTERPOLATION_CUBIC != 0 {
+            Interpolation::Cubic
+        } else {
+            Interpolation::Linear
+        };
+
+        let tangent_mode = if flags & TANGENT_TCB != 0 {
+            TangentMode::Tcb
+        } else if flags & TANGENT_BREAK != 0 {
+            TangentMode::Break
+        } else if flags & TANGENT_USER != 0 {
+            TangentMode::User
+        } else {
+            TangentMode::Auto
+        };
+
+        let (right_weight, next_left_weight) = unpack(data[2]);
+        let (right_velocity, next_left_velocity) = unpack(data[3]);
+
+        Key {
+            time,
+            value: value as f64,
+            interpolation,
+            tangent_mode,
+            right_slope: data[0] as f64,
+            next_left_slope: data[1] as f64,
+            right_weight: if flags & WEIGHTED_RIGHT != 0 { right_weight } else { DEFAULT_WEIGHT },
+            next_left_weight: if flags & WEIGHTED_NEXT_LEFT != 0 { next_left_weight } else { DEFAULT_WEIGHT },
+            right_velocity: if flags & VELOCITY_RIGHT != 0 { right_velocity } else { 0.0 },
+            next_left_velocity: if flags & VELOCITY_NEXT_LEFT != 0 { next_left_velocity } else { 0.0 },
+        }
+    }
+}
+
+// Kochanek-Bartels slopes of a TCB key, whose data holds tension, continuity and bias instead of slopes
+fn tcb_slopes(keys: &[Key], i: usize, data: [f32; 4]) -> (f64, f64) {
+    let (tension, continuity, bias) = (data[0] as f64, data[1] as f64, data[2] as f64);
+    let slope = |a: &Key, b: &Key| {
+        let dt = ticks_to_seconds(b.time - a.time);
+        if dt > 0.0 { (b.value - a.value) / dt } else { 0.0 }
+    };
+
+    let incoming = if i > 0 { slope(&keys[i - 1], &keys[i]) } else if i + 1 < keys.len() { slope(&keys[i], &keys[i + 1]) } else { 0.0 };
+    let outgoing = if i + 1 < keys.len() { slope(&keys[i], &keys[i + 1]) } else { incoming };
+
+    let left = (1.0 - tension) * ((1.0 + continuity) * (1.0 + bias) * incoming + (1.0 - continuity) * (1.0 - bias) * outgoing) / 2.0;
+    let right = (1.0 - tension) * ((1.0 - continuity) * (1.0 + bias) * incoming + (1.0 + continuity) * (1.0 - bias) * outgoing) / 2.0;
+
+    (left, right)
+}
+
+// cubic bezier through the key values, with handles placed by slope and weight and stretched along their tangent by velocity
+fn evaluate_cubic(a: &Key, b: &Key, time: i64) -> f64 {
+    let dt = ticks_to_seconds(b.time - a.time);
+    let x = ticks_to_seconds(time - a.time) / dt;
+
+    // a handle never reaches past the other key, so time keeps advancing along the segment
+    let w1 = (a.right_weight * (1.0 + a.right_velocity)).clamp(0.0, 1.0);
+    let w2 = (a.next_left_weight * (1.0 + a.next_left_velocity)).clamp(0.0, 1.0);
+    let (y0, y1, y2, y3) = (a.value, a.value + w1 * dt * a.right_slope, b.value - w2 * dt * a.next_left_slope, b.value);
+    let bezier = |p0: f64, p1: f64, p2: f64, p3: f64, u: f64| {
+        let v = 1.0 - u;
+        v * v * v * p0 + 3.0 * v * v * u * p1 + 3.0 * v * u * u * p2 + u * u * u * p3
+    };
+
+    // with handles at 1/3 time advances linearly, otherwise the bezier parameter is found by bisection
+    let u = if (w1 - DEFAULT_WEIGHT).abs() < 1e-4 && (w2 - DEFAULT_WEIGHT).abs() < 1e-4 {
+        x
+    } else {
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..64 {
+            let middle = (low + high) / 2.0;
+            if bezier(0.0, w1, 1.0 - w2, 1.0, middle) < x { low = middle } else { high = middle }
+        }
+        (low + high) / 2.0
+    };
+
+    bezier(y0, y1, y2, y3, u)
+}
+
+/// An `AnimationCurve`, one animated scalar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationCurve {
+    id: ObjectId,
+    default: f64,
+    key_times: Vec<i64>,
+    key_values: Vec<f32>,
+    key_attr_flags: Vec<i32>,
+    key_attr_data: Vec<f32>,
+    key_attr_ref_count: Vec<i32>,
+    keys: Vec<Key>,
+}
+
+impl AnimationCurve {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<AnimationCurve, SceneError> {
+        of_class(graph, id, "AnimationCurve")?;
+        let node = graph.object(id).ok_or(SceneError::MissingObject(id))?.node();
+        let first = |name: &str| child(node, name).and_then(|w| w.attributes_slice().first());
+
+        let key_times = first("KeyTime").and_then(|w| w.to_i64_vec()).unwrap_or_default();
+        let key_values: Vec<f32> = first("KeyValueFloat").and_then(|w| w.to_f64_vec()).unwrap_or_default().into_iter().map(|w| w as f32).collect();
+
+        if key_times.len() != key_values.len() {
+            return Err(SceneError::InvalidArrayLength("KeyValueFloat".to_owned(), key_values.len()));
+        }
+
+        let key_attr_data: Vec<f32> = first("KeyAttrDataFloat").and_then(|w| w.to_f64_vec()).unwrap_or_default().into_iter().map(|w| w as f32).collect();
+        let key_attr_flags = child_i32_array(node, "KeyAttrFlags").unwrap_or_default();
+        let key_attr_ref_count = child_i32_array(node, "KeyAttrRefCount").unwrap_or_default();
+
+        if key_attr_data.len() != key_attr_flags.len() * 4 {
+            return Err(SceneError::InvalidArrayLength("KeyAttrDataFloat".to_owned(), key_attr_data.len()));
+        }
+
+        if key_attr_ref_count.len() != key_attr_flags.len() {
+            return Err(SceneError::InvalidArrayLength("KeyAttrRefCount".to_owned(), key_attr_ref_count.len()));
+        }
+
+        let mut curve = AnimationCurve {
+            id,
+            default: first("Default").and_then(|w| w.to_f64()).unwrap_or_default(),
+            key_times,
+            key_values,
+            key_attr_flags,
+            key_attr_data,
+            key_attr_ref_count,
+            keys: Vec::new(),
+        };
+
+        curve.keys = curve.decode_keys();
+        Ok(curve)
+    }
+
+    fn decode_keys(&self) -> Vec<Key> {
+        let attributes = self.key_attributes();
+        let mut keys: Vec<Key> = self.key_times.iter().zip(self.key_values.iter()).enumerate().map(|(i, (time, value))| Key::decode(*time, *value, attributes.get(i))).collect();
+
+        // TCB slopes depend on the neighbouring keys, the left slope of a key is stored on the previous one
+        for i in 0..keys.len() {
+            if keys[i].tangent_mode == TangentMode::Tcb {
+                let (left, right) = tcb_slopes(&keys, i, attributes[i].data);
+                keys[i].right_slope = right;
+
+                if i > 0 {
+                    keys[i - 1].next_left_slope = left;
+                }
+            }
+        }
+
+        keys
+    }
+
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    // value at `time` in ticks, holding the first and last key values outside the keyed range
+    pub fn evaluate(&self, time: i64) -> f64 {
+        let keys = &self.keys;
+
+        let (first, last) = match (keys.first(), keys.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return self.default,
+        };
+
+        if time <= first.time {
+            return first.value;
+        }
+
+        if time >= last.time {
+            return last.value;
+        }
+
+        let next = keys.partition_point(|w| w.time <= time);
+        let (a, b) = (&keys[next - 1], &keys[next]);
+
+        match a.interpolation {
+            Interpolation::Constant => a.value,
+            // the key itself still has its own value, the jump comes right after it
+            Interpolation::ConstantNext => if time == a.time { a.value } else { b.value },
+            Interpolation::Linear => a.value + (b.value - a.value) * (time - a.time) as f64 / (b.time - a.time) as f64,
+            Interpolation::Cubic => evaluate_cubic(a, b, time),
+        }
+    }
+
+    pub fn evaluate_seconds(&self, seconds: f64) -> f64 {
+        self.evaluate(seconds_to_ticks(seconds))
+    }
+
+    // values sampled at a fixed rate from `start` to `stop` in ticks, both included
+    pub fn bake(&self, start: i64, stop: i64, frames_per_second: f64) -> Vec<(i64, f64)> {
+        let step = TICKS_PER_SECOND as f64 / frames_per_second;
+        let count = ((stop - start) as f64 / step).floor().max(0.0) as i64;
+
+        (0..=count).map(|frame| start + (frame as f64 * step).round() as i64).map(|time| (time, self.evaluate(time))).collect()
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    // value of a curve without keys
+    pub fn default(&self) -> f64 {
+        self.default
+    }
+
+    pub fn key_count(&self) -> usize {
+        self.key_times.len()
+    }
+
+    // key times in ticks, see `TICKS_PER_SECOND`
+    pub fn key_times(&self) -> &[i64] {
+        &self.key_times
+    }
+
+    pub fn key_values(&self) -> &[f32] {
+        &self.key_values
+    }
+
+    pub fn key_attr_flags(&self) -> &[i32] {
+        &self.key_attr_flags
+    }
+
+    // four floats per entry of `key_attr_flags`
+    pub fn key_attr_data(&self) -> &[f32] {
+        &self.key_attr_data
+    }
+
+    // number of consecutive keys sharing each entry of `key_attr_flags`
+    pub fn key_attr_ref_count(&self) -> &[i32] {
+        &self.key_attr_ref_count
+    }
+
+    // the attribute of every key, expanding the run-length encoding of `KeyAttrRefCount`;
+    // keys past the last run reuse the last attribute
+    pub fn key_attributes(&self) -> Vec<KeyAttribute> {
+        let mut attributes = Vec::with_capacity(self.key_count());
+
+        for (i, count) in self.key_attr_ref_count.iter().enumerate() {
+            let data = &self.key_attr_data[i * 4..i * 4 + 4];
+            let attribute = KeyAttribute { flags: self.key_attr_flags[i], data: [data[0], data[1], data[2], data[3]] };
+            attributes.extend(std::iter::repeat_n(attribute, (*count).max(0) as usize));
+        }
+
+        attributes.truncate(self.key_count());
+
+        if let Some(last) = attributes.last().copied() {
+            attributes.resize(self.key_count(), last);
+        }
+
+        attributes
+    }
+}
+
+/// One channel of a curve node, e.g. `d|X`, with its static value and the curve animating it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurveChannel {
+    pub name: String,
+    pub default: f64,
+    pub curve: Option<AnimationCurve>,
+}
+
+impl CurveChannel {
+    pub fn evaluate(&self, time: i64) -> f64 {
+        match &self.curve {
+            Some(curve) => curve.evaluate(time),
+            None => self.default,
+        }
+    }
+}
+
+/// An `AnimationCurveNode`, the curves animating one property of one object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationCurveNode {
+    id: ObjectId,
+    name: String,
+    channels: Vec<CurveChannel>,
+    target: Option<(ObjectId, String)>,
+}
+
+impl AnimationCurveNode {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<AnimationCurveNode, SceneError> {
+        of_class(graph, id, "AnimationCurveNode")?;
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+        let properties = graph.properties(id).unwrap_or_default();
+
+        // every `d|` property is a channel, whether or not a curve is connected to it
+        let mut channels: Vec<CurveChannel> = properties
+            .iter()
+            .filter(|w| w.name().starts_with("d|"))
+            .map(|w| CurveChannel { name: w.name().to_owned(), default: w.value().as_f64().unwrap_or_default(), curve: None })
+            .collect();
+
+        for connection in graph.property_connections(id).into_iter().filter(|w| w.kind() == ConnectionKind::ObjectProperty) {
+            if graph.object(connection.child()).map(|w| w.class() != "AnimationCurve").unwrap_or(true) {
+                continue;
+            }
+
+            let name = connection.parent_property().unwrap_or_default();
+            let curve = AnimationCurve::from_graph(graph, connection.child())?;
+
+            match channels.iter_mut().find(|w| w.name == name) {
+                Some(channel) => channel.curve = Some(curve),
+                None => channels.push(CurveChannel { name: name.to_owned(), default: curve.default, curve: Some(curve) }),
+            }
+        }
+
+        // the animated property, skipping the layer the node also connects to
+        let target = graph
+            .property_targets(id)
+            .into_iter()
+            .find(|(parent, _)| graph.object(*parent).map(|w| w.class() != "AnimationLayer").unwrap_or(false))
+            .map(|(parent, property)| (parent, property.to_owned()));
+
+        Ok(AnimationCurveNode { id, name: object.name().to_owned(), channels, target })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    // usually `T`, `R`, `S` or the name of the property
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn channels(&self) -> &[CurveChannel] {
+        &self.channels
+    }
+
+    pub fn channel(&self, name: &str) -> Option<&CurveChannel> {
+        self.channels.iter().find(|w| w.name == name)
+    }
+
+    // value of every channel at `time`, the static value where no curve is connected
+    pub fn evaluate(&self, time: i64) -> Vec<f64> {
+        self.channels.iter().map(|w| w.evaluate(time)).collect()
+    }
+
+    // object and property the node animates, e.g. a model's `Lcl Translation`
+    pub fn target(&self) -> Option<(ObjectId, &str)> {
+        self.target.as_ref().map(|(id, property)| (*id, property.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    Additive,
+    Override,
+    // like `Override`, but layers below show through in proportion to the weight
+    OverridePassthrough,
+}
+
+impl BlendMode {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            1 => BlendMode::Override,
+            2 => BlendMode::OverridePassthrough,
+            _ => BlendMode::Additive,
+        }
+    }
+}
+
+/// An `AnimationLayer`, blended with the other layers of its stack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationLayer {
+    id: ObjectId,
+    name: String,
+    weight: f64,
+    mute: bool,
+    solo: bool,
+    blend_mode: BlendMode,
+    curve_nodes: Vec<AnimationCurveNode>,
+}
+
+impl AnimationLayer {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<AnimationLayer, SceneError> {
+        of_class(graph, id, "AnimationLayer")?;
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+        let properties = graph.properties(id).unwrap_or_default();
+
+        let mut curve_nodes = Vec::new();
+        for curve_node in children_of_class(graph, id, "AnimationCurveNode") {
+            curve_nodes.push(AnimationCurveNode::from_graph(graph, curve_node)?);
+        }
+
+        Ok(AnimationLayer {
+            id,
+            name: object.name().to_owned(),
+            weight: properties.get_f64("Weight").unwrap_or(100.0),
+            mute: properties.get_bool("Mute").unwrap_or(false),
+            solo: properties.get_bool("Solo").unwrap_or(false),
+            blend_mode: BlendMode::from_fbx(properties.get_i64("BlendMode").unwrap_or_default()),
+            curve_nodes,
+        })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // in percent
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn mute(&self) -> bool {
+        self.mute
+    }
+
+    pub fn solo(&self) -> bool {
+        self.solo
+    }
+
+    // ignored on the first layer of a stack, which every other layer blends onto
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    pub fn curve_nodes(&self) -> &[AnimationCurveNode] {
+        &self.curve_nodes
+    }
+
+    // the curve node animating `property` of `object` in this layer
+    pub fn curve_node(&self, object: ObjectId, property: &str) -> Option<&AnimationCurveNode> {
+        self.curve_nodes.iter().find(|w| w.target() == Some((object, property)))
+    }
+}
+
+/// An animated channel of a take, as returned by `AnimationStack::channels`.
+#[derive(Debug, Clone, Copy)]
+pub struct AnimatedChannel<'a> {
+    pub layer: &'a AnimationLayer,
+    pub curve_node: &'a AnimationCurveNode,
+    pub channel: &'a CurveChannel,
+}
+
+/// An `AnimationStack`, one take.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationStack {
+    id: ObjectId,
+    name: String,
+    local_start: i64,
+    local_stop: i64,
+    reference_start: i64,
+    reference_stop: i64,
+    layers: Vec<AnimationLayer>,
+}
+
+impl AnimationStack {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<AnimationStack, SceneError> {
+        of_class(graph, id, "AnimationStack")?;
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+        let properties = graph.properties(id).unwrap_or_default();
+
+        let mut layers = Vec::new();
+        for layer in children_of_class(graph, id, "AnimationLayer") {
+            layers.push(AnimationLayer::from_graph(graph, layer)?);
+        }
+
+        let local_start = properties.get_i64("LocalStart").unwrap_or_default();
+        let local_stop = properties.get_i64("LocalStop").unwrap_or_default();
+
+        Ok(AnimationStack {
+            id,
+            name: object.name().to_owned(),
+            local_start,
+            local_stop,
+            reference_start: properties.get_i64("ReferenceStart").unwrap_or(local_start),
+            reference_stop: properties.get_i64("ReferenceStop").unwrap_or(local_stop),
+            layers,
+        })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // in ticks
+    pub fn local_start(&self) -> i64 {
+        self.local_start
+    }
+
+    pub fn local_stop(&self) -> i64 {
+        self.local_stop
+    }
+
+    pub fn reference_start(&self) -> i64 {
+        self.reference_start
+    }
+
+    pub fn reference_stop(&self) -> i64 {
+        self.reference_stop
+    }
+
+    pub fn layers(&self) -> &[AnimationLayer] {
+        &self.layers
+    }
+
+    // every channel of every layer that has a curve
+    pub fn channels(&self) -> Vec<AnimatedChannel<'_>> {
+        let mut channels = Vec::new();
+
+        for layer in self.layers.iter() {
+            for curve_node in layer.curve_nodes.iter() {
+                for channel in curve_node.channels.iter().filter(|w| w.curve.is_some()) {
+                    channels.push(AnimatedChannel { layer, curve_node, channel });
+                }
+            }
+        }
+
+        channels
+    }
+
+    // layers that play: all unmuted ones, or only the solo ones if any layer is solo
+    pub fn active_layers(&self) -> Vec<&AnimationLayer> {
+        let solo = self.layers.iter().any(|w| w.solo && !w.mute);
+        self.layers.iter().filter(|w| !w.mute && (!solo || w.solo)).collect()
+    }
+}
+
+// every take of the scene, in file order
+pub fn animation_stacks(graph: &ObjectGraph) -> Result<Vec<AnimationStack>, SceneError> {
+    let ids: Vec<ObjectId> = graph.objects_of_class("AnimationStack").map(|w| w.id()).collect();
+    let mut stacks = Vec::with_capacity(ids.len());
+
+    for id in ids {
+        stacks.push(AnimationStack::from_graph(graph, id)?);
+    }
+
+    Ok(stacks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use fbx::format::{Node, Type};
+
+    // an `AnimationCurve` with one attribute run per entry of `runs`: flags, data and reference count
+    fn curve(id: ObjectId, keys: &[(f64, f32)], runs: &[(i32, [f32; 4], i32)]) -> Node {
+        let times = keys.iter().map(|w| seconds_to_ticks(w.0)).collect();
+        let values = keys.iter().map(|w| w.1).collect();
+        let data = runs.iter().flat_map(|w| w.1).collect();
+
+        let children = vec![
+            number_node("Default", 0.0),
+            node("KeyTime", vec![Type::VecInt64(times)], vec![]),
+            node("KeyValueFloat", vec![Type::VecFloat32(values)], vec![]),
+            i32_array("KeyAttrFlags", &runs.iter().map(|w| w.0).collect::<Vec<_>>()),
+            node("KeyAttrDataFloat", vec![Type::VecFloat32(data)], vec![]),
+            i32_array("KeyAttrRefCount", &runs.iter().map(|w| w.2).collect::<Vec<_>>()),
+        ];
+
+        object("AnimationCurve", id, "", "", children)
+    }
+
+    fn number_node(name: &str, value: f64) -> Node {
+        node(name, vec![Type::Float64(value)], vec![])
+    }
+
+    fn curve_graph(keys: &[(f64, f32)], runs: &[(i32, [f32; 4], i32)]) -> AnimationCurve {
+        AnimationCurve::from_graph(&graph(vec![curve(1, keys, runs)], vec![]), 1).unwrap()
+    }
+
+    // a take with a base layer moving a model along X and a muted layer
+    fn take() -> ObjectGraph {
+        let linear = (INTERPOLATION_LINEAR, [0.0; 4], 2);
+        let stack = properties70(vec![property("LocalStart", "KTime", vec![Type::Int64(0)]), property("LocalStop", "KTime", vec![Type::Int64(TICKS_PER_SECOND)])]);
+        let translation = properties70(vec![number_property("d|X", 0.0), number_property("d|Y", 5.0), number_property("d|Z", 0.0)]);
+
+        let objects = vec![
+            object("Model", 1, "cube", "Mesh", vec![]),
+            object("AnimationStack", 2, "Take 001", "", vec![stack]),
+            object("AnimationLayer", 3, "Base", "", vec![]),
+            object("AnimationLayer", 4, "Muted", "", vec![properties70(vec![int_property("Mute", "bool", 1)])]),
+            object("AnimationCurveNode", 5, "T", "", vec![translation]),
+            curve(6, &[(0.0, 0.0), (1.0, 10.0)], &[linear]),
+        ];
+
+        graph(objects, vec![oo(3, 2), oo(4, 2), oo(5, 3), op(5, 1, "Lcl Translation"), op(6, 5, "d|X")])
+    }
+
+    #[test]
+    fn decodes_stacks_layers_and_curve_nodes() {
+        let stacks = animation_stacks(&take()).unwrap();
+        let stack = &stacks[0];
+
+        assert_eq!(stack.name(), "Take 001");
+        assert_eq!((stack.local_start(), stack.local_stop()), (0, TICKS_PER_SECOND));
+        assert_eq!((stack.reference_start(), stack.reference_stop()), (0, TICKS_PER_SECOND));
+        assert_eq!(stack.layers().iter().map(|w| w.name()).collect::<Vec<_>>(), ["Base", "Muted"]);
+        assert_eq!(stack.active_layers().iter().map(|w| w.name()).collect::<Vec<_>>(), ["Base"]);
+
+        let curve_node = stack.layers()[0].curve_node(1, "Lcl Translation").unwrap();
+        assert_eq!(curve_node.target(), Some((1, "Lcl Translation")));
+        assert_eq!(curve_node.evaluate(TICKS_PER_SECOND / 2), [5.0, 5.0, 0.0]);
+
+        let channels = stack.channels();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].channel.name, "d|X");
+        assert_eq!(channels[0].curve_node.id(), 5);
+    }
+
+    #[test]
+    fn expands_key_attribute_runs() {
+        let cubic = (INTERPOLATION_CUBIC | TANGENT_USER, [1.0, 2.0, 0.0, 0.0], 2);
+        let constant = (INTERPOLATION_CONSTANT, [0.0; 4], 1);
+        let curve = curve_graph(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0)], &[cubic, constant]);
+
+        let flags: Vec<i32> = curve.key_attributes().iter().map(|w| w.flags).collect();
+        assert_eq!(flags, [cubic.0, cubic.0, constant.0, constant.0]);
+        assert_eq!(curve.keys()[1].interpolation, Interpolation::Cubic);
+        assert_eq!(curve.keys()[1].tangent_mode, TangentMode::User);
+        assert_eq!(curve.keys()[3].interpolation, Interpolation::Constant);
+        assert_eq!(curve.key_times()[1], TICKS_PER_SECOND);
+    }
+
+    // the `KeyAttrDataFloat` entry holding a right and a next left weight
+    fn weights(right: f64, next_left: f64) -> f32 {
+        let pack = |w: f64| (w * 9999.0).round() as i16 as u16 as u32;
+        f32::from_bits(pack(right) | pack(next_left) << 16)
+    }
+
+    fn at(curve: &AnimationCurve, seconds: f64) -> f64 {
+        curve.evaluate_seconds(seconds)
+    }
+
+    // The expected values are worked out by hand from the segment's bezier form, with handles at
+    // `value ± weight * duration * slope` and time advancing linearly for unweighted keys.
+    #[test]
+    fn evaluates_constant_and_linear_keys() {
+        let keys = [(0.0, 0.0), (1.0, 10.0), (2.0, 4.0)];
+
+        let constant = curve_graph(&keys, &[(INTERPOLATION_CONSTANT, [0.0; 4], 3)]);
+        assert_eq!([at(&constant, -1.0), at(&constant, 0.5), at(&constant, 1.0), at(&constant, 1.5), at(&constant, 3.0)], [0.0, 0.0, 10.0, 10.0, 4.0]);
+
+        let next = curve_graph(&keys, &[(INTERPOLATION_CONSTANT | CONSTANT_NEXT, [0.0; 4], 3)]);
+        assert_eq!([at(&next, 0.0), at(&next, 0.5), at(&next, 1.0), at(&next, 1.5), at(&next, 2.0)], [0.0, 10.0, 10.0, 4.0, 4.0]);
+
+        let linear = curve_graph(&keys, &[(INTERPOLATION_LINEAR, [0.0; 4], 3)]);
+        assert_all_close(&[at(&linear, 0.25), at(&linear, 1.5)], &[2.5, 7.0], 1e-9);
+
+        // the interpolation of a key applies to the segment after it
+        let mixed = curve_graph(&keys, &[(INTERPOLATION_CONSTANT, [0.0; 4], 1), (INTERPOLATION_LINEAR, [0.0; 4], 2)]);
+        assert_all_close(&[at(&mixed, 0.5), at(&mixed, 1.5)], &[0.0, 7.0], 1e-9);
+    }
+
+    #[test]
+    fn evaluates_cubic_tangents() {
+        let keys = [(0.0, 0.0), (1.0, 1.0)];
+
+        // flat auto tangents make the segment a smoothstep
+        let auto = curve_graph(&keys, &[(INTERPOLATION_CUBIC, [0.0; 4], 2)]);
+        assert_all_close(&[at(&auto, 0.25), at(&auto, 0.5), at(&auto, 0.75)], &[0.15625, 0.5, 0.84375], 1e-6);
+
+        // a user slope of 2 leaving the first key: handles at 2/3 and 1
+        let user = curve_graph(&keys, &[(INTERPOLATION_CUBIC | TANGENT_USER, [2.0, 0.0, 0.0, 0.0], 2)]);
+        assert_eq!(user.keys()[0].tangent_mode, TangentMode::User);
+        assert_all_close(&[at(&user, 0.25), at(&user, 0.5)], &[0.4375, 0.75], 1e-6);
+
+        // broken tangents: the middle key arrives with slope 4 and leaves with slope -4
+        let keys = [(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)];
+        let broken = curve_graph(&keys, &[(INTERPOLATION_CUBIC | TANGENT_BREAK, [0.0, 4.0, 0.0, 0.0], 1), (INTERPOLATION_CUBIC | TANGENT_BREAK, [-4.0, 0.0, 0.0, 0.0], 2)]);
+        assert_eq!(broken.keys()[1].tangent_mode, TangentMode::Break);
+        assert_all_close(&[at(&broken, 0.5), at(&broken, 0.75), at(&broken, 1.25)], &[0.0, 0.28125, 0.28125], 1e-6);
+    }
+
+    #[test]
+    fn evaluates_weighted_tangents() {
+        let keys = [(0.0, 0.0), (1.0, 1.0)];
+        let weighted = INTERPOLATION_CUBIC | TANGENT_USER | WEIGHTED_RIGHT | WEIGHTED_NEXT_LEFT;
+
+        // equal weights keep the time curve symmetric, so the middle of the segment is at u = 1/2
+        let curve = curve_graph(&keys, &[(weighted, [2.0, 0.0, weights(0.5, 0.5), 0.0], 2)]);
+        assert_close(curve.keys()[0].right_weight, 0.5, 1e-4);
+        assert_close(at(&curve, 0.5), 0.875, 1e-3);
+
+        // weights of 1/3 reduce to the unweighted segment
+        let third = curve_graph(&keys, &[(weighted, [2.0, 0.0, weights(DEFAULT_WEIGHT, DEFAULT_WEIGHT), 0.0], 2)]);
+        assert_close(at(&third, 0.25), 0.4375, 1e-3);
+
+        // unweighted keys ignore whatever the weight entry holds
+        let unweighted = curve_graph(&keys, &[(INTERPOLATION_CUBIC | TANGENT_USER, [2.0, 0.0, weights(0.9, 0.9), 0.0], 2)]);
+        assert_eq!(unweighted.keys()[0].right_weight, DEFAULT_WEIGHT);
+    }
+
+    #[test]
+    fn evaluates_tangent_velocity() {
+        let keys = [(0.0, 0.0), (1.0, 1.0)];
+        let flags = INTERPOLATION_CUBIC | TANGENT_USER | VELOCITY_RIGHT | VELOCITY_NEXT_LEFT;
+
+        let decoded = curve_graph(&keys, &[(flags, [2.0, 0.0, 0.0, weights(0.25, -0.5)], 2)]);
+        assert_close(decoded.keys()[0].right_velocity, 0.25, 1e-4);
+        assert_close(decoded.keys()[0].next_left_velocity, -0.5, 1e-4);
+
+        // a right velocity of 0.5 moves the first handle from 1/3 to 1/2 of the segment: with flat tangents the
+        // control points are (0, 0), (1/2, 0), (2/3, 1) and (1, 1), and u = 1/2 lands at time 0.5625 with value 0.5
+        let faster = curve_graph(&keys, &[(flags, [0.0, 0.0, 0.0, weights(0.5, 0.0)], 2)]);
+        // the packed velocity is only precise to 1/9999
+        assert_close(at(&faster, 0.5625), 0.5, 1e-4);
+
+        // without velocity the same time is 0.5625 along a smoothstep
+        let flat = curve_graph(&keys, &[(INTERPOLATION_CUBIC | TANGENT_USER, [0.0; 4], 2)]);
+        assert_close(at(&flat, 0.5625), 0.59326171875, 1e-6);
+
+        // velocity only counts when flagged
+        let unflagged = curve_graph(&keys, &[(INTERPOLATION_CUBIC | TANGENT_USER, [0.0, 0.0, 0.0, weights(0.5, 0.0)], 2)]);
+        assert_close(at(&unflagged, 0.5625), 0.59326171875, 1e-6);
+    }
+
+    #[test]
+    fn rejects_mismatched_arrays() {
+        let mut broken = curve(1, &[(0.0, 0.0), (1.0, 1.0)], &[(INTERPOLATION_LINEAR, [0.0; 4], 2)]);
+        broken.children_mut()[2] = node("KeyValueFloat", vec![Type::VecFloat32(vec![0.0])], vec![]);
+        assert!(matches!(AnimationCurve::from_graph(&graph(vec![broken], vec![]), 1), Err(SceneError::InvalidArrayLength(_, 1))));
+
+        let mut broken = curve(1, &[(0.0, 0.0)], &[(INTERPOLATION_LINEAR, [0.0; 4], 1)]);
+        broken.children_mut()[4] = node("KeyAttrDataFloat", vec![Type::VecFloat32(vec![0.0; 3])], vec![]);
+        assert!(matches!(AnimationCurve::from_graph(&graph(vec![broken], vec![]), 1), Err(SceneError::InvalidArrayLength(_, 3))));
+
+        assert!(matches!(AnimationStack::from_graph(&take(), 3), Err(SceneError::UnexpectedObject(3, _))));
+    }
+}
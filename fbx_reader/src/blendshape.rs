@@ -0,0 +1,328 @@
+use crate::error::SceneError;
+use crate::graph::{is_class, ObjectGraph, ObjectId};
+use crate::math::Vector3;
+use crate::mesh::{child_f64_array, child_i32_array, to_vectors};
+
+fn densify(name: &str, indices: &[u32], deltas: &[Vector3], control_point_count: usize) -> Result<Vec<Vector3>, SceneError> {
+    if indices.len() != deltas.len() {
+        return Err(SceneError::InvalidArrayLength(name.to_owned(), deltas.len() * 3));
+    }
+
+    let mut dense = vec![[0.0; 3]; control_point_count];
+
+    for (index, delta) in indices.iter().zip(deltas.iter()) {
+        let slot = dense.get_mut(*index as usize).ok_or(SceneError::IndexOutOfRange(*index as i64, control_point_count))?;
+        *slot = *delta;
+    }
+
+    Ok(dense)
+}
+
+/// A `Geometry` of subclass `Shape`: offsets of some control points of the base mesh.
+#[derive(Debug, Clone)]
+pub struct Shape {
+    id: ObjectId,
+    name: String,
+    indices: Vec<u32>,
+    position_deltas: Vec<Vector3>,
+    normal_deltas: Option<Vec<Vector3>>,
+}
+
+impl Shape {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Shape, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if !is_class(graph, id, "Geometry", "Shape") {
+            return Err(SceneError::UnexpectedObject(id, "Geometry::Shape".to_owned()));
+        }
+
+        let node = object.node();
+        let indices = child_i32_array(node, "Indexes").unwrap_or_default();
+        let position_deltas = to_vectors("Vertices", &child_f64_array(node, "Vertices").unwrap_or_default())?;
+        let normal_deltas = match child_f64_array(node, "Normals") {
+            Some(normals) => Some(to_vectors("Normals", &normals)?),
+            None => None,
+        };
+
+        if position_deltas.len() != indices.len() {
+            return Err(SceneError::InvalidArrayLength("Vertices".to_owned(), position_deltas.len() * 3));
+        }
+
+        if let Some(index) = indices.iter().find(|w| **w < 0) {
+            return Err(SceneError::IndexOutOfRange(*index as i64, 0));
+        }
+
+        Ok(Shape { id, name: object.name().to_owned(), indices: indices.into_iter().map(|w| w as u32).collect(), position_deltas, normal_deltas })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // control points the shape moves, parallel to the deltas
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    pub fn position_deltas(&self) -> &[Vector3] {
+        &self.position_deltas
+    }
+
+    pub fn normal_deltas(&self) -> Option<&[Vector3]> {
+        self.normal_deltas.as_deref()
+    }
+
+    // position delta of every control point of the base mesh, zero where the shape does not move it
+    pub fn dense_position_deltas(&self, control_point_count: usize) -> Result<Vec<Vector3>, SceneError> {
+        densify("Vertices", &self.indices, &self.position_deltas, control_point_count)
+    }
+
+    pub fn dense_normal_deltas(&self, control_point_count: usize) -> Result<Option<Vec<Vector3>>, SceneError> {
+        match &self.normal_deltas {
+            Some(normals) => Ok(Some(densify("Normals", &self.indices, normals, control_point_count)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A `Deformer` of subclass `BlendShapeChannel`, one slider with its target and in-between shapes.
+#[derive(Debug, Clone)]
+pub struct BlendShapeChannel {
+    id: ObjectId,
+    name: String,
+    deform_percent: f64,
+    full_weights: Vec<f64>,
+    shapes: Vec<Shape>,
+}
+
+impl BlendShapeChannel {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<BlendShapeChannel, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if !is_class(graph, id, "Deformer", "BlendShapeChannel") {
+            return Err(SceneError::UnexpectedObject(id, "Deformer::BlendShapeChannel".to_owned()));
+        }
+
+        let mut shapes = Vec::new();
+        for shape in graph.children(id).into_iter().filter(|w| is_class(graph, *w, "Geometry", "Shape")) {
+            shapes.push(Shape::from_graph(graph, shape)?);
+        }
+
+        // without `FullWeights` the shapes are spread evenly up to 100%
+        let full_weights = child_f64_array(object.node(), "FullWeights").filter(|w| w.len() == shapes.len());
+        let full_weights = full_weights.unwrap_or_else(|| (1..=shapes.len()).map(|w| w as f64 * 100.0 / shapes.len() as f64).collect());
+
+        let deform_percent = graph.properties(id).and_then(|w| w.get_f64("DeformPercent")).unwrap_or_default();
+
+        Ok(BlendShapeChannel { id, name: object.name().to_owned(), deform_percent, full_weights, shapes })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // current weight in percent
+    pub fn deform_percent(&self) -> f64 {
+        self.deform_percent
+    }
+
+    // weight in percent at which each shape is reached, parallel to `shapes`
+    pub fn full_weights(&self) -> &[f64] {
+        &self.full_weights
+    }
+
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    // the shape reached at the highest weight
+    pub fn target(&self) -> Option<&Shape> {
+        self.ordered().last().map(|w| &self.shapes[*w])
+    }
+
+    // shapes reached before the target, in weight order
+    pub fn in_betweens(&self) -> Vec<&Shape> {
+        let ordered = self.ordered();
+        ordered[..ordered.len().saturating_sub(1)].iter().map(|w| &self.shapes[*w]).collect()
+    }
+
+    fn ordered(&self) -> Vec<usize> {
+        let mut ordered: Vec<usize> = (0..self.shapes.len()).collect();
+        ordered.sort_by(|a, b| self.full_weights[*a].total_cmp(&self.full_weights[*b]));
+        ordered
+    }
+
+    // how much of each shape to add at `percent`, blending linearly between neighbouring in-betweens
+    pub fn shape_weights(&self, percent: f64) -> Vec<(usize, f64)> {
+        let ordered = self.ordered();
+        let (mut previous, mut previous_weight): (Option<usize>, f64) = (None, 0.0);
+
+        for (position, shape) in ordered.iter().enumerate() {
+            let weight = self.full_weights[*shape];
+            let is_last = position + 1 == ordered.len();
+
+            if percent <= weight || is_last {
+                let span = weight - previous_weight;
+                let t = if span.abs() > f64::EPSILON { (percent - previous_weight) / span } else { 1.0 };
+
+                return match previous {
+                    Some(previous) if t < 1.0 => vec![(previous, 1.0 - t), (*shape, t)],
+                    _ => vec![(*shape, t)],
+                };
+            }
+
+            previous = Some(*shape);
+            previous_weight = weight;
+        }
+
+        Vec::new()
+    }
+
+    // position delta of every control point at `percent`
+    pub fn evaluate(&self, percent: f64, control_point_count: usize) -> Result<Vec<Vector3>, SceneError> {
+        let mut result = vec![[0.0; 3]; control_point_count];
+
+        for (shape, weight) in self.shape_weights(percent) {
+            let shape = &self.shapes[shape];
+
+            for (index, delta) in shape.indices.iter().zip(shape.position_deltas.iter()) {
+                let slot = result.get_mut(*index as usize).ok_or(SceneError::IndexOutOfRange(*index as i64, control_point_count))?;
+                for axis in 0..3 {
+                    slot[axis] += delta[axis] * weight;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// A `Deformer` of subclass `BlendShape`, grouping the channels of one mesh.
+#[derive(Debug, Clone)]
+pub struct BlendShape {
+    id: ObjectId,
+    name: String,
+    channels: Vec<BlendShapeChannel>,
+}
+
+impl BlendShape {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<BlendShape, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if !is_class(graph, id, "Deformer", "BlendShape") {
+            return Err(SceneError::UnexpectedObject(id, "Deformer::BlendShape".to_owned()));
+        }
+
+        let mut channels = Vec::new();
+        for channel in graph.children(id).into_iter().filter(|w| is_class(graph, *w, "Deformer", "BlendShapeChannel")) {
+            channels.push(BlendShapeChannel::from_graph(graph, channel)?);
+        }
+
+        Ok(BlendShape { id, name: object.name().to_owned(), channels })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn channels(&self) -> &[BlendShapeChannel] {
+        &self.channels
+    }
+
+    pub fn channel(&self, name: &str) -> Option<&BlendShapeChannel> {
+        self.channels.iter().find(|w| w.name == name)
+    }
+}
+
+// blend shape deformers of a geometry, in connection order
+pub fn geometry_blend_shapes(graph: &ObjectGraph, geometry: ObjectId) -> Result<Vec<BlendShape>, SceneError> {
+    let mut blend_shapes = Vec::new();
+
+    for blend_shape in graph.children(geometry).into_iter().filter(|w| is_class(graph, *w, "Deformer", "BlendShape")) {
+        blend_shapes.push(BlendShape::from_graph(graph, blend_shape)?);
+    }
+
+    Ok(blend_shapes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn shape(id: ObjectId, name: &str, indices: &[i32], vertices: &[f64]) -> fbx::format::Node {
+        object("Geometry", id, name, "Shape", vec![i32_array("Indexes", indices), f64_array("Vertices", vertices), f64_array("Normals", &vec![0.0; vertices.len()])])
+    }
+
+    // a smile channel with a half-way shape at 50% and the target at 100%, on a mesh with three control points
+    fn smile(full_weights: Option<&[f64]>) -> ObjectGraph {
+        let mut channel = vec![properties70(vec![number_property("DeformPercent", 40.0)])];
+        channel.extend(full_weights.map(|w| f64_array("FullWeights", w)));
+
+        let objects = vec![
+            object("Geometry", 1, "face", "Mesh", vec![]),
+            object("Deformer", 2, "face", "BlendShape", vec![]),
+            object("Deformer", 3, "smile", "BlendShapeChannel", channel),
+            shape(4, "smile_full", &[0, 2], &[0.0, 2.0, 0.0, 0.0, 0.0, 4.0]),
+            shape(5, "smile_half", &[2], &[0.0, 0.0, 1.0]),
+        ];
+
+        graph(objects, vec![oo(2, 1), oo(3, 2), oo(4, 3), oo(5, 3)])
+    }
+
+    #[test]
+    fn decodes_channels_and_shapes() {
+        let graph = smile(Some(&[100.0, 50.0]));
+        let blend_shapes = geometry_blend_shapes(&graph, 1).unwrap();
+        let channel = blend_shapes[0].channel("smile").unwrap();
+
+        assert_eq!(channel.deform_percent(), 40.0);
+        assert_eq!(channel.target().unwrap().name(), "smile_full");
+        assert_eq!(channel.in_betweens().iter().map(|w| w.name()).collect::<Vec<_>>(), ["smile_half"]);
+        assert_eq!(channel.shapes()[0].dense_position_deltas(3).unwrap(), [[0.0, 2.0, 0.0], [0.0; 3], [0.0, 0.0, 4.0]]);
+        assert_eq!(channel.shapes()[1].dense_normal_deltas(3).unwrap().unwrap().len(), 3);
+        assert!(matches!(channel.shapes()[0].dense_position_deltas(2), Err(SceneError::IndexOutOfRange(2, 2))));
+    }
+
+    #[test]
+    fn blends_between_in_between_shapes() {
+        let graph = smile(Some(&[100.0, 50.0]));
+        let channel = BlendShapeChannel::from_graph(&graph, 3).unwrap();
+
+        assert_eq!(channel.shape_weights(25.0), [(1, 0.5)]);
+        assert_eq!(channel.shape_weights(75.0), [(1, 0.5), (0, 0.5)]);
+        assert_eq!(channel.shape_weights(100.0), [(0, 1.0)]);
+
+        assert_eq!(channel.evaluate(25.0, 3).unwrap(), [[0.0; 3], [0.0; 3], [0.0, 0.0, 0.5]]);
+        assert_eq!(channel.evaluate(75.0, 3).unwrap(), [[0.0, 1.0, 0.0], [0.0; 3], [0.0, 0.0, 2.5]]);
+    }
+
+    #[test]
+    fn spreads_shapes_evenly_without_full_weights() {
+        let channel = BlendShapeChannel::from_graph(&smile(None), 3).unwrap();
+
+        assert_eq!(channel.full_weights(), [50.0, 100.0]);
+        assert_eq!(channel.target().unwrap().name(), "smile_half");
+    }
+
+    #[test]
+    fn rejects_mismatched_shapes() {
+        let objects = vec![shape(4, "broken", &[0, 1], &[0.0, 1.0, 0.0])];
+        assert!(matches!(Shape::from_graph(&graph(objects, vec![]), 4), Err(SceneError::InvalidArrayLength(_, 3))));
+
+        let objects = vec![shape(4, "negative", &[-1], &[0.0, 1.0, 0.0])];
+        assert!(matches!(Shape::from_graph(&graph(objects, vec![]), 4), Err(SceneError::IndexOutOfRange(-1, 0))));
+    }
+}
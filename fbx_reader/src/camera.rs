@@ -0,0 +1,260 @@
+use crate::error::SceneError;
+use crate::graph::{is_class, ObjectGraph, ObjectId};
+
+// film back sizes are stored in inches, focal lengths in millimeters
+const MILLIMETERS_PER_INCH: f64 = 25.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionType {
+    Perspective,
+    Orthographic,
+}
+
+impl ProjectionType {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            1 => ProjectionType::Orthographic,
+            _ => ProjectionType::Perspective,
+        }
+    }
+}
+
+/// Which of the stored values defines the field of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApertureMode {
+    // `FieldOfViewX` and `FieldOfViewY`
+    HorizontalAndVertical,
+    // `FieldOfView` is horizontal
+    Horizontal,
+    // `FieldOfView` is vertical
+    Vertical,
+    // `FocalLength` with the film back
+    FocalLength,
+}
+
+impl ApertureMode {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            0 => ApertureMode::HorizontalAndVertical,
+            1 => ApertureMode::Horizontal,
+            3 => ApertureMode::FocalLength,
+            _ => ApertureMode::Vertical,
+        }
+    }
+}
+
+/// A `NodeAttribute` of subclass `Camera`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera {
+    id: ObjectId,
+    name: String,
+    projection: ProjectionType,
+    aperture_mode: ApertureMode,
+    field_of_view: f64,
+    field_of_view_x: f64,
+    field_of_view_y: f64,
+    focal_length: f64,
+    film_width: f64,
+    film_height: f64,
+    aspect_width: f64,
+    aspect_height: f64,
+    near_plane: f64,
+    far_plane: f64,
+    ortho_zoom: f64,
+}
+
+impl Camera {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Camera, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if !is_class(graph, id, "NodeAttribute", "Camera") {
+            return Err(SceneError::UnexpectedObject(id, "NodeAttribute::Camera".to_owned()));
+        }
+
+        // defaults are the SDK's, for files written without a camera template
+        let properties = graph.properties(id).unwrap_or_default();
+        let number = |name: &str, default: f64| properties.get_f64(name).unwrap_or(default);
+
+        Ok(Camera {
+            id,
+            name: object.name().to_owned(),
+            projection: ProjectionType::from_fbx(properties.get_i64("CameraProjectionType").unwrap_or_default()),
+            aperture_mode: ApertureMode::from_fbx(properties.get_i64("ApertureMode").unwrap_or(2)),
+            field_of_view: number("FieldOfView", 25.114999),
+            field_of_view_x: number("FieldOfViewX", 40.0),
+            field_of_view_y: number("FieldOfViewY", 40.0),
+            focal_length: number("FocalLength", 34.89327),
+            film_width: number("FilmWidth", 0.816),
+            film_height: number("FilmHeight", 0.612),
+            aspect_width: number("AspectWidth", 320.0),
+            aspect_height: number("AspectHeight", 200.0),
+            near_plane: number("NearPlane", 10.0),
+            far_plane: number("FarPlane", 4000.0),
+            ortho_zoom: number("OrthoZoom", 1.0),
+        })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn projection(&self) -> ProjectionType {
+        self.projection
+    }
+
+    pub fn aperture_mode(&self) -> ApertureMode {
+        self.aperture_mode
+    }
+
+    // in degrees, horizontal or vertical depending on the aperture mode
+    pub fn field_of_view(&self) -> f64 {
+        self.field_of_view
+    }
+
+    // in millimeters
+    pub fn focal_length(&self) -> f64 {
+        self.focal_length
+    }
+
+    // film back in inches
+    pub fn film_size(&self) -> (f64, f64) {
+        (self.film_width, self.film_height)
+    }
+
+    // image width divided by height
+    pub fn aspect_ratio(&self) -> f64 {
+        if self.aspect_height > 0.0 { self.aspect_width / self.aspect_height } else { 1.0 }
+    }
+
+    // clip plane distances in scene units
+    pub fn near_plane(&self) -> f64 {
+        self.near_plane
+    }
+
+    pub fn far_plane(&self) -> f64 {
+        self.far_plane
+    }
+
+    pub fn ortho_zoom(&self) -> f64 {
+        self.ortho_zoom
+    }
+
+    // vertical field of view in radians, derived from whatever the aperture mode says is authoritative
+    pub fn vertical_fov(&self) -> f64 {
+        match self.aperture_mode {
+            ApertureMode::Vertical => self.field_of_view.to_radians(),
+            ApertureMode::HorizontalAndVertical => self.field_of_view_y.to_radians(),
+            ApertureMode::Horizontal => 2.0 * ((self.field_of_view.to_radians() / 2.0).tan() / self.aspect_ratio()).atan(),
+            ApertureMode::FocalLength => self.fov_from_film(self.film_height),
+        }
+    }
+
+    // horizontal field of view in radians
+    pub fn horizontal_fov(&self) -> f64 {
+        match self.aperture_mode {
+            ApertureMode::Horizontal => self.field_of_view.to_radians(),
+            ApertureMode::HorizontalAndVertical => self.field_of_view_x.to_radians(),
+            ApertureMode::Vertical => 2.0 * ((self.field_of_view.to_radians() / 2.0).tan() * self.aspect_ratio()).atan(),
+            ApertureMode::FocalLength => self.fov_from_film(self.film_width),
+        }
+    }
+
+    fn fov_from_film(&self, size: f64) -> f64 {
+        if self.focal_length > 0.0 {
+            2.0 * (size * MILLIMETERS_PER_INCH / (2.0 * self.focal_length)).atan()
+        } else {
+            0.0
+        }
+    }
+}
+
+// the camera settings linked to a model, if it is a camera
+pub fn model_camera(graph: &ObjectGraph, model: ObjectId) -> Result<Option<Camera>, SceneError> {
+    match graph.children(model).into_iter().find(|w| is_class(graph, *w, "NodeAttribute", "Camera")) {
+        Some(camera) => Ok(Some(Camera::from_graph(graph, camera)?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use fbx::format::Node;
+
+    fn camera(properties: Vec<Node>) -> Camera {
+        let graph = graph(vec![object("NodeAttribute", 1, "Camera", "Camera", vec![properties70(properties)])], vec![]);
+        Camera::from_graph(&graph, 1).unwrap()
+    }
+
+    fn aspect(width: f64, height: f64) -> Vec<Node> {
+        vec![number_property("AspectWidth", width), number_property("AspectHeight", height)]
+    }
+
+    #[test]
+    fn derives_the_horizontal_fov_from_a_vertical_one() {
+        let mut properties = aspect(320.0, 200.0);
+        properties.extend([int_property("ApertureMode", "enum", 2), number_property("FieldOfView", 40.0)]);
+        let camera = camera(properties);
+
+        assert_eq!(camera.aperture_mode(), ApertureMode::Vertical);
+        assert_close(camera.vertical_fov(), 40f64.to_radians(), 1e-12);
+        assert_close(camera.horizontal_fov(), 1.0546844581472943, 1e-12);
+    }
+
+    #[test]
+    fn derives_the_vertical_fov_from_a_horizontal_one() {
+        let mut properties = aspect(1920.0, 1080.0);
+        properties.extend([int_property("ApertureMode", "enum", 1), number_property("FieldOfView", 60.0)]);
+        let camera = camera(properties);
+
+        assert_close(camera.horizontal_fov(), 60f64.to_radians(), 1e-12);
+        assert_close(camera.vertical_fov(), 0.6280287671649805, 1e-12);
+    }
+
+    #[test]
+    fn reads_both_fovs_when_both_are_stored() {
+        let camera = camera(vec![
+            int_property("ApertureMode", "enum", 0),
+            number_property("FieldOfView", 10.0),
+            number_property("FieldOfViewX", 50.0),
+            number_property("FieldOfViewY", 30.0),
+        ]);
+
+        assert_close(camera.horizontal_fov(), 50f64.to_radians(), 1e-12);
+        assert_close(camera.vertical_fov(), 30f64.to_radians(), 1e-12);
+    }
+
+    #[test]
+    fn derives_fovs_from_the_focal_length_and_film_back() {
+        // a 1 by 0.5 inch film back behind a 25.4 millimeter lens
+        let focal = |focal_length: f64| {
+            camera(vec![
+                int_property("ApertureMode", "enum", 3),
+                number_property("FocalLength", focal_length),
+                number_property("FilmWidth", 1.0),
+                number_property("FilmHeight", 0.5),
+            ])
+        };
+
+        let camera = focal(25.4);
+        assert_close(camera.horizontal_fov(), 2.0 * 0.5f64.atan(), 1e-12);
+        assert_close(camera.vertical_fov(), 2.0 * 0.25f64.atan(), 1e-12);
+        assert_eq!(focal(0.0).vertical_fov(), 0.0);
+    }
+
+    #[test]
+    fn falls_back_to_the_sdk_defaults() {
+        assert_eq!(camera(aspect(320.0, 0.0)).aspect_ratio(), 1.0);
+
+        let camera = camera(vec![]);
+        assert_eq!((camera.projection(), camera.aperture_mode()), (ProjectionType::Perspective, ApertureMode::Vertical));
+        assert_eq!(camera.aspect_ratio(), 1.6);
+        assert_close(camera.vertical_fov(), 25.114999f64.to_radians(), 1e-12);
+        assert_eq!(camera.film_size(), (0.816, 0.612));
+    }
+}
@@ -0,0 +1,361 @@
+use crate::error::SceneError;
+use crate::graph::{ConnectionKind, ObjectGraph, ObjectId};
+use crate::math::Vector3;
+use crate::mesh::child;
+use crate::properties::Properties;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintType {
+    Position,
+    Rotation,
+    Scale,
+    Parent,
+    Aim,
+    SingleChainIk,
+    Other(String),
+}
+
+impl ConstraintType {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "Position From Positions" => ConstraintType::Position,
+            "Rotation From Rotations" => ConstraintType::Rotation,
+            "Scale From Scales" => ConstraintType::Scale,
+            "Parent-Child" => ConstraintType::Parent,
+            "Aim" => ConstraintType::Aim,
+            "Single Chain IK" => ConstraintType::SingleChainIk,
+            other => ConstraintType::Other(other.to_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldUpType {
+    SceneUp,
+    ObjectUp,
+    ObjectRotationUp,
+    Vector,
+    None,
+}
+
+impl WorldUpType {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            1 => WorldUpType::ObjectUp,
+            2 => WorldUpType::ObjectRotationUp,
+            3 => WorldUpType::Vector,
+            4 => WorldUpType::None,
+            _ => WorldUpType::SceneUp,
+        }
+    }
+}
+
+/// An object driving a constraint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintSource {
+    pub object: ObjectId,
+    // from `<name>.Weight`, 0 to 1
+    pub weight: f64,
+    // `<name>.Offset T` and `<name>.Offset R` of parent constraints
+    pub translation_offset: Option<Vector3>,
+    pub rotation_offset: Option<Vector3>,
+}
+
+/// What only some kinds of constraint have.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstraintSettings {
+    // offset added to the blended value of the sources: a translation, euler angles or a scaling
+    Position { offset: Vector3 },
+    Rotation { offset: Vector3 },
+    Scale { offset: Vector3 },
+    Parent,
+    Aim { aim_vector: Vector3, up_vector: Vector3, world_up_type: WorldUpType, world_up_vector: Vector3, world_up_object: Option<ObjectId>, offset: Vector3 },
+    SingleChainIk { first_joint: Option<ObjectId>, end_joint: Option<ObjectId>, pole_vector_object: Option<ObjectId>, pole_vector: Vector3, twist: f64 },
+    Other,
+}
+
+/// A `Constraint` object with its connected objects resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    id: ObjectId,
+    name: String,
+    constraint_type: ConstraintType,
+    active: bool,
+    lock: bool,
+    weight: f64,
+    constrained: Option<ObjectId>,
+    sources: Vec<ConstraintSource>,
+    affect_translation: [bool; 3],
+    affect_rotation: [bool; 3],
+    affect_scaling: [bool; 3],
+    settings: ConstraintSettings,
+}
+
+fn affect(properties: &Properties, prefix: &str) -> [bool; 3] {
+    ["X", "Y", "Z"].map(|axis| properties.get_bool(&format!("{}{}", prefix, axis)).unwrap_or(true))
+}
+
+impl Constraint {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Constraint, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if object.class() != "Constraint" {
+            return Err(SceneError::UnexpectedObject(id, "Constraint".to_owned()));
+        }
+
+        let properties = graph.properties(id).unwrap_or_default();
+        // the `Type` child names the kind, the subclass is always `Constraint`
+        let type_name = child(object.node(), "Type").and_then(|w| w.attributes_slice().first()).and_then(|w| w.as_str());
+        let constraint_type = ConstraintType::parse(&type_name.unwrap_or_else(|| object.subclass().to_owned()));
+
+        // objects connect to named properties, whose spelling varies between Maya and MotionBuilder
+        let connected: Vec<(ObjectId, String)> = graph
+            .property_connections(id)
+            .into_iter()
+            .filter(|w| w.kind() == ConnectionKind::ObjectProperty)
+            .map(|w| (w.child(), w.parent_property().unwrap_or_default().to_lowercase()))
+            .collect();
+
+        let first = |prefix: &str| connected.iter().find(|(_, property)| property.starts_with(prefix)).map(|w| w.0);
+        let vector = |name: &str, default: Vector3| properties.get_vector3(name).unwrap_or(default);
+
+        let sources = connected
+            .iter()
+            .filter(|(_, property)| property.starts_with("source") || property.starts_with("aim at object"))
+            .map(|(source, _)| {
+                let name = graph.object(*source).map(|w| w.name().to_owned()).unwrap_or_default();
+
+                ConstraintSource {
+                    object: *source,
+                    weight: properties.get_f64(&format!("{}.Weight", name)).unwrap_or(100.0) / 100.0,
+                    translation_offset: properties.get_vector3(&format!("{}.Offset T", name)),
+                    rotation_offset: properties.get_vector3(&format!("{}.Offset R", name)),
+                }
+            })
+            .collect();
+
+        let (mut affect_translation, mut affect_rotation, mut affect_scaling) = ([true; 3], [true; 3], [true; 3]);
+
+        let settings = match constraint_type {
+            ConstraintType::Position => {
+                affect_translation = affect(&properties, "Affect");
+                ConstraintSettings::Position { offset: vector("Translation", [0.0; 3]) }
+            }
+            ConstraintType::Rotation => {
+                affect_rotation = affect(&properties, "Affect");
+                ConstraintSettings::Rotation { offset: vector("Rotation", [0.0; 3]) }
+            }
+            ConstraintType::Scale => {
+                affect_scaling = affect(&properties, "Affect");
+                ConstraintSettings::Scale { offset: vector("Scaling", [1.0; 3]) }
+            }
+            ConstraintType::Parent => {
+                affect_translation = affect(&properties, "AffectTranslation");
+                affect_rotation = affect(&properties, "AffectRotation");
+                affect_scaling = affect(&properties, "AffectScaling");
+                ConstraintSettings::Parent
+            }
+            ConstraintType::Aim => {
+                affect_rotation = affect(&properties, "Affect");
+                ConstraintSettings::Aim {
+                    aim_vector: vector("AimVector", [1.0, 0.0, 0.0]),
+                    up_vector: vector("UpVector", [0.0, 1.0, 0.0]),
+                    world_up_type: WorldUpType::from_fbx(properties.get_i64("WorldUpType").unwrap_or_default()),
+                    world_up_vector: vector("WorldUpVector", [0.0, 1.0, 0.0]),
+                    world_up_object: first("world up object"),
+                    offset: vector("RotationOffset", [0.0; 3]),
+                }
+            }
+            ConstraintType::SingleChainIk => ConstraintSettings::SingleChainIk {
+                first_joint: first("first joint"),
+                end_joint: first("end joint"),
+                pole_vector_object: first("pole vector object"),
+                pole_vector: vector("PoleVector", [0.0, 1.0, 0.0]),
+                twist: properties.get_f64("Twist").unwrap_or_default(),
+            },
+            ConstraintType::Other(_) => ConstraintSettings::Other,
+        };
+
+        // the IK effector takes the place of the constrained object
+        let constrained = first("constrained object").or_else(|| first("effector"));
+
+        Ok(Constraint {
+            id,
+            name: object.name().to_owned(),
+            constraint_type,
+            active: properties.get_bool("Active").unwrap_or(true),
+            lock: properties.get_bool("Lock").unwrap_or(false),
+            weight: properties.get_f64("Weight").unwrap_or(100.0) / 100.0,
+            constrained,
+            sources,
+            affect_translation,
+            affect_rotation,
+            affect_scaling,
+            settings,
+        })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn constraint_type(&self) -> &ConstraintType {
+        &self.constraint_type
+    }
+
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    pub fn lock(&self) -> bool {
+        self.lock
+    }
+
+    // 0 to 1
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    // the object the constraint moves
+    pub fn constrained(&self) -> Option<ObjectId> {
+        self.constrained
+    }
+
+    pub fn sources(&self) -> &[ConstraintSource] {
+        &self.sources
+    }
+
+    // axes the constraint drives, all of them for kinds that do not touch the channel
+    pub fn affect_translation(&self) -> [bool; 3] {
+        self.affect_translation
+    }
+
+    pub fn affect_rotation(&self) -> [bool; 3] {
+        self.affect_rotation
+    }
+
+    pub fn affect_scaling(&self) -> [bool; 3] {
+        self.affect_scaling
+    }
+
+    pub fn settings(&self) -> &ConstraintSettings {
+        &self.settings
+    }
+}
+
+// every constraint of the scene, in file order
+pub fn constraints(graph: &ObjectGraph) -> Result<Vec<Constraint>, SceneError> {
+    let ids: Vec<ObjectId> = graph.objects_of_class("Constraint").map(|w| w.id()).collect();
+    let mut constraints = Vec::with_capacity(ids.len());
+
+    for id in ids {
+        constraints.push(Constraint::from_graph(graph, id)?);
+    }
+
+    Ok(constraints)
+}
+
+// constraints moving `object`
+pub fn object_constraints(graph: &ObjectGraph, object: ObjectId) -> Result<Vec<Constraint>, SceneError> {
+    Ok(constraints(graph)?.into_iter().filter(|w| w.constrained == Some(object)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use fbx::format::Node;
+
+    fn constraint(id: ObjectId, name: &str, type_name: &str, properties: Vec<Node>) -> Node {
+        object("Constraint", id, name, "Constraint", vec![text_node("Type", type_name), properties70(properties)])
+    }
+
+    fn scene_graph() -> ObjectGraph {
+        let objects = vec![
+            object("Model", 1, "box", "Mesh", vec![]),
+            object("Model", 2, "left", "Null", vec![]),
+            object("Model", 3, "right", "Null", vec![]),
+            object("Model", 20, "thigh", "LimbNode", vec![]),
+            object("Model", 21, "ankle", "LimbNode", vec![]),
+            object("Model", 22, "foot_ik", "Null", vec![]),
+            constraint(10, "follow", "Parent-Child", vec![
+                number_property("Weight", 100.0),
+                number_property("left.Weight", 25.0),
+                vector_property("left.Offset T", "Vector", [1.0, 0.0, 0.0]),
+                number_property("right.Weight", 75.0),
+                int_property("AffectTranslationY", "bool", 0),
+            ]),
+            constraint(11, "look", "Aim", vec![int_property("Lock", "bool", 1), vector_property("AimVector", "Vector", [0.0, 0.0, 1.0]), int_property("WorldUpType", "enum", 1), int_property("AffectZ", "bool", 0)]),
+            constraint(12, "leg", "Single Chain IK", vec![vector_property("PoleVector", "Vector", [0.0, 0.0, 1.0]), number_property("Twist", 15.0), int_property("Active", "bool", 0)]),
+            constraint(13, "custom", "Spline IK", vec![]),
+        ];
+
+        let connections = vec![
+            op(1, 10, "Constrained object (Child)"),
+            op(2, 10, "Source (Parent)"),
+            op(3, 10, "Source (Parent)"),
+            op(1, 11, "Constrained Object"),
+            op(2, 11, "Aim At Object"),
+            op(3, 11, "World Up Object"),
+            op(20, 12, "First Joint"),
+            op(21, 12, "End Joint"),
+            op(22, 12, "Effector"),
+        ];
+
+        graph(objects, connections)
+    }
+
+    #[test]
+    fn decodes_parent_constraints() {
+        let constraint = Constraint::from_graph(&scene_graph(), 10).unwrap();
+
+        assert_eq!(constraint.constraint_type(), &ConstraintType::Parent);
+        assert_eq!((constraint.active(), constraint.lock(), constraint.weight()), (true, false, 1.0));
+        assert_eq!(constraint.constrained(), Some(1));
+        assert_eq!(
+            constraint.sources(),
+            [
+                ConstraintSource { object: 2, weight: 0.25, translation_offset: Some([1.0, 0.0, 0.0]), rotation_offset: None },
+                ConstraintSource { object: 3, weight: 0.75, translation_offset: None, rotation_offset: None },
+            ]
+        );
+        assert_eq!(constraint.affect_translation(), [true, false, true]);
+        assert_eq!(constraint.affect_rotation(), [true; 3]);
+    }
+
+    #[test]
+    fn decodes_aim_and_ik_settings() {
+        let graph = scene_graph();
+
+        let aim = Constraint::from_graph(&graph, 11).unwrap();
+        assert!(aim.lock());
+        assert_eq!(aim.sources().iter().map(|w| w.object).collect::<Vec<_>>(), [2]);
+        assert_eq!(aim.affect_rotation(), [true, true, false]);
+        assert_eq!(
+            aim.settings(),
+            &ConstraintSettings::Aim { aim_vector: [0.0, 0.0, 1.0], up_vector: [0.0, 1.0, 0.0], world_up_type: WorldUpType::ObjectUp, world_up_vector: [0.0, 1.0, 0.0], world_up_object: Some(3), offset: [0.0; 3] }
+        );
+
+        let ik = Constraint::from_graph(&graph, 12).unwrap();
+        assert!(!ik.active());
+        assert_eq!(ik.constrained(), Some(22));
+        assert_eq!(ik.settings(), &ConstraintSettings::SingleChainIk { first_joint: Some(20), end_joint: Some(21), pole_vector_object: None, pole_vector: [0.0, 0.0, 1.0], twist: 15.0 });
+
+        let custom = Constraint::from_graph(&graph, 13).unwrap();
+        assert_eq!(custom.constraint_type(), &ConstraintType::Other("Spline IK".to_owned()));
+        assert_eq!(custom.settings(), &ConstraintSettings::Other);
+    }
+
+    #[test]
+    fn finds_the_constraints_of_an_object() {
+        let graph = scene_graph();
+
+        assert_eq!(constraints(&graph).unwrap().len(), 4);
+        assert_eq!(object_constraints(&graph, 1).unwrap().iter().map(|w| w.id()).collect::<Vec<_>>(), [10, 11]);
+        assert!(matches!(Constraint::from_graph(&graph, 1), Err(SceneError::UnexpectedObject(1, _))));
+    }
+}
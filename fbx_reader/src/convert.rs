@@ -0,0 +1,705 @@
+use std::collections::{HashMap, HashSet};
+
+use fbx::format::{Node, Object, Type};
+
+use crate::error::SceneError;
+use crate::graph::{ObjectGraph, ObjectId};
+use crate::math::{Matrix4, RotationOrder, Vector3};
+use crate::properties::Properties;
+use crate::settings::{AxisSystem, GlobalSettings};
+
+const CHANNELS: [&str; 3] = ["d|X", "d|Y", "d|Z"];
+
+// distance-valued node attribute properties with the SDK default used when neither the object nor its template sets them
+const DISTANCE_PROPERTIES: [(&str, &str, f64); 8] = [
+    ("Camera", "NearPlane", 10.0),
+    ("Camera", "FarPlane", 4000.0),
+    ("Camera", "FocusDistance", 200.0),
+    ("Light", "DecayStart", 0.0),
+    ("Light", "NearAttenuationStart", 0.0),
+    ("Light", "NearAttenuationEnd", 0.0),
+    ("Light", "FarAttenuationStart", 0.0),
+    ("Light", "FarAttenuationEnd", 0.0),
+];
+
+fn is_distance_property(subclass: &str, property: &str) -> bool {
+    DISTANCE_PROPERTIES.iter().any(|(class, name, _)| *class == subclass && *name == property)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ConversionOptions {
+    pub axis_system: AxisSystem,
+    // centimeters per unit of the converted scene, e.g. 100.0 for meters
+    pub unit_scale_factor: f64,
+    // rewrite `GlobalSettings` so the converted file describes itself correctly
+    pub update_settings: bool,
+}
+
+impl ConversionOptions {
+    pub fn new(axis_system: AxisSystem, unit_scale_factor: f64) -> Self {
+        ConversionOptions { axis_system, unit_scale_factor, update_settings: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Channel {
+    Translation,
+    Rotation,
+    Scaling,
+    // an animated distance of a node attribute, e.g. a camera's `FocusDistance`
+    Distance,
+}
+
+impl Channel {
+    fn from_property(property: &str) -> Option<Self> {
+        match property {
+            "Lcl Translation" => Some(Channel::Translation),
+            "Lcl Rotation" => Some(Channel::Rotation),
+            "Lcl Scaling" => Some(Channel::Scaling),
+            _ => None,
+        }
+    }
+}
+
+// change of basis `C = s * M` where `M` is a signed axis permutation
+struct AxisMapping {
+    matrix: Matrix4,
+    inverse: Matrix4,
+    rotation: Matrix4,
+    scale: f64,
+    // target axis and sign of every source axis
+    targets: [(usize, f64); 3],
+    determinant: f64,
+}
+
+impl AxisMapping {
+    fn new(rotation: Matrix4, scale: f64) -> Self {
+        let mut targets = [(0, 1.0); 3];
+
+        for (source, target) in targets.iter_mut().enumerate() {
+            let column = rotation.column(source);
+            let axis = (0..3).find(|w| column[*w] != 0.0).unwrap_or(source);
+            *target = (axis, column[axis]);
+        }
+
+        AxisMapping {
+            matrix: rotation * Matrix4::scaling([scale; 3]),
+            inverse: Matrix4::scaling([1.0 / scale; 3]) * rotation.transpose(),
+            rotation,
+            scale,
+            targets,
+            determinant: rotation.determinant(),
+        }
+    }
+
+    fn point(&self, v: Vector3) -> Vector3 {
+        self.matrix.transform_vector(v)
+    }
+
+    fn direction(&self, v: Vector3) -> Vector3 {
+        self.rotation.transform_vector(v)
+    }
+
+    fn scaling(&self, v: Vector3) -> Vector3 {
+        let mut result = [1.0; 3];
+        for (source, (target, _)) in self.targets.iter().enumerate() {
+            result[*target] = v[source];
+        }
+        result
+    }
+
+    fn euler(&self, degrees: Vector3, order: RotationOrder) -> Vector3 {
+        let rotation = self.rotation * Matrix4::from_euler(degrees, order) * self.rotation.transpose();
+        rotation.to_euler(order)
+    }
+
+    // per channel mapping for rotations that are animated, the rotation order changes along
+    fn euler_channels(&self, degrees: Vector3) -> Vector3 {
+        let mut result = [0.0; 3];
+        for (source, (target, sign)) in self.targets.iter().enumerate() {
+            result[*target] = degrees[source] * sign * self.determinant;
+        }
+        result
+    }
+
+    fn rotation_order(&self, order: RotationOrder) -> RotationOrder {
+        let axes = order.axes().map(|w| self.targets[w].0);
+        RotationOrder::from_axes(axes).unwrap_or(order)
+    }
+
+    fn matrix(&self, m: Matrix4) -> Matrix4 {
+        self.matrix * m * self.inverse
+    }
+
+    // factor applied to the values of the channel that ends up on `source`'s target axis
+    fn channel_factor(&self, channel: Channel, source: usize) -> f64 {
+        let sign = self.targets[source].1;
+        match channel {
+            Channel::Translation => sign * self.scale,
+            Channel::Rotation => sign * self.determinant,
+            Channel::Scaling => 1.0,
+            Channel::Distance => self.scale,
+        }
+    }
+}
+
+fn child_mut<'a>(node: &'a mut Node, name: &str) -> Option<&'a mut Node> {
+    node.children_mut().iter_mut().find(|w| w.name_str() == name)
+}
+
+fn map_tuples(node: &mut Node, stride: usize, f: impl Fn(Vector3) -> Vector3) {
+    let apply = |values: &mut [f64]| {
+        for chunk in values.chunks_exact_mut(stride) {
+            let v = f([chunk[0], chunk[1], chunk[2]]);
+            chunk[..3].copy_from_slice(&v);
+        }
+    };
+
+    match node.attributes_mut().first_mut() {
+        Some(Type::VecFloat64(values)) => apply(values),
+        Some(Type::VecFloat32(values)) => {
+            let mut wide: Vec<f64> = values.iter().map(|w| *w as f64).collect();
+            apply(&mut wide);
+            *values = wide.into_iter().map(|w| w as f32).collect();
+        }
+        _ => {}
+    }
+}
+
+fn map_child_tuples(node: &mut Node, name: &str, stride: usize, f: impl Fn(Vector3) -> Vector3) {
+    if let Some(child) = child_mut(node, name) {
+        map_tuples(child, stride, f);
+    }
+}
+
+fn map_matrix(node: &mut Node, name: &str, mapping: &AxisMapping) {
+    let child = match child_mut(node, name) {
+        Some(child) => child,
+        None => return,
+    };
+
+    if let Some(Type::VecFloat64(values)) = child.attributes_mut().first_mut() {
+        if let Some(m) = Matrix4::from_slice(values) {
+            *values = mapping.matrix(m).as_array().to_vec();
+        }
+    }
+}
+
+fn property_node_mut<'a>(node: &'a mut Node, name: &str) -> Option<&'a mut Node> {
+    let properties = child_mut(node, "Properties70")?;
+    properties.children_mut().iter_mut().find(|w| w.name_str() == "P" && w.attributes_slice().first().and_then(|a| a.as_str_ref()) == Some(name))
+}
+
+fn read_vector3(property: &Node) -> Option<Vector3> {
+    let values = property.attributes_slice().get(4..7)?;
+    Some([values[0].to_f64()?, values[1].to_f64()?, values[2].to_f64()?])
+}
+
+fn map_vector_property(node: &mut Node, name: &str, f: impl Fn(Vector3) -> Vector3) {
+    if let Some(property) = property_node_mut(node, name) {
+        if let Some(v) = read_vector3(property) {
+            let attributes = property.attributes_mut();
+            attributes.truncate(4);
+            attributes.extend(f(v).iter().map(|w| Type::Float64(*w)));
+        }
+    }
+}
+
+fn set_property(node: &mut Node, name: &str, type_name: &str, label: &str, value: Type) {
+    if let Some(property) = property_node_mut(node, name) {
+        let attributes = property.attributes_mut();
+        attributes.truncate(4);
+        attributes.push(value);
+        return;
+    }
+
+    let attributes = vec![
+        Type::String(name.to_owned()),
+        Type::String(type_name.to_owned()),
+        Type::String(label.to_owned()),
+        Type::String("".to_owned()),
+        value,
+    ];
+
+    match child_mut(node, "Properties70") {
+        Some(properties) => properties.children_mut().push(Node::new("P".to_owned(), attributes, vec![])),
+        None => node.children_mut().push(Node::new("Properties70".to_owned(), vec![], vec![Node::new("P".to_owned(), attributes, vec![])])),
+    }
+}
+
+fn convert_geometry(node: &mut Node, mapping: &AxisMapping) {
+    let (vertices_stride, points_stride) = match node.attributes_slice().get(2).and_then(|w| w.as_str_ref()) {
+        Some("NurbsCurve") | Some("NurbsSurface") => (3, 4),
+        _ => (3, 3),
+    };
+
+    map_child_tuples(node, "Vertices", vertices_stride, |v| mapping.point(v));
+    map_child_tuples(node, "Points", points_stride, |v| mapping.point(v));
+    map_child_tuples(node, "Normals", 3, |v| mapping.direction(v));
+
+    for child in node.children_mut().iter_mut() {
+        let array = match child.name_str() {
+            "LayerElementNormal" => "Normals",
+            "LayerElementBinormal" => "Binormals",
+            "LayerElementTangent" => "Tangents",
+            _ => continue,
+        };
+
+        map_child_tuples(child, array, 3, |v| mapping.direction(v));
+    }
+}
+
+fn convert_model(node: &mut Node, properties: &Properties, rotation_animated: bool, mapping: &AxisMapping) {
+    for name in ["Lcl Translation", "RotationOffset", "RotationPivot", "ScalingOffset", "ScalingPivot", "GeometricTranslation"] {
+        map_vector_property(node, name, |v| mapping.point(v));
+    }
+
+    for name in ["Lcl Scaling", "GeometricScaling"] {
+        map_vector_property(node, name, |v| mapping.scaling(v));
+    }
+
+    // pre/post and geometric rotations are always applied in XYZ order
+    for name in ["PreRotation", "PostRotation", "GeometricRotation"] {
+        map_vector_property(node, name, |v| mapping.euler(v, RotationOrder::Xyz));
+    }
+
+    let order = RotationOrder::from_fbx(properties.get_i64("RotationOrder").unwrap_or(0));
+
+    if rotation_animated {
+        map_vector_property(node, "Lcl Rotation", |v| mapping.euler_channels(v));
+
+        let converted = mapping.rotation_order(order);
+        if converted != order {
+            set_property(node, "RotationOrder", "enum", "", Type::Int32(converted.to_fbx()));
+        }
+    } else {
+        map_vector_property(node, "Lcl Rotation", |v| mapping.euler(v, order));
+    }
+}
+
+// scales the distance properties of a camera or light, writing the template or SDK default explicitly so the
+// converted value does not fall back to a default in the old unit
+fn convert_node_attribute(node: &mut Node, subclass: &str, properties: &Properties, mapping: &AxisMapping) {
+    for (_, name, default) in DISTANCE_PROPERTIES.iter().filter(|w| w.0 == subclass) {
+        let value = properties.get_f64(name).unwrap_or(*default);
+
+        if value != 0.0 || property_node_mut(node, name).is_some() {
+            set_property(node, name, "double", "Number", Type::Float64(value * mapping.scale));
+        }
+    }
+}
+
+// remaps the `d|X`, `d|Y` and `d|Z` defaults of an animation curve node
+fn convert_curve_node(node: &mut Node, channel: Channel, mapping: &AxisMapping) {
+    if channel == Channel::Distance {
+        let properties = child_mut(node, "Properties70").map(|w| w.children_mut().iter_mut()).into_iter().flatten();
+
+        for property in properties.filter(|w| w.attributes_slice().first().and_then(|a| a.as_str_ref()).map(|a| a.starts_with("d|")).unwrap_or(false)) {
+            if let Some(value) = property.attributes_mut().get_mut(4) {
+                if let Some(v) = value.to_f64() {
+                    *value = Type::Float64(v * mapping.scale);
+                }
+            }
+        }
+
+        return;
+    }
+
+    let mut values: [Option<Type>; 3] = [None, None, None];
+
+    for (source, name) in CHANNELS.iter().enumerate() {
+        if let Some(value) = property_node_mut(node, name).and_then(|w| w.attributes_slice().get(4).and_then(|a| a.to_f64())) {
+            let target = mapping.targets[source].0;
+            values[target] = Some(Type::Float64(value * mapping.channel_factor(channel, source)));
+        }
+    }
+
+    for (target, value) in values.into_iter().enumerate() {
+        if let Some(value) = value {
+            set_property(node, CHANNELS[target], "Number", "", value);
+        }
+    }
+}
+
+fn convert_curve(node: &mut Node, factor: f64) {
+    if factor == 1.0 {
+        return;
+    }
+
+    if let Some(Type::Float64(value)) = child_mut(node, "Default").and_then(|w| w.attributes_mut().first_mut()) {
+        *value *= factor;
+    }
+
+    match child_mut(node, "KeyValueFloat").and_then(|w| w.attributes_mut().first_mut()) {
+        Some(Type::VecFloat32(values)) => values.iter_mut().for_each(|w| *w *= factor as f32),
+        Some(Type::VecFloat64(values)) => values.iter_mut().for_each(|w| *w *= factor),
+        _ => {}
+    }
+
+    // the first two values of every attribute are the right and next-left slopes
+    if let Some(Type::VecFloat32(values)) = child_mut(node, "KeyAttrDataFloat").and_then(|w| w.attributes_mut().first_mut()) {
+        for chunk in values.chunks_exact_mut(4) {
+            chunk[0] *= factor as f32;
+            chunk[1] *= factor as f32;
+        }
+    }
+}
+
+fn convert_objects(
+    objects: &mut Node,
+    graph: &ObjectGraph,
+    curve_nodes: &HashMap<ObjectId, Channel>,
+    curves: &HashMap<ObjectId, f64>,
+    animated_rotations: &HashSet<ObjectId>,
+    mapping: &AxisMapping,
+) {
+    for node in objects.children_mut().iter_mut() {
+        let id = match node.attributes_slice().first().and_then(|w| w.as_int64()) {
+            Some(id) => id,
+            None => continue,
+        };
+
+        match node.name().as_str() {
+            "Geometry" => convert_geometry(node, mapping),
+            "Model" => {
+                let properties = graph.properties(id).unwrap_or_default();
+                convert_model(node, &properties, animated_rotations.contains(&id), mapping);
+            }
+            "NodeAttribute" => {
+                let subclass = graph.object(id).map(|w| w.subclass().to_owned()).unwrap_or_default();
+                let properties = graph.properties(id).unwrap_or_default();
+                convert_node_attribute(node, &subclass, &properties, mapping);
+            }
+            "Deformer" => {
+                for name in ["Transform", "TransformLink", "TransformAssociateModel"] {
+                    map_matrix(node, name, mapping);
+                }
+            }
+            "Pose" => {
+                for pose_node in node.children_mut().iter_mut().filter(|w| w.name_str() == "PoseNode") {
+                    map_matrix(pose_node, "Matrix", mapping);
+                }
+            }
+            "AnimationCurveNode" => {
+                if let Some(channel) = curve_nodes.get(&id) {
+                    convert_curve_node(node, *channel, mapping);
+                }
+            }
+            "AnimationCurve" => {
+                if let Some(factor) = curves.get(&id) {
+                    convert_curve(node, *factor);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// moves the curves of converted curve nodes onto their new channels
+fn convert_connections(connections: &mut Node, curve_nodes: &HashMap<ObjectId, Channel>, mapping: &AxisMapping) {
+    for connection in connections.children_mut().iter_mut().filter(|w| w.name_str() == "C") {
+        let attributes = connection.attributes_mut();
+        let is_op = attributes.first().and_then(|w| w.as_str_ref()) == Some("OP");
+        let to_curve_node = attributes.get(2).and_then(|w| w.as_int64()).map(|w| curve_nodes.contains_key(&w)).unwrap_or(false);
+
+        if !(is_op && to_curve_node) {
+            continue;
+        }
+
+        if let Some(Type::String(property)) = attributes.get_mut(3) {
+            if let Some(source) = CHANNELS.iter().position(|w| w == property) {
+                *property = CHANNELS[mapping.targets[source].0].to_owned();
+            }
+        }
+    }
+}
+
+fn convert_settings(object: &mut Object, options: &ConversionOptions) {
+    let settings = match object.children_mut().iter_mut().find(|w| w.name_str() == "GlobalSettings") {
+        Some(settings) => settings,
+        None => return,
+    };
+
+    let system = options.axis_system;
+    let values = [
+        ("UpAxis", system.up.axis.index() as i32),
+        ("UpAxisSign", system.up.sign()),
+        ("FrontAxis", system.front.axis.index() as i32),
+        ("FrontAxisSign", system.front.sign()),
+        ("CoordAxis", system.coord.axis.index() as i32),
+        ("CoordAxisSign", system.coord.sign()),
+    ];
+
+    for (name, value) in values {
+        set_property(settings, name, "int", "Integer", Type::Int32(value));
+    }
+
+    set_property(settings, "UnitScaleFactor", "double", "Number", Type::Float64(options.unit_scale_factor));
+}
+
+/// Rewrites the scene into the requested axis system and unit.
+///
+/// Model transforms, geometry, normals, cluster and pose matrices, camera and light distances and their animation
+/// curves are converted.
+/// Animated rotations keep their curves per channel, so the model's `RotationOrder` is permuted instead.
+pub fn convert_scene(object: &mut Object, options: &ConversionOptions) -> Result<(), SceneError> {
+    if !(options.unit_scale_factor.is_finite() && options.unit_scale_factor > 0.0) {
+        return Err(SceneError::InvalidUnitScaleFactor(options.unit_scale_factor));
+    }
+
+    let settings = GlobalSettings::read(object)?;
+    if !(settings.unit_scale_factor.is_finite() && settings.unit_scale_factor > 0.0) {
+        return Err(SceneError::InvalidUnitScaleFactor(settings.unit_scale_factor));
+    }
+
+    let rotation = settings.axis_system.conversion_to(&options.axis_system)?;
+    let mapping = AxisMapping::new(rotation, settings.unit_scale_factor / options.unit_scale_factor);
+    let graph = ObjectGraph::from(&*object);
+
+    // animation curve nodes driving model transforms or node attribute distances, and the curves connected to them
+    let mut curve_nodes: HashMap<ObjectId, Channel> = HashMap::new();
+    let mut animated_rotations: HashSet<ObjectId> = HashSet::new();
+
+    for curve_node in graph.objects_of_class("AnimationCurveNode") {
+        for (target, property) in graph.property_targets(curve_node.id()) {
+            let channel = match graph.object(target) {
+                Some(w) if w.class() == "Model" => Channel::from_property(property),
+                Some(w) if w.class() == "NodeAttribute" && is_distance_property(w.subclass(), property) => Some(Channel::Distance),
+                _ => None,
+            };
+
+            if let Some(channel) = channel {
+                curve_nodes.insert(curve_node.id(), channel);
+                if channel == Channel::Rotation {
+                    animated_rotations.insert(target);
+                }
+            }
+        }
+    }
+
+    let mut curves: HashMap<ObjectId, f64> = HashMap::new();
+    for (curve_node, channel) in curve_nodes.iter() {
+        for connection in graph.property_connections(*curve_node) {
+            let factor = match channel {
+                Channel::Distance => Some(mapping.scale),
+                _ => CHANNELS.iter().position(|w| Some(*w) == connection.parent_property()).map(|source| mapping.channel_factor(*channel, source)),
+            };
+
+            if let Some(factor) = factor {
+                curves.insert(connection.child(), factor);
+            }
+        }
+    }
+
+    for section in object.children_mut().iter_mut() {
+        match section.name_str() {
+            "Objects" => convert_objects(section, &graph, &curve_nodes, &curves, &animated_rotations, &mapping),
+            "Connections" => convert_connections(section, &curve_nodes, &mapping),
+            _ => {}
+        }
+    }
+
+    if options.update_settings {
+        convert_settings(object, options);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn global_settings(up: i32, front: i32, front_sign: i32, unit: f64) -> Node {
+        node("GlobalSettings", vec![], vec![properties70(vec![
+            int_property("UpAxis", "int", up),
+            int_property("UpAxisSign", "int", 1),
+            int_property("FrontAxis", "int", front),
+            int_property("FrontAxisSign", "int", front_sign),
+            int_property("CoordAxis", "int", 0),
+            int_property("CoordAxisSign", "int", 1),
+            property("UnitScaleFactor", "double", vec![Type::Float64(unit)]),
+        ])])
+    }
+
+    #[test]
+    fn converts_z_up_centimeters_to_y_up_meters() {
+        let objects = vec![
+            object("Model", 1, "box", "Mesh", vec![properties70(vec![
+                vector_property("Lcl Translation", "Lcl Translation", [1.0, 2.0, 3.0]),
+                vector_property("Lcl Rotation", "Lcl Rotation", [90.0, 0.0, 0.0]),
+                vector_property("Lcl Scaling", "Lcl Scaling", [1.0, 2.0, 3.0]),
+            ])]),
+            object("NodeAttribute", 2, "camera", "Camera", vec![properties70(vec![number_property("FarPlane", 5000.0)])]),
+            object("NodeAttribute", 3, "light", "Light", vec![properties70(vec![number_property("DecayStart", 50.0)])]),
+            object("AnimationCurveNode", 4, "focus", "", vec![properties70(vec![number_property("d|FocusDistance", 300.0)])]),
+            object("AnimationCurve", 5, "", "", vec![node("KeyValueFloat", vec![Type::VecFloat32(vec![300.0, 400.0])], vec![])]),
+        ];
+        let connections = vec![op(4, 2, "FocusDistance"), op(5, 4, "d|FocusDistance")];
+
+        let mut scene = scene(vec![], objects, connections);
+        scene.children_mut().insert(0, global_settings(2, 1, -1, 1.0));
+        convert_scene(&mut scene, &ConversionOptions::new(AxisSystem::Y_UP, 100.0)).unwrap();
+
+        let settings = GlobalSettings::read(&scene).unwrap();
+        assert_eq!(settings.axis_system, AxisSystem::Y_UP);
+        assert_eq!(settings.unit_scale_factor, 100.0);
+
+        let graph = ObjectGraph::from(&scene);
+        let model = graph.properties(1).unwrap();
+        assert_all_close(&model.get_vector3("Lcl Translation").unwrap(), &[0.01, 0.03, -0.02], 1e-12);
+        assert_all_close(&model.get_vector3("Lcl Rotation").unwrap(), &[90.0, 0.0, 0.0], 1e-9);
+        assert_all_close(&model.get_vector3("Lcl Scaling").unwrap(), &[1.0, 3.0, 2.0], 1e-12);
+
+        // the near plane comes from the SDK default, the far plane from the file
+        let camera = graph.properties(2).unwrap();
+        assert_close(camera.get_f64("NearPlane").unwrap(), 0.1, 1e-12);
+        assert_close(camera.get_f64("FarPlane").unwrap(), 50.0, 1e-12);
+        assert_close(graph.properties(3).unwrap().get_f64("DecayStart").unwrap(), 0.5, 1e-12);
+
+        assert_close(graph.properties(4).unwrap().get_f64("d|FocusDistance").unwrap(), 3.0, 1e-12);
+        let keys = graph.object(5).unwrap().node().children_slice()[0].attributes_slice()[0].to_f64_vec().unwrap();
+        assert_all_close(&keys, &[3.0, 4.0], 1e-6);
+    }
+
+    // converts a Z-up centimeter scene to Y-up meters, where (x, y, z) becomes (x, z, -y) / 100
+    fn convert_z_up(objects: Vec<Node>, connections: Vec<Node>) -> ObjectGraph {
+        let mut scene = scene(vec![], objects, connections);
+        scene.children_mut().insert(0, global_settings(2, 1, -1, 1.0));
+        convert_scene(&mut scene, &ConversionOptions::new(AxisSystem::Y_UP, 100.0)).unwrap();
+        ObjectGraph::from(&scene)
+    }
+
+    fn key_values(graph: &ObjectGraph, curve: ObjectId) -> Vec<f64> {
+        let node = graph.object(curve).unwrap().node();
+        node.children_slice().iter().find(|w| w.name_str() == "KeyValueFloat").unwrap().attributes_slice()[0].to_f64_vec().unwrap()
+    }
+
+    fn matrix_node(name: &str, m: Matrix4) -> Node {
+        f64_array(name, m.as_array())
+    }
+
+    fn read_matrix(node: &Node, name: &str) -> Matrix4 {
+        let values = node.children_slice().iter().find(|w| w.name_str() == name).unwrap().attributes_slice()[0].to_f64_vec().unwrap();
+        Matrix4::from_slice(&values).unwrap()
+    }
+
+    fn assert_matrix(actual: Matrix4, expected: Matrix4) {
+        assert!(actual.approx_eq(&expected, 1e-9), "{:?} is not {:?}", actual, expected);
+    }
+
+    #[test]
+    fn converts_rotations_that_do_not_commute_with_the_basis_change() {
+        let objects = vec![
+            object("Model", 1, "turned", "Null", vec![properties70(vec![vector_property("Lcl Rotation", "Lcl Rotation", [0.0, 0.0, 90.0])])]),
+            object("Model", 2, "ordered", "Null", vec![properties70(vec![
+                int_property("RotationOrder", "enum", RotationOrder::Zyx.to_fbx()),
+                vector_property("Lcl Rotation", "Lcl Rotation", [30.0, 40.0, 50.0]),
+            ])]),
+        ];
+        let graph = convert_z_up(objects, vec![]);
+
+        // a quarter turn about the Z-up scene's up axis is a quarter turn about Y
+        assert_all_close(&graph.properties(1).unwrap().get_vector3("Lcl Rotation").unwrap(), &[0.0, 90.0, 0.0], 1e-9);
+
+        // the order of a static rotation is kept and the angles describe the same rotation in the new basis
+        let properties = graph.properties(2).unwrap();
+        assert_eq!(properties.get_i64("RotationOrder"), Some(RotationOrder::Zyx.to_fbx() as i64));
+
+        let source = Matrix4::from_euler([30.0, 40.0, 50.0], RotationOrder::Zyx);
+        let converted = Matrix4::from_euler(properties.get_vector3("Lcl Rotation").unwrap(), RotationOrder::Zyx);
+        let basis = |v: Vector3| [v[0], v[2], -v[1]];
+
+        for v in [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]] {
+            assert_all_close(&basis(source.transform_vector(v)), &converted.transform_vector(basis(v)), 1e-9);
+        }
+    }
+
+    #[test]
+    fn converts_vertices_and_normals() {
+        let geometry = object("Geometry", 1, "box", "Mesh", vec![
+            f64_array("Vertices", &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]),
+            node("LayerElementNormal", vec![Type::Int32(0)], vec![f64_array("Normals", &[0.0, 0.0, 1.0, 0.0, -1.0, 0.0])]),
+        ]);
+        let graph = convert_z_up(vec![geometry], vec![]);
+        let node = graph.object(1).unwrap().node();
+
+        let vertices = node.children_slice()[0].attributes_slice()[0].to_f64_vec().unwrap();
+        assert_all_close(&vertices, &[0.01, 0.03, -0.02, 0.04, 0.06, -0.05], 1e-12);
+
+        let normals = node.children_slice()[1].children_slice()[0].attributes_slice()[0].to_f64_vec().unwrap();
+        assert_all_close(&normals, &[0.0, 1.0, 0.0, 0.0, 0.0, 1.0], 1e-12);
+    }
+
+    #[test]
+    fn converts_cluster_and_pose_matrices() {
+        let cluster = object("Deformer", 1, "arm", "Cluster", vec![
+            matrix_node("Transform", Matrix4::translation([1.0, 2.0, 3.0])),
+            matrix_node("TransformLink", Matrix4::rotation_axis(2, 90.0)),
+        ]);
+        let pose = object("Pose", 2, "bind", "BindPose", vec![
+            node("PoseNode", vec![], vec![node("Node", vec![Type::Int64(3)], vec![]), matrix_node("Matrix", Matrix4::translation([0.0, 0.0, 5.0]))]),
+        ]);
+        let graph = convert_z_up(vec![cluster, pose], vec![]);
+
+        let cluster = graph.object(1).unwrap().node();
+        assert_matrix(read_matrix(cluster, "Transform"), Matrix4::translation([0.01, 0.03, -0.02]));
+        assert_matrix(read_matrix(cluster, "TransformLink"), Matrix4::rotation_axis(1, 90.0));
+
+        let pose_node = &graph.object(2).unwrap().node().children_slice()[0];
+        assert_matrix(read_matrix(pose_node, "Matrix"), Matrix4::translation([0.0, 0.05, 0.0]));
+    }
+
+    #[test]
+    fn remaps_animated_rotation_and_translation_channels() {
+        let channels = |values: [f64; 3]| properties70(CHANNELS.iter().zip(values).map(|(name, value)| number_property(name, value)).collect());
+        let curve = |id: ObjectId, value: f32| object("AnimationCurve", id, "", "", vec![node("KeyValueFloat", vec![Type::VecFloat32(vec![value])], vec![])]);
+
+        let objects = vec![
+            object("Model", 1, "arm", "LimbNode", vec![properties70(vec![
+                vector_property("Lcl Translation", "Lcl Translation", [1.0, 2.0, 3.0]),
+                vector_property("Lcl Rotation", "Lcl Rotation", [10.0, 20.0, 30.0]),
+            ])]),
+            object("AnimationCurveNode", 2, "R", "", vec![channels([10.0, 20.0, 30.0])]),
+            curve(3, 10.0),
+            curve(4, 20.0),
+            curve(5, 30.0),
+            object("AnimationCurveNode", 6, "T", "", vec![channels([1.0, 2.0, 3.0])]),
+            curve(7, 1.0),
+            curve(8, 2.0),
+            curve(9, 3.0),
+        ];
+        let connections = vec![
+            op(2, 1, "Lcl Rotation"),
+            op(3, 2, "d|X"),
+            op(4, 2, "d|Y"),
+            op(5, 2, "d|Z"),
+            op(6, 1, "Lcl Translation"),
+            op(7, 6, "d|X"),
+            op(8, 6, "d|Y"),
+            op(9, 6, "d|Z"),
+        ];
+        let graph = convert_z_up(objects, connections);
+
+        // X, then Y, then Z becomes X, then -Z, then Y, so the rotation order turns into XZY
+        let model = graph.properties(1).unwrap();
+        assert_all_close(&model.get_vector3("Lcl Rotation").unwrap(), &[10.0, 30.0, -20.0], 1e-12);
+        assert_eq!(model.get_i64("RotationOrder"), Some(RotationOrder::Xzy.to_fbx() as i64));
+        assert_all_close(&model.get_vector3("Lcl Translation").unwrap(), &[0.01, 0.03, -0.02], 1e-12);
+
+        let rotation = graph.properties(2).unwrap();
+        assert_all_close(&CHANNELS.map(|w| rotation.get_f64(w).unwrap()), &[10.0, 30.0, -20.0], 1e-12);
+        assert_eq!(graph.property_targets(4), [(2, "d|Z")]);
+        assert_eq!(graph.property_targets(5), [(2, "d|Y")]);
+        assert_all_close(&key_values(&graph, 4), &[-20.0], 1e-6);
+        assert_all_close(&key_values(&graph, 5), &[30.0], 1e-6);
+
+        let translation = graph.properties(6).unwrap();
+        assert_all_close(&CHANNELS.map(|w| translation.get_f64(w).unwrap()), &[0.01, 0.03, -0.02], 1e-12);
+        assert_eq!(graph.property_targets(8), [(6, "d|Z")]);
+        assert_all_close(&key_values(&graph, 7), &[0.01], 1e-6);
+        assert_all_close(&key_values(&graph, 8), &[-0.02], 1e-6);
+        assert_all_close(&key_values(&graph, 9), &[0.03], 1e-6);
+    }
+}
@@ -0,0 +1,45 @@
+use thiserror::Error;
+
+use crate::graph::ObjectId;
+
+#[derive(Error, Debug)]
+pub enum SceneError {
+    #[error("invalid axis system: up, front and coord axes must differ")]
+    InvalidAxisSystem,
+
+    #[error("`{0}` is not an axis: `{1}`")]
+    InvalidAxis(String, i64),
+
+    #[error("invalid unit scale factor: `{0}`")]
+    InvalidUnitScaleFactor(f64),
+
+    #[error("object `{0}` does not exist")]
+    MissingObject(ObjectId),
+
+    #[error("object `{0}` is not a `{1}`")]
+    UnexpectedObject(ObjectId, String),
+
+    #[error("`{0}` is missing")]
+    MissingNode(String),
+
+    #[error("`{0}` has an invalid length: `{1}`")]
+    InvalidArrayLength(String, usize),
+
+    #[error("index `{0}` is out of range for `{1}` elements")]
+    IndexOutOfRange(i64, usize),
+
+    #[error("the last polygon is not terminated by a negative index")]
+    UnterminatedPolygon,
+
+    #[error("no edge connects control points `{0}` and `{1}`")]
+    MissingEdge(u32, u32),
+
+    #[error("unknown layer element `{0}`")]
+    UnknownLayerElement(String),
+
+    #[error("object `{0}` is its own ancestor")]
+    CyclicHierarchy(ObjectId),
+
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
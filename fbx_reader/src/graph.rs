@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use fbx::format::{Node, Object, Type};
+
+use crate::properties::{Properties, PropertyTemplates};
+
+pub type ObjectId = i64;
+
+// the implicit scene root referenced by connections, it has no node in `Objects`
+pub const ROOT_ID: ObjectId = 0;
+
+// splits "Name\x00\x01Class" (binary) or "Class::Name" (ASCII) into the name and class parts
+pub fn split_object_name(raw: &str) -> (&str, Option<&str>) {
+    if let Some((name, class)) = raw.split_once("\x00\x01") {
+        return (name, Some(class));
+    }
+
+    if let Some((class, name)) = raw.split_once("::") {
+        return (name, Some(class));
+    }
+
+    (raw, None)
+}
+
+// true if `id` exists and has the given class and subclass
+pub(crate) fn is_class(graph: &ObjectGraph, id: ObjectId, class: &str, subclass: &str) -> bool {
+    graph.object(id).map(|w| w.class() == class && w.subclass() == subclass).unwrap_or(false)
+}
+
+#[derive(Debug, Clone)]
+pub struct SceneObject {
+    id: ObjectId,
+    class: String,
+    name: String,
+    subclass: String,
+    node: Node,
+}
+
+impl SceneObject {
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    // node name in `Objects`, e.g. `Model`, `Geometry` or `Deformer`
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // e.g. `Mesh`, `LimbNode` or `Skin`
+    pub fn subclass(&self) -> &str {
+        &self.subclass
+    }
+
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionKind {
+    ObjectObject,
+    ObjectProperty,
+    PropertyObject,
+    PropertyProperty,
+}
+
+/// A `C` entry of the `Connections` section; `child` is the source and `parent` the destination.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connection {
+    kind: ConnectionKind,
+    child: ObjectId,
+    child_property: Option<String>,
+    parent: ObjectId,
+    parent_property: Option<String>,
+}
+
+impl Connection {
+    pub fn kind(&self) -> ConnectionKind {
+        self.kind
+    }
+
+    pub fn child(&self) -> ObjectId {
+        self.child
+    }
+
+    pub fn child_property(&self) -> Option<&str> {
+        self.child_property.as_deref()
+    }
+
+    pub fn parent(&self) -> ObjectId {
+        self.parent
+    }
+
+    pub fn parent_property(&self) -> Option<&str> {
+        self.parent_property.as_deref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnostic {
+    MalformedObject { class: String, position: usize },
+    DuplicateObject { id: ObjectId },
+    MalformedConnection { index: usize },
+    MissingObject { connection: usize, id: ObjectId },
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::MalformedObject { class, position } => write!(f, "object `{}` at position {} has no valid ID", class, position),
+            Diagnostic::DuplicateObject { id } => write!(f, "object ID {} is defined more than once", id),
+            Diagnostic::MalformedConnection { index } => write!(f, "connection {} is malformed", index),
+            Diagnostic::MissingObject { connection, id } => write!(f, "connection {} references missing object {}", connection, id),
+        }
+    }
+}
+
+fn parse_connection(node: &Node) -> Option<Connection> {
+    let attributes = node.attributes_slice();
+    let kind = attributes.first()?.as_str_ref()?;
+    let id = |i: usize| attributes.get(i).and_then(|w| w.as_int64());
+    let property = |i: usize| attributes.get(i).and_then(|w| w.as_str());
+
+    let connection = match kind {
+        "OO" => Connection { kind: ConnectionKind::ObjectObject, child: id(1)?, child_property: None, parent: id(2)?, parent_property: None },
+        "OP" => Connection { kind: ConnectionKind::ObjectProperty, child: id(1)?, child_property: None, parent: id(2)?, parent_property: Some(property(3)?) },
+        "PO" => Connection { kind: ConnectionKind::PropertyObject, child: id(1)?, child_property: Some(property(2)?), parent: id(3)?, parent_property: None },
+        "PP" => Connection { kind: ConnectionKind::PropertyProperty, child: id(1)?, child_property: Some(property(2)?), parent: id(3)?, parent_property: Some(property(4)?) },
+        _ => return None,
+    };
+
+    Some(connection)
+}
+
+/// Objects of the `Objects` section indexed by ID and linked through the `Connections` section.
+#[derive(Debug, Clone, Default)]
+pub struct ObjectGraph {
+    objects: Vec<SceneObject>,
+    indices: HashMap<ObjectId, usize>,
+    connections: Vec<Connection>,
+    by_child: HashMap<ObjectId, Vec<usize>>,
+    by_parent: HashMap<ObjectId, Vec<usize>>,
+    templates: PropertyTemplates,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl ObjectGraph {
+    fn read_objects(&mut self, node: &Node) {
+        for (position, child) in node.children_slice().iter().enumerate() {
+            let attributes = child.attributes_slice();
+            let id = match attributes.first() {
+                Some(Type::Int64(id)) => *id,
+                _ => {
+                    self.diagnostics.push(Diagnostic::MalformedObject { class: child.name(), position });
+                    continue;
+                }
+            };
+
+            if self.indices.contains_key(&id) {
+                self.diagnostics.push(Diagnostic::DuplicateObject { id });
+                continue;
+            }
+
+            let raw_name = attributes.get(1).and_then(|w| w.as_str_ref()).unwrap_or("");
+            let name = split_object_name(raw_name).0.to_owned();
+            let subclass = attributes.get(2).and_then(|w| w.as_str()).unwrap_or_default();
+
+            self.indices.insert(id, self.objects.len());
+            self.objects.push(SceneObject { id, class: child.name(), name, subclass, node: child.clone() });
+        }
+    }
+
+    fn read_connections(&mut self, node: &Node) {
+        for (index, child) in node.children_slice().iter().filter(|w| w.name_str() == "C").enumerate() {
+            let connection = match parse_connection(child) {
+                Some(c) => c,
+                None => {
+                    self.diagnostics.push(Diagnostic::MalformedConnection { index });
+                    continue;
+                }
+            };
+
+            for id in [connection.child, connection.parent] {
+                if id != ROOT_ID && !self.indices.contains_key(&id) {
+                    self.diagnostics.push(Diagnostic::MissingObject { connection: index, id });
+                }
+            }
+
+            let at = self.connections.len();
+            self.by_child.entry(connection.child).or_default().push(at);
+            self.by_parent.entry(connection.parent).or_default().push(at);
+            self.connections.push(connection);
+        }
+    }
+
+    pub fn object(&self, id: ObjectId) -> Option<&SceneObject> {
+        self.indices.get(&id).map(|w| &self.objects[*w])
+    }
+
+    pub fn objects(&self) -> &[SceneObject] {
+        &self.objects
+    }
+
+    pub fn objects_of_class<'a>(&'a self, class: &'a str) -> impl Iterator<Item = &'a SceneObject> + 'a {
+        self.objects.iter().filter(move |w| w.class == class)
+    }
+
+    pub fn connections(&self) -> &[Connection] {
+        &self.connections
+    }
+
+    // every connection where `id` is the source, in file order
+    pub fn parent_connections(&self, id: ObjectId) -> Vec<&Connection> {
+        self.by_child.get(&id).map(|w| w.iter().map(|i| &self.connections[*i]).collect()).unwrap_or_default()
+    }
+
+    // every connection where `id` is the destination, in file order
+    pub fn child_connections(&self, id: ObjectId) -> Vec<&Connection> {
+        self.by_parent.get(&id).map(|w| w.iter().map(|i| &self.connections[*i]).collect()).unwrap_or_default()
+    }
+
+    // object-object parents of `id`
+    pub fn parents(&self, id: ObjectId) -> Vec<ObjectId> {
+        self.parent_connections(id).into_iter().filter(|w| w.kind == ConnectionKind::ObjectObject).map(|w| w.parent).collect()
+    }
+
+    // object-object children of `id`
+    pub fn children(&self, id: ObjectId) -> Vec<ObjectId> {
+        self.child_connections(id).into_iter().filter(|w| w.kind == ConnectionKind::ObjectObject).map(|w| w.child).collect()
+    }
+
+    // connections ending at a property of `id`
+    pub fn property_connections(&self, id: ObjectId) -> Vec<&Connection> {
+        self.child_connections(id).into_iter().filter(|w| w.parent_property.is_some()).collect()
+    }
+
+    // objects connected to the property `property` of `id`
+    pub fn property_sources(&self, id: ObjectId, property: &str) -> Vec<ObjectId> {
+        self.property_connections(id).into_iter().filter(|w| w.parent_property() == Some(property)).map(|w| w.child).collect()
+    }
+
+    // parents of `id` through a connection to one of their properties, with the property name
+    pub fn property_targets(&self, id: ObjectId) -> Vec<(ObjectId, &str)> {
+        self.parent_connections(id).into_iter().filter_map(|w| w.parent_property().map(|p| (w.parent, p))).collect()
+    }
+
+    pub fn templates(&self) -> &PropertyTemplates {
+        &self.templates
+    }
+
+    // effective properties of the object, falling back to the defaults of its template, see `PropertyTemplates::select`
+    pub fn properties(&self, id: ObjectId) -> Option<Properties> {
+        let object = self.object(id)?;
+        let properties = Properties::from_node(&object.node);
+
+        Some(match self.templates.select(&object.class, &object.subclass, &object.node) {
+            Some(template) => properties.with_template(template.properties()),
+            None => properties,
+        })
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+}
+
+impl From<&Object> for ObjectGraph {
+    fn from(object: &Object) -> Self {
+        let mut graph = ObjectGraph { templates: PropertyTemplates::from(object), ..ObjectGraph::default() };
+
+        for node in object.children_slice().iter().filter(|w| w.name_str() == "Objects") {
+            graph.read_objects(node);
+        }
+
+        for node in object.children_slice().iter().filter(|w| w.name_str() == "Connections") {
+            graph.read_connections(node);
+        }
+
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn splits_binary_and_ascii_object_names() {
+        assert_eq!(split_object_name("cube\x00\x01Model"), ("cube", Some("Model")));
+        assert_eq!(split_object_name("Model::cube"), ("cube", Some("Model")));
+        assert_eq!(split_object_name("Model::a::b"), ("a::b", Some("Model")));
+        assert_eq!(split_object_name("cube"), ("cube", None));
+    }
+
+    #[test]
+    fn indexes_objects_and_connections() {
+        let objects = vec![
+            object("Model", 1, "root", "Null", vec![]),
+            object("Model", 2, "cube", "Mesh", vec![]),
+            node("Geometry", vec![Type::Int64(3), string("Geometry::cube"), string("Mesh")], vec![]),
+            object("Material", 4, "red", "", vec![]),
+            object("Texture", 5, "diffuse", "", vec![]),
+        ];
+        let graph = graph(objects, vec![oo(1, ROOT_ID), oo(2, 1), oo(3, 2), oo(4, 2), op(5, 4, "DiffuseColor")]);
+
+        let geometry = graph.object(3).unwrap();
+        assert_eq!((geometry.class(), geometry.name(), geometry.subclass()), ("Geometry", "cube", "Mesh"));
+        assert_eq!(graph.objects_of_class("Model").map(|w| w.name()).collect::<Vec<_>>(), ["root", "cube"]);
+
+        assert_eq!(graph.parents(2), [1]);
+        assert_eq!(graph.children(ROOT_ID), [1]);
+        assert_eq!(graph.children(2), [3, 4]);
+
+        // property connections are not object-object children
+        assert!(graph.children(4).is_empty());
+        let connections = graph.property_connections(4);
+        assert_eq!(connections.len(), 1);
+        assert_eq!((connections[0].kind(), connections[0].child(), connections[0].parent_property()), (ConnectionKind::ObjectProperty, 5, Some("DiffuseColor")));
+        assert_eq!(graph.property_sources(4, "DiffuseColor"), [5]);
+        assert_eq!(graph.property_targets(5), [(4, "DiffuseColor")]);
+
+        assert!(graph.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn reports_malformed_duplicate_and_missing_objects() {
+        let objects = vec![
+            object("Model", 1, "cube", "Mesh", vec![]),
+            object("Model", 1, "copy", "Mesh", vec![]),
+            node("Model", vec![string("no id")], vec![]),
+        ];
+        let connections = vec![
+            oo(1, ROOT_ID),
+            oo(9, 1),
+            node("C", vec![string("OO"), Type::Int64(1)], vec![]),
+            node("C", vec![string("OP"), Type::Int64(1), Type::Int64(ROOT_ID)], vec![]),
+        ];
+        let graph = graph(objects, connections);
+
+        assert_eq!(graph.diagnostics(), [
+            Diagnostic::DuplicateObject { id: 1 },
+            Diagnostic::MalformedObject { class: "Model".to_owned(), position: 2 },
+            Diagnostic::MissingObject { connection: 1, id: 9 },
+            Diagnostic::MalformedConnection { index: 2 },
+            Diagnostic::MalformedConnection { index: 3 },
+        ]);
+
+        // the first definition wins and dangling connections are kept
+        assert_eq!(graph.object(1).unwrap().name(), "cube");
+        assert_eq!(graph.children(1), [9]);
+        assert_eq!(graph.connections().len(), 2);
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::error::SceneError;
+use crate::graph::{ObjectGraph, ObjectId};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelType {
+    Mesh,
+    // a bone
+    LimbNode,
+    // the top of a skeleton, some exporters use it instead of `LimbNode`
+    Root,
+    Null,
+    Camera,
+    Light,
+    Other(String),
+}
+
+impl ModelType {
+    pub fn parse(subclass: &str) -> Self {
+        match subclass {
+            "Mesh" => ModelType::Mesh,
+            "LimbNode" => ModelType::LimbNode,
+            "Root" => ModelType::Root,
+            "Null" | "" => ModelType::Null,
+            "Camera" => ModelType::Camera,
+            "Light" => ModelType::Light,
+            other => ModelType::Other(other.to_owned()),
+        }
+    }
+
+    pub fn is_skeleton(&self) -> bool {
+        matches!(self, ModelType::LimbNode | ModelType::Root)
+    }
+}
+
+/// A `Model` placed in the scene hierarchy.
+#[derive(Debug, Clone)]
+pub struct SceneNode {
+    id: ObjectId,
+    name: String,
+    subclass: String,
+    model_type: ModelType,
+    parent: Option<ObjectId>,
+    children: Vec<ObjectId>,
+    attribute: Option<ObjectId>,
+    attribute_type: Option<String>,
+}
+
+impl SceneNode {
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    // the name without its `\x00\x01Model` or `Model::` class part
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn subclass(&self) -> &str {
+        &self.subclass
+    }
+
+    pub fn model_type(&self) -> &ModelType {
+        &self.model_type
+    }
+
+    // the parent `Model`, `None` for models under the scene root
+    pub fn parent(&self) -> Option<ObjectId> {
+        self.parent
+    }
+
+    // child models, in connection order
+    pub fn children(&self) -> &[ObjectId] {
+        &self.children
+    }
+
+    // the linked `NodeAttribute`, e.g. the camera or light settings
+    pub fn attribute(&self) -> Option<ObjectId> {
+        self.attribute
+    }
+
+    // subclass of the linked `NodeAttribute`, e.g. `LimbNode`, `Camera` or `Light`
+    pub fn attribute_type(&self) -> Option<&str> {
+        self.attribute_type.as_deref()
+    }
+}
+
+/// The tree of `Model` objects, linked through their object-object connections.
+#[derive(Debug, Clone, Default)]
+pub struct SceneHierarchy {
+    nodes: Vec<SceneNode>,
+    indices: HashMap<ObjectId, usize>,
+    roots: Vec<ObjectId>,
+}
+
+impl SceneHierarchy {
+    pub fn from_graph(graph: &ObjectGraph) -> Result<SceneHierarchy, SceneError> {
+        let is_model = |id: &ObjectId| graph.object(*id).map(|w| w.class() == "Model").unwrap_or(false);
+        let mut hierarchy = SceneHierarchy::default();
+
+        for object in graph.objects_of_class("Model") {
+            let id = object.id();
+
+            // the first model a model is connected to is its parent, anything else leaves it at the root
+            let parent = graph.parents(id).into_iter().find(is_model);
+            let attribute = graph.children(id).into_iter().find(|w| graph.object(*w).map(|o| o.class() == "NodeAttribute").unwrap_or(false));
+
+            hierarchy.indices.insert(id, hierarchy.nodes.len());
+            hierarchy.nodes.push(SceneNode {
+                id,
+                name: object.name().to_owned(),
+                subclass: object.subclass().to_owned(),
+                model_type: ModelType::parse(object.subclass()),
+                parent,
+                children: Vec::new(),
+                attribute,
+                attribute_type: attribute.and_then(|w| graph.object(w)).map(|w| w.subclass().to_owned()),
+            });
+        }
+
+        for index in 0..hierarchy.nodes.len() {
+            let id = hierarchy.nodes[index].id;
+            let children: Vec<ObjectId> = graph.children(id).into_iter().filter(|w| hierarchy.node(*w).and_then(|n| n.parent) == Some(id)).collect();
+            hierarchy.nodes[index].children = children;
+
+            if hierarchy.nodes[index].parent.is_none() {
+                hierarchy.roots.push(id);
+            }
+        }
+
+        // models in a parent loop are unreachable from the roots
+        let reachable: HashSet<ObjectId> = hierarchy.depth_first().map(|(_, w)| w.id).collect();
+        if let Some(node) = hierarchy.nodes.iter().find(|w| !reachable.contains(&w.id)) {
+            return Err(SceneError::CyclicHierarchy(node.id));
+        }
+
+        Ok(hierarchy)
+    }
+
+    pub fn node(&self, id: ObjectId) -> Option<&SceneNode> {
+        self.indices.get(&id).map(|w| &self.nodes[*w])
+    }
+
+    // every model, in file order
+    pub fn nodes(&self) -> &[SceneNode] {
+        &self.nodes
+    }
+
+    // models under the scene root, in file order
+    pub fn roots(&self) -> &[ObjectId] {
+        &self.roots
+    }
+
+    // skeleton models whose parent is not part of a skeleton, in depth-first order
+    pub fn skeleton_roots(&self) -> Vec<ObjectId> {
+        let is_skeleton = |id: Option<ObjectId>| id.and_then(|w| self.node(w)).map(|w| w.model_type.is_skeleton()).unwrap_or(false);
+        self.depth_first().map(|(_, w)| w).filter(|w| w.model_type.is_skeleton() && !is_skeleton(w.parent)).map(|w| w.id).collect()
+    }
+
+    // the first model with this name in depth-first order
+    pub fn find(&self, name: &str) -> Option<&SceneNode> {
+        self.depth_first().map(|(_, w)| w).find(|w| w.name == name)
+    }
+
+    // every model with this name, in depth-first order
+    pub fn find_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a SceneNode> + 'a {
+        self.depth_first().map(|(_, w)| w).filter(move |w| w.name == name)
+    }
+
+    // every model with its depth, parents before children
+    pub fn depth_first(&self) -> DepthFirst<'_> {
+        DepthFirst { hierarchy: self, stack: self.roots.iter().rev().map(|w| (*w, 0)).collect() }
+    }
+
+    // `id` at depth 0 and everything below it
+    pub fn descendants(&self, id: ObjectId) -> DepthFirst<'_> {
+        let stack = if self.indices.contains_key(&id) { vec![(id, 0)] } else { Vec::new() };
+        DepthFirst { hierarchy: self, stack }
+    }
+
+    // parents of `id`, nearest first
+    pub fn ancestors(&self, id: ObjectId) -> Vec<&SceneNode> {
+        let mut ancestors = Vec::new();
+        let mut current = self.node(id).and_then(|w| w.parent);
+
+        while let Some(node) = current.and_then(|w| self.node(w)) {
+            ancestors.push(node);
+            current = node.parent;
+        }
+
+        ancestors
+    }
+
+    // names from the root down to `id`, joined with `/`
+    pub fn path(&self, id: ObjectId) -> Option<String> {
+        let node = self.node(id)?;
+        let mut names: Vec<&str> = self.ancestors(id).into_iter().map(|w| w.name.as_str()).collect();
+        names.reverse();
+        names.push(&node.name);
+
+        Some(names.join("/"))
+    }
+}
+
+/// Depth-first iterator over a `SceneHierarchy`, yielding every node with its depth.
+#[derive(Debug, Clone)]
+pub struct DepthFirst<'a> {
+    hierarchy: &'a SceneHierarchy,
+    stack: Vec<(ObjectId, usize)>,
+}
+
+impl<'a> Iterator for DepthFirst<'a> {
+    type Item = (usize, &'a SceneNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, depth) = self.stack.pop()?;
+        let node = self.hierarchy.node(id)?;
+        self.stack.extend(node.children.iter().rev().map(|w| (*w, depth + 1)));
+
+        Some((depth, node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::ROOT_ID;
+    use crate::testing::*;
+
+    // an armature with two legs of the same name as a root model, plus a mesh and a camera
+    fn hierarchy() -> SceneHierarchy {
+        let objects = vec![
+            object("Model", 1, "Armature", "Null", vec![]),
+            object("Model", 2, "Hips", "LimbNode", vec![]),
+            object("Model", 3, "Spine", "LimbNode", vec![]),
+            object("Model", 4, "Leg", "LimbNode", vec![]),
+            object("Model", 5, "Body", "Mesh", vec![]),
+            object("Model", 6, "Camera", "Camera", vec![]),
+            object("Model", 7, "Leg", "", vec![]),
+            object("NodeAttribute", 20, "Hips", "LimbNode", vec![]),
+            object("NodeAttribute", 21, "Camera", "Camera", vec![]),
+        ];
+        let connections = vec![oo(1, ROOT_ID), oo(2, 1), oo(3, 2), oo(4, 2), oo(5, 1), oo(6, ROOT_ID), oo(7, ROOT_ID), oo(20, 2), oo(21, 6)];
+
+        SceneHierarchy::from_graph(&graph(objects, connections)).unwrap()
+    }
+
+    #[test]
+    fn parses_model_types() {
+        assert_eq!(ModelType::parse("LimbNode"), ModelType::LimbNode);
+        assert_eq!(ModelType::parse(""), ModelType::Null);
+        assert_eq!(ModelType::parse("Nurbs"), ModelType::Other("Nurbs".to_owned()));
+        assert!(ModelType::Root.is_skeleton());
+        assert!(!ModelType::Mesh.is_skeleton());
+    }
+
+    #[test]
+    fn walks_depth_first_with_depths() {
+        let hierarchy = hierarchy();
+
+        let order: Vec<(usize, ObjectId)> = hierarchy.depth_first().map(|(depth, w)| (depth, w.id())).collect();
+        assert_eq!(order, [(0, 1), (1, 2), (2, 3), (2, 4), (1, 5), (0, 6), (0, 7)]);
+        assert_eq!(hierarchy.roots(), [1, 6, 7]);
+
+        let below: Vec<(usize, ObjectId)> = hierarchy.descendants(2).map(|(depth, w)| (depth, w.id())).collect();
+        assert_eq!(below, [(0, 2), (1, 3), (1, 4)]);
+        assert_eq!(hierarchy.descendants(99).count(), 0);
+        assert_eq!(hierarchy.skeleton_roots(), [2]);
+    }
+
+    #[test]
+    fn finds_models_and_their_paths() {
+        let hierarchy = hierarchy();
+
+        assert_eq!(hierarchy.find("Leg").unwrap().id(), 4);
+        assert_eq!(hierarchy.find_all("Leg").map(|w| w.id()).collect::<Vec<_>>(), [4, 7]);
+        assert!(hierarchy.find("Arm").is_none());
+
+        assert_eq!(hierarchy.path(4).as_deref(), Some("Armature/Hips/Leg"));
+        assert_eq!(hierarchy.path(7).as_deref(), Some("Leg"));
+        assert_eq!(hierarchy.path(99), None);
+        assert_eq!(hierarchy.ancestors(3).iter().map(|w| w.id()).collect::<Vec<_>>(), [2, 1]);
+        assert!(hierarchy.ancestors(1).is_empty());
+    }
+
+    #[test]
+    fn links_parents_children_and_node_attributes() {
+        let hierarchy = hierarchy();
+
+        let hips = hierarchy.node(2).unwrap();
+        assert_eq!((hips.name(), hips.model_type(), hips.parent()), ("Hips", &ModelType::LimbNode, Some(1)));
+        assert_eq!(hips.children(), [3, 4]);
+        assert_eq!((hips.attribute(), hips.attribute_type()), (Some(20), Some("LimbNode")));
+
+        let camera = hierarchy.node(6).unwrap();
+        assert_eq!((camera.parent(), camera.attribute(), camera.attribute_type()), (None, Some(21), Some("Camera")));
+
+        let body = hierarchy.node(5).unwrap();
+        assert_eq!((body.attribute(), body.attribute_type()), (None, None));
+        assert_eq!(hierarchy.node(7).unwrap().model_type(), &ModelType::Null);
+    }
+
+    #[test]
+    fn rejects_parent_loops() {
+        let objects = vec![object("Model", 1, "a", "Null", vec![]), object("Model", 2, "b", "Null", vec![]), object("Model", 3, "c", "Null", vec![])];
+        let graph = graph(objects, vec![oo(3, ROOT_ID), oo(1, 2), oo(2, 1)]);
+
+        assert!(matches!(SceneHierarchy::from_graph(&graph), Err(SceneError::CyclicHierarchy(1))));
+    }
+}
@@ -0,0 +1,585 @@
+use std::collections::HashMap;
+
+use crate::error::SceneError;
+use crate::graph::{ObjectGraph, ObjectId};
+use crate::hierarchy::SceneHierarchy;
+use crate::transform::TransformEvaluator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Finger {
+    Thumb,
+    Index,
+    Middle,
+    Ring,
+    Little,
+}
+
+impl Finger {
+    // 3ds Max numbers its fingers from the thumb
+    fn from_number(number: u32) -> Option<Self> {
+        match number {
+            0 => Some(Finger::Thumb),
+            1 => Some(Finger::Index),
+            2 => Some(Finger::Middle),
+            3 => Some(Finger::Ring),
+            4 => Some(Finger::Little),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BodyPart {
+    Hips,
+    Spine,
+    Chest,
+    UpperChest,
+    Neck,
+    Head,
+    Jaw,
+    Eye,
+    Shoulder,
+    UpperArm,
+    LowerArm,
+    Hand,
+    UpperLeg,
+    LowerLeg,
+    Foot,
+    Toes,
+    // segment 1 is the proximal, 3 the distal one
+    Finger(Finger, u8),
+}
+
+impl BodyPart {
+    fn is_sided(self) -> bool {
+        !matches!(self, BodyPart::Hips | BodyPart::Spine | BodyPart::Chest | BodyPart::UpperChest | BodyPart::Neck | BodyPart::Head | BodyPart::Jaw)
+    }
+}
+
+/// A bone of the standard humanoid rig, named like Unity's `HumanBodyBones`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HumanoidBone {
+    pub part: BodyPart,
+    // `None` for bones on the center line
+    pub side: Option<Side>,
+}
+
+impl HumanoidBone {
+    pub const fn new(part: BodyPart, side: Option<Side>) -> Self {
+        HumanoidBone { part, side }
+    }
+
+    // e.g. `Hips`, `LeftUpperArm` or `RightIndexIntermediate`
+    pub fn name(&self) -> String {
+        let side = match self.side {
+            Some(Side::Left) => "Left",
+            Some(Side::Right) => "Right",
+            None => "",
+        };
+
+        let part = match self.part {
+            BodyPart::Finger(finger, segment) => {
+                let segment = match segment {
+                    1 => "Proximal",
+                    2 => "Intermediate",
+                    _ => "Distal",
+                };
+                format!("{:?}{}", finger, segment)
+            }
+            part => format!("{:?}", part),
+        };
+
+        format!("{}{}", side, part)
+    }
+
+    // the bones a humanoid avatar cannot do without, the same set Unity requires
+    pub fn required() -> Vec<HumanoidBone> {
+        let mut bones = vec![
+            HumanoidBone::new(BodyPart::Hips, None),
+            HumanoidBone::new(BodyPart::Spine, None),
+            HumanoidBone::new(BodyPart::Head, None),
+        ];
+
+        for side in [Side::Left, Side::Right] {
+            for part in [BodyPart::UpperArm, BodyPart::LowerArm, BodyPart::Hand, BodyPart::UpperLeg, BodyPart::LowerLeg, BodyPart::Foot] {
+                bones.push(HumanoidBone::new(part, Some(side)));
+            }
+        }
+
+        bones
+    }
+}
+
+/// A skeleton model recognised as a humanoid bone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoneMatch {
+    pub bone: HumanoidBone,
+    pub node: ObjectId,
+    pub name: String,
+    // 1 for an unambiguous name in the expected place of the hierarchy, lower for guesses
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HumanoidMapping {
+    matches: Vec<BoneMatch>,
+    unmapped_required: Vec<HumanoidBone>,
+}
+
+impl HumanoidMapping {
+    // matched bones, ordered like the skeleton
+    pub fn matches(&self) -> &[BoneMatch] {
+        &self.matches
+    }
+
+    pub fn get(&self, bone: HumanoidBone) -> Option<&BoneMatch> {
+        self.matches.iter().find(|w| w.bone == bone)
+    }
+
+    pub fn unmapped_required(&self) -> &[HumanoidBone] {
+        &self.unmapped_required
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.unmapped_required.is_empty()
+    }
+
+    // average confidence of the required bones, unmapped ones counting as 0
+    pub fn confidence(&self) -> f64 {
+        let required = HumanoidBone::required();
+        required.iter().map(|w| self.get(*w).map(|m| m.confidence).unwrap_or_default()).sum::<f64>() / required.len() as f64
+    }
+}
+
+// splits a bone name into lowercase words and numbers, ignoring namespaces like `mixamorig:`
+fn tokens(name: &str) -> Vec<String> {
+    let name = name.rsplit([':', '|']).next().unwrap_or(name);
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut previous: Option<char> = None;
+
+    for c in name.chars() {
+        let boundary = match previous {
+            _ if !c.is_alphanumeric() => true,
+            Some(p) => (p.is_lowercase() && c.is_uppercase()) || (p.is_ascii_digit() != c.is_ascii_digit()),
+            None => false,
+        };
+
+        if boundary && !current.is_empty() {
+            tokens.push(std::mem::take(&mut current).to_lowercase());
+        }
+
+        if c.is_alphanumeric() {
+            current.push(c);
+        }
+
+        previous = Some(c);
+    }
+
+    if !current.is_empty() {
+        tokens.push(current.to_lowercase());
+    }
+
+    tokens
+}
+
+// what a bone name says: the body part and side, or only that it is part of the spine
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Guess {
+    Part(BodyPart, f64),
+    // a spine segment whose place in the chain decides between `Spine`, `Chest` and `UpperChest`
+    SpineSegment,
+    // a finger whose segment follows from its depth below the hand
+    Finger(Finger),
+}
+
+// prefixes of the usual conventions that carry no meaning: VRoid's `J_Bip_C_`, Blender's `DEF-` and `f_`
+const NOISE: [&str; 10] = ["j", "bip", "c", "def", "org", "mch", "f", "jnt", "joint", "sk"];
+
+fn classify(name: &str) -> Option<(Guess, Option<Side>)> {
+    let tokens = tokens(name);
+
+    // end bones of Mixamo and 3ds Max only mark where the chain stops
+    if tokens.iter().any(|w| w == "end" || w == "nub" || w == "tip") {
+        return None;
+    }
+
+    let mut side = None;
+    let mut words = String::new();
+    let mut word_tokens = Vec::new();
+    let mut numbers = Vec::new();
+    let mut after_bip = false;
+
+    for token in tokens.iter() {
+        match token.as_str() {
+            "l" | "left" => side = Some(Side::Left),
+            "r" | "right" => side = Some(Side::Right),
+            w if w.chars().all(|c| c.is_ascii_digit()) => {
+                // the `01` of `Bip01` is not part of the bone name
+                if !after_bip {
+                    numbers.push(w.to_owned());
+                }
+            }
+            w if NOISE.contains(&w) => {}
+            w => {
+                words.push_str(w);
+                word_tokens.push(w);
+            }
+        }
+
+        after_bip = token == "bip";
+    }
+
+    // whole words only, so `Spring` or `Earring` are not ring fingers
+    for (word, finger) in [("thumb", Finger::Thumb), ("index", Finger::Index), ("middle", Finger::Middle), ("ring", Finger::Ring), ("little", Finger::Little), ("pinky", Finger::Little)] {
+        if word_tokens.contains(&word) {
+            return Some((Guess::Finger(finger), side));
+        }
+    }
+
+    // 3ds Max: `Finger0` is the thumb, `Finger01` its second segment
+    if words == "finger" {
+        let finger = numbers.last().and_then(|w| w.chars().next()).and_then(|w| w.to_digit(10)).and_then(Finger::from_number)?;
+        return Some((Guess::Finger(finger), side));
+    }
+
+    let guess = match words.as_str() {
+        "hips" | "hip" | "pelvis" => Guess::Part(BodyPart::Hips, 1.0),
+        "spine" | "torso" | "abdomen" | "waist" => Guess::SpineSegment,
+        "chest" => Guess::Part(BodyPart::Chest, 1.0),
+        "upperchest" => Guess::Part(BodyPart::UpperChest, 1.0),
+        "neck" => Guess::Part(BodyPart::Neck, 1.0),
+        "head" => Guess::Part(BodyPart::Head, 1.0),
+        "jaw" => Guess::Part(BodyPart::Jaw, 1.0),
+        "eye" => Guess::Part(BodyPart::Eye, 1.0),
+        "shoulder" | "clavicle" | "collar" | "collarbone" => Guess::Part(BodyPart::Shoulder, 1.0),
+        "upperarm" | "uparm" => Guess::Part(BodyPart::UpperArm, 1.0),
+        "arm" => Guess::Part(BodyPart::UpperArm, 0.8),
+        "lowerarm" | "forearm" | "lowarm" => Guess::Part(BodyPart::LowerArm, 1.0),
+        "elbow" => Guess::Part(BodyPart::LowerArm, 0.7),
+        "hand" => Guess::Part(BodyPart::Hand, 1.0),
+        "wrist" => Guess::Part(BodyPart::Hand, 0.8),
+        "upperleg" | "upleg" | "thigh" => Guess::Part(BodyPart::UpperLeg, 1.0),
+        "lowerleg" | "lowleg" | "shin" | "calf" => Guess::Part(BodyPart::LowerLeg, 1.0),
+        "leg" => Guess::Part(BodyPart::LowerLeg, 0.8),
+        "knee" => Guess::Part(BodyPart::LowerLeg, 0.7),
+        "foot" => Guess::Part(BodyPart::Foot, 1.0),
+        "ankle" => Guess::Part(BodyPart::Foot, 0.8),
+        "toes" | "toe" | "toebase" => Guess::Part(BodyPart::Toes, 1.0),
+        "ball" => Guess::Part(BodyPart::Toes, 0.8),
+        _ => return None,
+    };
+
+    Some((guess, side))
+}
+
+// (parent, child) pairs where the child must be below the parent
+const CHAINS: [(BodyPart, BodyPart); 10] = [
+    (BodyPart::Hips, BodyPart::Spine),
+    (BodyPart::Spine, BodyPart::Neck),
+    (BodyPart::Neck, BodyPart::Head),
+    (BodyPart::Shoulder, BodyPart::UpperArm),
+    (BodyPart::UpperArm, BodyPart::LowerArm),
+    (BodyPart::LowerArm, BodyPart::Hand),
+    (BodyPart::Hips, BodyPart::UpperLeg),
+    (BodyPart::UpperLeg, BodyPart::LowerLeg),
+    (BodyPart::LowerLeg, BodyPart::Foot),
+    (BodyPart::Foot, BodyPart::Toes),
+];
+
+// finger bones of one hand with their depth in the hierarchy and the confidence of their side
+type FingerChains = HashMap<(Finger, Side), Vec<(usize, ObjectId, f64)>>;
+
+struct Matcher<'a> {
+    hierarchy: &'a SceneHierarchy,
+    matches: HashMap<HumanoidBone, (ObjectId, f64)>,
+}
+
+impl<'a> Matcher<'a> {
+    // keeps the better candidate when two models claim the same bone, the one found first on a tie
+    fn offer(&mut self, bone: HumanoidBone, node: ObjectId, confidence: f64) {
+        let taken = self.matches.values().any(|(w, _)| *w == node);
+        let better = self.matches.get(&bone).map(|(_, c)| confidence > *c).unwrap_or(true);
+
+        if !taken && better {
+            self.matches.insert(bone, (node, confidence));
+        }
+    }
+
+    fn node(&self, bone: HumanoidBone) -> Option<ObjectId> {
+        self.matches.get(&bone).map(|w| w.0)
+    }
+
+    fn parent(&self, node: ObjectId) -> Option<ObjectId> {
+        self.hierarchy.node(node).and_then(|w| w.parent())
+    }
+
+    fn is_below(&self, node: ObjectId, ancestor: ObjectId) -> bool {
+        self.hierarchy.ancestors(node).iter().any(|w| w.id() == ancestor)
+    }
+
+    // skeleton children of a model
+    fn bone_children(&self, node: ObjectId) -> Vec<ObjectId> {
+        let children = self.hierarchy.node(node).map(|w| w.children().to_vec()).unwrap_or_default();
+        children.into_iter().filter(|w| self.hierarchy.node(*w).map(|n| n.model_type().is_skeleton()).unwrap_or(false)).collect()
+    }
+}
+
+// maps the skeleton models of the scene to humanoid bones, first by name for the Blender, Mixamo, VRoid,
+// Unity and 3ds Max conventions, then by position in the hierarchy for what the names leave open
+pub fn detect_humanoid(graph: &ObjectGraph, hierarchy: &SceneHierarchy) -> Result<HumanoidMapping, SceneError> {
+    let evaluator = TransformEvaluator::new(graph, None);
+    let mut matcher = Matcher { hierarchy, matches: HashMap::new() };
+
+    let bones: Vec<(usize, ObjectId, String)> =
+        hierarchy.depth_first().filter(|(_, w)| w.model_type().is_skeleton()).map(|(depth, w)| (depth, w.id(), w.name().to_owned())).collect();
+
+    let mut spine = Vec::new();
+    let mut fingers = Vec::new();
+    let mut unsided = Vec::new();
+
+    for (depth, id, name) in bones.iter() {
+        let (guess, side) = match classify(name) {
+            Some(classified) => classified,
+            None => continue,
+        };
+
+        match guess {
+            Guess::SpineSegment => spine.push(*id),
+            Guess::Finger(finger) => fingers.push((finger, side, *depth, *id)),
+            Guess::Part(part, confidence) if part.is_sided() && side.is_none() => unsided.push((part, *id, confidence)),
+            Guess::Part(part, confidence) => matcher.offer(HumanoidBone::new(part, if part.is_sided() { side } else { None }), *id, confidence),
+        }
+    }
+
+    // mixamo's `Spine`, `Spine1`, `Spine2` fill the spine, chest and upper chest in chain order
+    for id in spine {
+        let free = [BodyPart::Spine, BodyPart::Chest, BodyPart::UpperChest].into_iter().map(|w| HumanoidBone::new(w, None)).find(|w| matcher.node(*w).is_none());
+
+        if let Some(bone) = free {
+            matcher.offer(bone, id, 0.9);
+        }
+    }
+
+    // names without a side get it from the rest pose, characters face +Z with their left towards +X
+    let center = matcher.node(HumanoidBone::new(BodyPart::Hips, None)).map(|w| evaluator.world_matrix(w, 0)).transpose()?.map(|w| w.get_translation()[0]);
+    let side_of = |id: ObjectId| -> Result<Option<Side>, SceneError> {
+        let x = evaluator.world_matrix(id, 0)?.get_translation()[0] - center.unwrap_or_default();
+        Ok(if x.abs() > 1e-6 { Some(if x > 0.0 { Side::Left } else { Side::Right }) } else { None })
+    };
+
+    for (part, id, confidence) in unsided {
+        if let Some(side) = side_of(id)? {
+            matcher.offer(HumanoidBone::new(part, Some(side)), id, confidence * 0.6);
+        }
+    }
+
+    fill_gaps(&mut matcher);
+
+    // fingers without a side in their name take the side of the hand they are below, or of their position
+    let mut chains = FingerChains::new();
+    for (finger, side, depth, id) in fingers {
+        let (side, confidence) = match side {
+            Some(side) => (side, 0.9),
+            None => {
+                let hand = [Side::Left, Side::Right].into_iter().find(|w| matcher.node(HumanoidBone::new(BodyPart::Hand, Some(*w))).map(|hand| matcher.is_below(id, hand)).unwrap_or(false));
+
+                let side = match hand {
+                    Some(side) => Some(side),
+                    None => side_of(id)?,
+                };
+
+                match side {
+                    Some(side) => (side, 0.5),
+                    None => continue,
+                }
+            }
+        };
+
+        chains.entry((finger, side)).or_default().push((depth, id, confidence));
+    }
+
+    // segments follow from the depth below the hand, whatever numbering the convention uses
+    for ((finger, side), mut segments) in chains {
+        segments.sort_by_key(|w| w.0);
+        for (segment, (_, id, confidence)) in segments.into_iter().take(3).enumerate() {
+            matcher.offer(HumanoidBone::new(BodyPart::Finger(finger, segment as u8 + 1), Some(side)), id, confidence);
+        }
+    }
+
+    // a bone that is not below its parent bone was probably matched to the wrong model
+    let mut penalties = Vec::new();
+    for (bone, (node, _)) in matcher.matches.iter() {
+        let mut parents = CHAINS.iter().filter(|(_, child)| *child == bone.part).map(|(parent, _)| HumanoidBone::new(*parent, if parent.is_sided() { bone.side } else { None }));
+
+        if parents.any(|w| matcher.node(w).map(|parent| !matcher.is_below(*node, parent)).unwrap_or(false)) {
+            penalties.push(*bone);
+        }
+    }
+
+    for bone in penalties {
+        if let Some(matched) = matcher.matches.get_mut(&bone) {
+            matched.1 *= 0.5;
+        }
+    }
+
+    let mut matches: Vec<BoneMatch> = Vec::new();
+    for (_, id, name) in bones.iter() {
+        if let Some((bone, (_, confidence))) = matcher.matches.iter().find(|(_, (node, _))| node == id) {
+            matches.push(BoneMatch { bone: *bone, node: *id, name: name.clone(), confidence: *confidence });
+        }
+    }
+
+    let unmapped_required = HumanoidBone::required().into_iter().filter(|w| matcher.node(*w).is_none()).collect();
+
+    Ok(HumanoidMapping { matches, unmapped_required })
+}
+
+// infers bones the names missed from their neighbours in the chain
+fn fill_gaps(matcher: &mut Matcher) {
+    for side in [Side::Left, Side::Right] {
+        for [upper, middle, lower] in [[BodyPart::UpperArm, BodyPart::LowerArm, BodyPart::Hand], [BodyPart::UpperLeg, BodyPart::LowerLeg, BodyPart::Foot]] {
+            let bone = |part| HumanoidBone::new(part, Some(side));
+
+            match (matcher.node(bone(upper)), matcher.node(bone(middle)), matcher.node(bone(lower))) {
+                // the model between the two ends
+                (Some(upper_node), None, Some(lower_node)) => {
+                    if let Some(parent) = matcher.parent(lower_node).filter(|w| matcher.parent(*w) == Some(upper_node)) {
+                        matcher.offer(bone(middle), parent, 0.6);
+                    }
+                }
+                // the only child of each bone down the chain
+                (Some(upper_node), None, None) => {
+                    if let [child] = matcher.bone_children(upper_node)[..] {
+                        matcher.offer(bone(middle), child, 0.4);
+
+                        if let [grandchild] = matcher.bone_children(child)[..] {
+                            matcher.offer(bone(lower), grandchild, 0.4);
+                        }
+                    }
+                }
+                (_, Some(middle_node), None) => {
+                    if let [child] = matcher.bone_children(middle_node)[..] {
+                        matcher.offer(bone(lower), child, 0.4);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // the hips are where both legs meet
+    let legs = [Side::Left, Side::Right].map(|w| matcher.node(HumanoidBone::new(BodyPart::UpperLeg, Some(w))).and_then(|n| matcher.parent(n)));
+    if let [Some(left), Some(right)] = legs {
+        if left == right {
+            matcher.offer(HumanoidBone::new(BodyPart::Hips, None), left, 0.5);
+        }
+    }
+
+    let neck = matcher.node(HumanoidBone::new(BodyPart::Neck, None));
+    if let Some(neck) = neck.filter(|_| matcher.node(HumanoidBone::new(BodyPart::Head, None)).is_none()) {
+        if let [child] = matcher.bone_children(neck)[..] {
+            matcher.offer(HumanoidBone::new(BodyPart::Head, None), child, 0.5);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    // skeleton models, each with its parent and rest translation relative to it
+    fn skeleton(bones: &[(ObjectId, &str, ObjectId, [f64; 3])]) -> HumanoidMapping {
+        let objects = bones.iter().map(|(id, name, _, translation)| object("Model", *id, name, "LimbNode", vec![properties70(vec![vector_property("Lcl Translation", "Lcl Translation", *translation)])])).collect();
+        let connections = bones.iter().map(|(id, _, parent, _)| oo(*id, *parent)).collect();
+
+        let graph = graph(objects, connections);
+        detect_humanoid(&graph, &SceneHierarchy::from_graph(&graph).unwrap()).unwrap()
+    }
+
+    fn mapped(mapping: &HumanoidMapping) -> Vec<(String, String)> {
+        mapping.matches().iter().map(|w| (w.name.clone(), w.bone.name())).collect()
+    }
+
+    #[test]
+    fn splits_names_into_tokens() {
+        assert_eq!(tokens("mixamorig:LeftHandIndex1"), ["left", "hand", "index", "1"]);
+        assert_eq!(tokens("J_Bip_L_UpperArm"), ["j", "bip", "l", "upper", "arm"]);
+        assert_eq!(tokens("Bip01 R Finger01"), ["bip", "01", "r", "finger", "01"]);
+    }
+
+    #[test]
+    fn classifies_fingers_by_whole_words() {
+        assert_eq!(classify("mixamorig:LeftHandRing2"), Some((Guess::Finger(Finger::Ring), Some(Side::Left))));
+        assert_eq!(classify("f_pinky.01.R"), Some((Guess::Finger(Finger::Little), Some(Side::Right))));
+        assert_eq!(classify("Bip01 L Finger0"), Some((Guess::Finger(Finger::Thumb), Some(Side::Left))));
+        assert_eq!(classify("Index1"), Some((Guess::Finger(Finger::Index), None)));
+
+        for name in ["HairSpring", "Earring_L", "String", "Reindexed"] {
+            assert_eq!(classify(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn maps_a_mixamo_skeleton() {
+        let mapping = skeleton(&[
+            (1, "mixamorig:Hips", 0, [0.0, 100.0, 0.0]),
+            (2, "mixamorig:Spine", 1, [0.0, 10.0, 0.0]),
+            (3, "mixamorig:Spine1", 2, [0.0, 10.0, 0.0]),
+            (4, "mixamorig:Neck", 3, [0.0, 20.0, 0.0]),
+            (5, "mixamorig:Head", 4, [0.0, 10.0, 0.0]),
+            (6, "mixamorig:HeadTop_End", 5, [0.0, 10.0, 0.0]),
+            (7, "mixamorig:HairSpring", 5, [0.0, 5.0, -5.0]),
+            (10, "mixamorig:LeftArm", 3, [15.0, 15.0, 0.0]),
+            (11, "mixamorig:LeftForeArm", 10, [25.0, 0.0, 0.0]),
+            (12, "mixamorig:LeftHand", 11, [25.0, 0.0, 0.0]),
+            (13, "mixamorig:LeftHandIndex1", 12, [8.0, 0.0, 0.0]),
+            (14, "mixamorig:LeftHandIndex2", 13, [3.0, 0.0, 0.0]),
+            (20, "mixamorig:RightArm", 3, [-15.0, 15.0, 0.0]),
+            (21, "mixamorig:RightForeArm", 20, [-25.0, 0.0, 0.0]),
+            (22, "mixamorig:RightHand", 21, [-25.0, 0.0, 0.0]),
+            (30, "mixamorig:LeftUpLeg", 1, [10.0, 0.0, 0.0]),
+            (31, "mixamorig:LeftLeg", 30, [0.0, -45.0, 0.0]),
+            (32, "mixamorig:LeftFoot", 31, [0.0, -45.0, 0.0]),
+            (40, "mixamorig:RightUpLeg", 1, [-10.0, 0.0, 0.0]),
+            (41, "mixamorig:RightLeg", 40, [0.0, -45.0, 0.0]),
+            (42, "mixamorig:RightFoot", 41, [0.0, -45.0, 0.0]),
+        ]);
+
+        assert!(mapping.is_complete(), "{:?}", mapping.unmapped_required());
+        assert_eq!(mapping.get(HumanoidBone::new(BodyPart::Chest, None)).map(|w| w.node), Some(3));
+        assert_eq!(mapping.get(HumanoidBone::new(BodyPart::Finger(Finger::Index, 2), Some(Side::Left))).map(|w| w.node), Some(14));
+        assert!(mapping.matches().iter().all(|w| w.node != 6 && w.node != 7), "{:?}", mapped(&mapping));
+        // mixamo's `Spine`, `Arm` and `Leg` are weaker names than the rest
+        assert_close(mapping.confidence(), (0.9 + 4.0 * 0.8 + 10.0) / 15.0, 1e-9);
+    }
+
+    #[test]
+    fn fingers_without_a_side_take_the_side_of_their_hand() {
+        let mapping = skeleton(&[
+            (1, "Hips", 0, [0.0, 100.0, 0.0]),
+            (2, "Spine", 1, [0.0, 10.0, 0.0]),
+            (10, "UpperArm_L", 2, [15.0, 15.0, 0.0]),
+            (11, "LowerArm_L", 10, [25.0, 0.0, 0.0]),
+            (12, "Hand_L", 11, [25.0, 0.0, 0.0]),
+            (13, "Thumb1", 12, [2.0, 0.0, 3.0]),
+            (20, "UpperArm_R", 2, [-15.0, 15.0, 0.0]),
+            (21, "LowerArm_R", 20, [-25.0, 0.0, 0.0]),
+            (22, "Hand_R", 21, [-25.0, 0.0, 0.0]),
+            (23, "Thumb1", 22, [-2.0, 0.0, 3.0]),
+            // a finger on the center line with no hand above it cannot be placed
+            (30, "Thumb1", 2, [0.0, 0.0, 3.0]),
+        ]);
+
+        assert_eq!(mapping.get(HumanoidBone::new(BodyPart::Finger(Finger::Thumb, 1), Some(Side::Left))).map(|w| w.node), Some(13));
+        assert_eq!(mapping.get(HumanoidBone::new(BodyPart::Finger(Finger::Thumb, 1), Some(Side::Right))).map(|w| w.node), Some(23));
+        assert!(mapping.matches().iter().all(|w| w.node != 30 && (!matches!(w.bone.part, BodyPart::Finger(..)) || w.bone.side.is_some())));
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+
+use fbx::format::{Node, Type};
+
+use crate::error::SceneError;
+use crate::mesh::{child, Mesh};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MappingMode {
+    ByPolygonVertex,
+    ByControlPoint,
+    ByPolygon,
+    ByEdge,
+    AllSame,
+    NoMapping,
+}
+
+impl MappingMode {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "ByPolygonVertex" => MappingMode::ByPolygonVertex,
+            "ByControlPoint" | "ByVertice" | "ByVertex" => MappingMode::ByControlPoint,
+            "ByPolygon" => MappingMode::ByPolygon,
+            "ByEdge" => MappingMode::ByEdge,
+            "AllSame" => MappingMode::AllSame,
+            _ => MappingMode::NoMapping,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceMode {
+    Direct,
+    IndexToDirect,
+}
+
+impl ReferenceMode {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "IndexToDirect" | "Index" => ReferenceMode::IndexToDirect,
+            _ => ReferenceMode::Direct,
+        }
+    }
+}
+
+// data array, index array and components per value of the known element types
+fn layout(element_type: &str) -> Option<(&'static str, Option<&'static str>, usize)> {
+    let layout = match element_type {
+        "LayerElementNormal" => ("Normals", Some("NormalsIndex"), 3),
+        "LayerElementBinormal" => ("Binormals", Some("BinormalsIndex"), 3),
+        "LayerElementTangent" => ("Tangents", Some("TangentsIndex"), 3),
+        "LayerElementUV" => ("UV", Some("UVIndex"), 2),
+        "LayerElementColor" => ("Colors", Some("ColorIndex"), 4),
+        "LayerElementSmoothing" => ("Smoothing", None, 1),
+        "LayerElementMaterial" => ("Materials", None, 1),
+        "LayerElementVisibility" => ("Visibility", None, 1),
+        "LayerElementEdgeCrease" => ("EdgeCrease", None, 1),
+        "LayerElementVertexCrease" => ("VertexCrease", None, 1),
+        "LayerElementPolygonGroup" => ("PolygonGroup", None, 1),
+        "LayerElementHole" => ("Hole", None, 1),
+        _ => return None,
+    };
+
+    Some(layout)
+}
+
+// data arrays widened to f64; visibility and hole elements store booleans, read as 0 and 1
+fn element_values(value: &Type) -> Option<Vec<f64>> {
+    value.to_f64_vec().or_else(|| value.as_bool_slice().map(|w| w.iter().map(|b| if *b { 1.0 } else { 0.0 }).collect()))
+}
+
+/// A `LayerElement*` node of a geometry, e.g. normals, UVs, vertex colors or material indices.
+#[derive(Debug, Clone)]
+pub struct LayerElement {
+    element_type: String,
+    index: i32,
+    name: String,
+    mapping: MappingMode,
+    reference: ReferenceMode,
+    values: Vec<f64>,
+    indices: Option<Vec<i32>>,
+    stride: usize,
+}
+
+impl LayerElement {
+    pub fn from_node(node: &Node) -> Result<LayerElement, SceneError> {
+        let element_type = node.name();
+        let text = |name: &str| child(node, name).and_then(|w| w.attributes_slice().first()).and_then(|w| w.as_str());
+
+        let (data, index_name, stride) = match layout(&element_type) {
+            Some(layout) => layout,
+            None => return Err(SceneError::UnknownLayerElement(element_type)),
+        };
+
+        let values = child(node, data)
+            .and_then(|w| w.attributes_slice().first())
+            .and_then(element_values)
+            .ok_or(SceneError::MissingNode(data.to_owned()))?;
+
+        if !values.len().is_multiple_of(stride) {
+            return Err(SceneError::InvalidArrayLength(data.to_owned(), values.len()));
+        }
+
+        let mut reference = ReferenceMode::parse(&text("ReferenceInformationType").unwrap_or_default());
+        let indices = index_name.and_then(|w| child(node, w)).and_then(|w| w.attributes_slice().first()).and_then(|w| w.as_int32_array());
+
+        // material and smoothing elements claim `IndexToDirect` but their data already is the index
+        if indices.is_none() {
+            reference = ReferenceMode::Direct;
+        }
+
+        Ok(LayerElement {
+            element_type,
+            index: node.attributes_slice().first().and_then(|w| w.to_i64()).unwrap_or_default() as i32,
+            name: text("Name").unwrap_or_default(),
+            mapping: MappingMode::parse(&text("MappingInformationType").unwrap_or_default()),
+            reference,
+            values,
+            indices,
+            stride,
+        })
+    }
+
+    // node name, e.g. `LayerElementUV`
+    pub fn element_type(&self) -> &str {
+        &self.element_type
+    }
+
+    // typed index referenced by `Layer` nodes
+    pub fn index(&self) -> i32 {
+        self.index
+    }
+
+    // e.g. the UV set name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn mapping(&self) -> MappingMode {
+        self.mapping
+    }
+
+    pub fn reference(&self) -> ReferenceMode {
+        self.reference
+    }
+
+    // number of components of every value
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn value_count(&self) -> usize {
+        self.values.len() / self.stride
+    }
+
+    pub fn value(&self, index: usize) -> &[f64] {
+        &self.values[index * self.stride..(index + 1) * self.stride]
+    }
+
+    pub fn indices(&self) -> Option<&[i32]> {
+        self.indices.as_deref()
+    }
+
+    // index into the mapped elements (polygon vertices, control points, ...) of every polygon vertex
+    fn mapped_elements(&self, mesh: &Mesh) -> Result<Vec<usize>, SceneError> {
+        let count = mesh.polygon_vertex_count();
+
+        let elements = match self.mapping {
+            MappingMode::ByPolygonVertex => (0..count).collect(),
+            MappingMode::ByControlPoint => mesh.polygon_vertices().iter().map(|w| *w as usize).collect(),
+            MappingMode::ByPolygon => mesh.polygon_vertex_polygons(),
+            MappingMode::AllSame => vec![0; count],
+            MappingMode::NoMapping => Vec::new(),
+            MappingMode::ByEdge => {
+                // edges are shared between polygons, so they are matched by their control points
+                let mut edges: HashMap<(u32, u32), usize> = HashMap::new();
+                for (edge, (a, b)) in mesh.edge_vertices().into_iter().enumerate() {
+                    edges.entry((a.min(b), a.max(b))).or_insert(edge);
+                }
+
+                let vertices = mesh.polygon_vertices();
+                let mut elements = Vec::with_capacity(count);
+
+                for polygon_vertex in 0..count {
+                    let (a, b) = (vertices[polygon_vertex], vertices[mesh.next_polygon_vertex(polygon_vertex)]);
+                    elements.push(*edges.get(&(a.min(b), a.max(b))).ok_or(SceneError::MissingEdge(a, b))?);
+                }
+
+                elements
+            }
+        };
+
+        Ok(elements)
+    }
+
+    // index into the values of every polygon vertex, empty if the element has no mapping
+    pub fn polygon_vertex_indices(&self, mesh: &Mesh) -> Result<Vec<usize>, SceneError> {
+        let mut elements = self.mapped_elements(mesh)?;
+
+        if let (ReferenceMode::IndexToDirect, Some(indices)) = (self.reference, &self.indices) {
+            for element in elements.iter_mut() {
+                let index = *indices.get(*element).ok_or(SceneError::IndexOutOfRange(*element as i64, indices.len()))?;
+
+                if index < 0 {
+                    return Err(SceneError::IndexOutOfRange(index as i64, self.value_count()));
+                }
+
+                *element = index as usize;
+            }
+        }
+
+        let count = self.value_count();
+        if let Some(element) = elements.iter().find(|w| **w >= count) {
+            return Err(SceneError::IndexOutOfRange(*element as i64, count));
+        }
+
+        Ok(elements)
+    }
+
+    // value of every polygon vertex, components past the stride are zero
+    pub fn decode<const N: usize>(&self, mesh: &Mesh) -> Result<Vec<[f64; N]>, SceneError> {
+        let indices = self.polygon_vertex_indices(mesh)?;
+
+        Ok(indices
+            .into_iter()
+            .map(|w| {
+                let mut value = [0.0; N];
+                for (slot, component) in value.iter_mut().zip(self.value(w)) {
+                    *slot = *component;
+                }
+                value
+            })
+            .collect())
+    }
+
+    // first component of the value of every polygon vertex, for material, smoothing and other integer elements
+    pub fn decode_i32(&self, mesh: &Mesh) -> Result<Vec<i32>, SceneError> {
+        Ok(self.polygon_vertex_indices(mesh)?.into_iter().map(|w| self.values[w * self.stride] as i32).collect())
+    }
+}
+
+/// Reference from a `Layer` to one of the geometry's layer elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerReference {
+    pub element_type: String,
+    pub typed_index: i32,
+}
+
+/// A `Layer` node, grouping one element of each type; extra UV sets live in layers past the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layer {
+    index: i32,
+    elements: Vec<LayerReference>,
+}
+
+impl Layer {
+    pub fn from_node(node: &Node) -> Self {
+        let elements = node
+            .children_slice()
+            .iter()
+            .filter(|w| w.name_str() == "LayerElement")
+            .filter_map(|w| {
+                let element_type = child(w, "Type")?.attributes_slice().first()?.as_str()?;
+                let typed_index = child(w, "TypedIndex").and_then(|w| w.attributes_slice().first()).and_then(|w| w.to_i64()).unwrap_or_default() as i32;
+                Some(LayerReference { element_type, typed_index })
+            })
+            .collect();
+
+        Layer { index: node.attributes_slice().first().and_then(|w| w.to_i64()).unwrap_or_default() as i32, elements }
+    }
+
+    pub fn index(&self) -> i32 {
+        self.index
+    }
+
+    pub fn elements(&self) -> &[LayerReference] {
+        &self.elements
+    }
+}
+
+/// Every layer element and layer of a geometry node.
+#[derive(Debug, Clone, Default)]
+pub struct LayerElements {
+    elements: Vec<LayerElement>,
+    layers: Vec<Layer>,
+}
+
+impl LayerElements {
+    // decodes the known `LayerElement*` children, unknown element types are skipped
+    pub fn from_node(node: &Node) -> Result<LayerElements, SceneError> {
+        let mut elements = Vec::new();
+
+        for element in node.children_slice().iter().filter(|w| w.name_str().starts_with("LayerElement")) {
+            if layout(element.name_str()).is_some() {
+                elements.push(LayerElement::from_node(element)?);
+            }
+        }
+
+        let mut layers: Vec<Layer> = node.children_slice().iter().filter(|w| w.name_str() == "Layer").map(Layer::from_node).collect();
+        layers.sort_by_key(|w| w.index);
+
+        Ok(LayerElements { elements, layers })
+    }
+
+    pub fn from_mesh(mesh: &Mesh) -> Result<LayerElements, SceneError> {
+        LayerElements::from_node(mesh.node())
+    }
+
+    pub fn elements(&self) -> &[LayerElement] {
+        &self.elements
+    }
+
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    pub fn get(&self, element_type: &str, index: i32) -> Option<&LayerElement> {
+        self.elements.iter().find(|w| w.element_type == element_type && w.index == index)
+    }
+
+    // elements of `element_type` in layer order, followed by the ones no layer references
+    pub fn of_type(&self, element_type: &str) -> Vec<&LayerElement> {
+        let mut result: Vec<&LayerElement> = Vec::new();
+
+        let referenced = self.layers.iter().flat_map(|w| w.elements.iter()).filter(|w| w.element_type == element_type);
+        for reference in referenced {
+            if let Some(element) = self.get(element_type, reference.typed_index) {
+                if !result.iter().any(|w| std::ptr::eq(*w, element)) {
+                    result.push(element);
+                }
+            }
+        }
+
+        for element in self.elements.iter().filter(|w| w.element_type == element_type) {
+            if !result.iter().any(|w| std::ptr::eq(*w, element)) {
+                result.push(element);
+            }
+        }
+
+        result
+    }
+
+    // element of `element_type` used by the first layer, the one most tools treat as the default
+    pub fn first(&self, element_type: &str) -> Option<&LayerElement> {
+        self.of_type(element_type).into_iter().next()
+    }
+
+    pub fn uv_sets(&self) -> Vec<&LayerElement> {
+        self.of_type("LayerElementUV")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    // two triangles sharing the edge 1-2: (0, 1, 2) and (2, 1, 3)
+    fn geometry(elements: Vec<Node>) -> Node {
+        let mut children = vec![f64_array("Vertices", &[0.0; 12]), i32_array("PolygonVertexIndex", &[0, 1, -3, 2, 1, -4]), i32_array("Edges", &[0, 1, 2, 4, 5])];
+        children.extend(elements);
+        object("Geometry", 1, "mesh", "Mesh", children)
+    }
+
+    fn element(element_type: &str, index: i32, mapping: &str, reference: &str, data: Node, indices: Option<Node>) -> Node {
+        let mut children = vec![text_node("Name", &format!("{}{}", element_type, index)), text_node("MappingInformationType", mapping), text_node("ReferenceInformationType", reference), data];
+        children.extend(indices);
+        node(element_type, vec![Type::Int32(index)], children)
+    }
+
+    fn layer(index: i32, elements: &[(&str, i32)]) -> Node {
+        let children = elements.iter().map(|(element_type, typed_index)| node("LayerElement", vec![], vec![text_node("Type", element_type), node("TypedIndex", vec![Type::Int32(*typed_index)], vec![])])).collect();
+        node("Layer", vec![Type::Int32(index)], children)
+    }
+
+    fn decode(elements: Vec<Node>) -> (Mesh, LayerElements) {
+        let geometry = geometry(elements);
+        (Mesh::from_node(&geometry).unwrap(), LayerElements::from_node(&geometry).unwrap())
+    }
+
+    #[test]
+    fn decodes_every_mapping_mode() {
+        let (mesh, elements) = decode(vec![
+            element("LayerElementUV", 0, "ByPolygonVertex", "IndexToDirect", f64_array("UV", &[0.0, 0.0, 1.0, 1.0]), Some(i32_array("UVIndex", &[0, 1, 0, 1, 1, 0]))),
+            element("LayerElementNormal", 0, "ByControlPoint", "Direct", f64_array("Normals", &[0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, -1.0]), None),
+            element("LayerElementMaterial", 0, "ByPolygon", "IndexToDirect", i32_array("Materials", &[3, 5]), None),
+            element("LayerElementSmoothing", 0, "ByEdge", "Direct", i32_array("Smoothing", &[0, 1, 0, 0, 0]), None),
+            element("LayerElementColor", 0, "AllSame", "Direct", f64_array("Colors", &[1.0, 0.5, 0.25, 1.0]), None),
+        ]);
+
+        let uv = elements.first("LayerElementUV").unwrap().decode::<2>(&mesh).unwrap();
+        assert_eq!(uv, [[0.0, 0.0], [1.0, 1.0], [0.0, 0.0], [1.0, 1.0], [1.0, 1.0], [0.0, 0.0]]);
+
+        let normals = elements.first("LayerElementNormal").unwrap().decode::<3>(&mesh).unwrap();
+        assert_eq!(normals[3], [1.0, 0.0, 0.0]);
+        assert_eq!(normals[5], [0.0, 0.0, -1.0]);
+
+        assert_eq!(elements.first("LayerElementMaterial").unwrap().decode_i32(&mesh).unwrap(), [3, 3, 3, 5, 5, 5]);
+        // the edge 1-2 is stored once, starting at polygon vertex 1, and is shared by both triangles
+        assert_eq!(elements.first("LayerElementSmoothing").unwrap().decode_i32(&mesh).unwrap(), [0, 1, 0, 1, 0, 0]);
+        assert_eq!(elements.first("LayerElementColor").unwrap().decode::<4>(&mesh).unwrap(), vec![[1.0, 0.5, 0.25, 1.0]; 6]);
+    }
+
+    #[test]
+    fn decodes_boolean_elements() {
+        let (mesh, elements) = decode(vec![element("LayerElementHole", 0, "ByPolygon", "Direct", node("Hole", vec![Type::VecBool(vec![false, true])], vec![]), None)]);
+
+        assert_eq!(elements.first("LayerElementHole").unwrap().decode_i32(&mesh).unwrap(), [0, 0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn orders_elements_by_layer() {
+        let uv = |index: i32| element("LayerElementUV", index, "ByPolygonVertex", "Direct", f64_array("UV", &[0.0; 12]), None);
+        let (_, elements) = decode(vec![uv(0), uv(1), uv(2), layer(1, &[("LayerElementUV", 2)]), layer(0, &[("LayerElementUV", 1)])]);
+
+        let names: Vec<&str> = elements.uv_sets().iter().map(|w| w.name()).collect();
+        assert_eq!(names, ["LayerElementUV1", "LayerElementUV2", "LayerElementUV0"]);
+    }
+
+    #[test]
+    fn rejects_out_of_range_indices() {
+        let (mesh, elements) = decode(vec![element("LayerElementUV", 0, "ByPolygonVertex", "IndexToDirect", f64_array("UV", &[0.0, 0.0]), Some(i32_array("UVIndex", &[0, 0, 0, 0, 0, 1])))]);
+
+        assert!(matches!(elements.first("LayerElementUV").unwrap().decode::<2>(&mesh), Err(SceneError::IndexOutOfRange(1, 1))));
+    }
+}
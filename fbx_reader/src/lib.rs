@@ -2,6 +2,35 @@ extern crate fbx;
 
 use fbx::format::{Node, Object, Type};
 
+use crate::graph::ObjectGraph;
+
+pub mod animation;
+pub mod blendshape;
+pub mod camera;
+pub mod constraint;
+pub mod convert;
+pub mod error;
+pub mod graph;
+pub mod hierarchy;
+pub mod humanoid;
+pub mod layer;
+pub mod light;
+pub mod material;
+pub mod math;
+pub mod media;
+pub mod mesh;
+pub mod nurbs;
+pub mod paths;
+pub mod pose;
+pub mod properties;
+pub mod render;
+pub mod settings;
+pub mod skin;
+pub mod transform;
+
+#[cfg(test)]
+mod testing;
+
 #[derive(Debug)]
 pub struct FBXReader {
     object: Object,
@@ -19,7 +48,11 @@ impl FBXReader {
             }
         }
 
-        return None;
+        None
+    }
+
+    pub fn graph(&self) -> ObjectGraph {
+        ObjectGraph::from(&self.object)
     }
 }
 
@@ -34,7 +67,7 @@ impl FBXNodeReader {
     }
 
     pub fn get_nodes(&self, name: &str) -> Vec<FBXNodeReader> {
-        self.node.children().iter().map(|w| FBXNodeReader::from(w.clone())).collect()
+        self.node.children_slice().iter().filter(|w| w.name_str() == name).map(|w| FBXNodeReader::from(w.clone())).collect()
     }
 
     pub fn get_node(&self, name: &str) -> Option<FBXNodeReader> {
@@ -53,10 +86,29 @@ impl FBXNodeReader {
 
     pub fn get_value(&self) -> Option<Type> {
         let values = self.get_values();
-        if values.iter().count() != 1 {
+        if values.len() != 1 {
             return None;
         }
 
-        Some(values.get(0).unwrap().clone())
+        values.first().cloned()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn gets_only_the_children_with_the_name() {
+        let reader = FBXNodeReader::from(node("Takes", vec![], vec![
+            node("Current", vec![string("")], vec![]),
+            node("Take", vec![string("Walk")], vec![]),
+            node("Take", vec![string("Run")], vec![]),
+        ]));
+
+        let takes: Vec<Option<String>> = reader.get_nodes("Take").iter().map(|w| w.get_value().and_then(|w| w.as_str())).collect();
+        assert_eq!(takes, vec![Some("Walk".to_owned()), Some("Run".to_owned())]);
+        assert!(reader.get_nodes("Missing").is_empty());
+    }
+}
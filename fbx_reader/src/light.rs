@@ -0,0 +1,239 @@
+use crate::error::SceneError;
+use crate::graph::{is_class, ObjectGraph, ObjectId};
+use crate::math::Vector3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightType {
+    Point,
+    Directional,
+    Spot,
+    Area,
+    Volume,
+}
+
+impl LightType {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            1 => LightType::Directional,
+            2 => LightType::Spot,
+            3 => LightType::Area,
+            4 => LightType::Volume,
+            _ => LightType::Point,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecayType {
+    None,
+    Linear,
+    Quadratic,
+    Cubic,
+}
+
+impl DecayType {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            1 => DecayType::Linear,
+            2 => DecayType::Quadratic,
+            3 => DecayType::Cubic,
+            _ => DecayType::None,
+        }
+    }
+
+    // exponent of the distance the intensity is divided by
+    pub fn exponent(self) -> u32 {
+        match self {
+            DecayType::None => 0,
+            DecayType::Linear => 1,
+            DecayType::Quadratic => 2,
+            DecayType::Cubic => 3,
+        }
+    }
+}
+
+/// A `NodeAttribute` of subclass `Light`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Light {
+    id: ObjectId,
+    name: String,
+    light_type: LightType,
+    color: Vector3,
+    intensity: f64,
+    inner_angle: f64,
+    outer_angle: f64,
+    decay_type: DecayType,
+    decay_start: f64,
+    cast_light: bool,
+    cast_shadows: bool,
+    shadow_color: Vector3,
+}
+
+impl Light {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Light, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if !is_class(graph, id, "NodeAttribute", "Light") {
+            return Err(SceneError::UnexpectedObject(id, "NodeAttribute::Light".to_owned()));
+        }
+
+        // defaults are the SDK's, for files written without a light template
+        let properties = graph.properties(id).unwrap_or_default();
+
+        Ok(Light {
+            id,
+            name: object.name().to_owned(),
+            light_type: LightType::from_fbx(properties.get_i64("LightType").unwrap_or_default()),
+            color: properties.get_vector3("Color").unwrap_or([1.0; 3]),
+            intensity: properties.get_f64("Intensity").unwrap_or(100.0),
+            inner_angle: properties.get_f64("InnerAngle").or_else(|| properties.get_f64("HotSpot")).unwrap_or(0.0),
+            outer_angle: properties.get_f64("OuterAngle").or_else(|| properties.get_f64("Cone angle")).unwrap_or(45.0),
+            decay_type: DecayType::from_fbx(properties.get_i64("DecayType").unwrap_or_default()),
+            decay_start: properties.get_f64("DecayStart").unwrap_or_default(),
+            cast_light: properties.get_bool("CastLight").unwrap_or(true),
+            cast_shadows: properties.get_bool("CastShadows").unwrap_or(false),
+            shadow_color: properties.get_vector3("ShadowColor").unwrap_or([0.0; 3]),
+        })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn light_type(&self) -> LightType {
+        self.light_type
+    }
+
+    pub fn color(&self) -> Vector3 {
+        self.color
+    }
+
+    // in percent, 100 is the nominal brightness
+    pub fn intensity(&self) -> f64 {
+        self.intensity
+    }
+
+    // full cone angles in degrees
+    pub fn inner_angle(&self) -> f64 {
+        self.inner_angle
+    }
+
+    pub fn outer_angle(&self) -> f64 {
+        self.outer_angle
+    }
+
+    pub fn decay_type(&self) -> DecayType {
+        self.decay_type
+    }
+
+    // distance at which the decay starts, in scene units
+    pub fn decay_start(&self) -> f64 {
+        self.decay_start
+    }
+
+    pub fn cast_light(&self) -> bool {
+        self.cast_light
+    }
+
+    pub fn cast_shadows(&self) -> bool {
+        self.cast_shadows
+    }
+
+    pub fn shadow_color(&self) -> Vector3 {
+        self.shadow_color
+    }
+
+    // intensity as a multiplier of the color, zero for lights that do not cast light
+    pub fn intensity_factor(&self) -> f64 {
+        if self.cast_light { self.intensity / 100.0 } else { 0.0 }
+    }
+
+    // color multiplied by the intensity factor
+    pub fn radiance(&self) -> Vector3 {
+        let factor = self.intensity_factor();
+        [self.color[0] * factor, self.color[1] * factor, self.color[2] * factor]
+    }
+
+    // spot cone half angles from the axis in radians, as engines and glTF expect; the inner angle never exceeds the outer
+    pub fn cone_angles(&self) -> (f64, f64) {
+        let outer = (self.outer_angle / 2.0).to_radians();
+        let inner = (self.inner_angle / 2.0).to_radians().min(outer);
+        (inner, outer)
+    }
+
+    // attenuation factor at `distance` in scene units, 1 up to the decay start; the falloff only begins one
+    // scene unit past the decay start so the factor never exceeds 1, which ties its shape to the file's unit scale
+    pub fn attenuation(&self, distance: f64) -> f64 {
+        let distance = distance - self.decay_start;
+
+        if self.light_type == LightType::Directional || distance <= 1.0 {
+            return 1.0;
+        }
+
+        1.0 / distance.powi(self.decay_type.exponent() as i32)
+    }
+}
+
+// the light settings linked to a model, if it is a light
+pub fn model_light(graph: &ObjectGraph, model: ObjectId) -> Result<Option<Light>, SceneError> {
+    match graph.children(model).into_iter().find(|w| is_class(graph, *w, "NodeAttribute", "Light")) {
+        Some(light) => Ok(Some(Light::from_graph(graph, light)?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use fbx::format::Node;
+
+    fn light(properties: Vec<Node>) -> Light {
+        let graph = graph(vec![object("NodeAttribute", 1, "Light", "Light", vec![properties70(properties)])], vec![]);
+        Light::from_graph(&graph, 1).unwrap()
+    }
+
+    #[test]
+    fn halves_cone_angles_and_clamps_the_inner_one() {
+        let spot = light(vec![int_property("LightType", "enum", 2), number_property("InnerAngle", 20.0), number_property("OuterAngle", 60.0)]);
+        let (inner, outer) = spot.cone_angles();
+        assert_close(inner, 10f64.to_radians(), 1e-12);
+        assert_close(outer, 30f64.to_radians(), 1e-12);
+
+        // 6.1 files name the angles `HotSpot` and `Cone angle`
+        let wide = light(vec![number_property("HotSpot", 80.0), number_property("Cone angle", 60.0)]);
+        let (inner, outer) = wide.cone_angles();
+        assert_close(inner, 30f64.to_radians(), 1e-12);
+        assert_close(outer, 30f64.to_radians(), 1e-12);
+    }
+
+    #[test]
+    fn scales_the_color_by_the_intensity() {
+        let bright = light(vec![vector_property("Color", "Color", [1.0, 0.5, 0.25]), number_property("Intensity", 200.0)]);
+        assert_eq!(bright.radiance(), [2.0, 1.0, 0.5]);
+
+        let dark = light(vec![vector_property("Color", "Color", [1.0, 0.5, 0.25]), int_property("CastLight", "bool", 0)]);
+        assert_eq!(dark.intensity_factor(), 0.0);
+        assert_eq!(dark.radiance(), [0.0; 3]);
+
+        assert_eq!(light(vec![]).radiance(), [1.0; 3]);
+    }
+
+    #[test]
+    fn attenuates_past_the_decay_start() {
+        let point = light(vec![int_property("DecayType", "enum", 2), number_property("DecayStart", 2.0)]);
+        assert_eq!(point.attenuation(1.0), 1.0);
+        assert_eq!(point.attenuation(6.0), 1.0 / 16.0);
+        // within one scene unit of the decay start the factor is clamped rather than brightened
+        assert_eq!(point.attenuation(2.5), 1.0);
+        assert_eq!(point.attenuation(3.0), 1.0);
+
+        let directional = light(vec![int_property("LightType", "enum", 1), int_property("DecayType", "enum", 2)]);
+        assert_eq!(directional.attenuation(100.0), 1.0);
+        assert_eq!(light(vec![]).attenuation(100.0), 1.0);
+    }
+}
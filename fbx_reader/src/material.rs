@@ -0,0 +1,446 @@
+use fbx::format::Node;
+
+use crate::error::SceneError;
+use crate::graph::{ConnectionKind, ObjectGraph, ObjectId};
+use crate::math::Vector3;
+use crate::mesh::child;
+use crate::properties::Properties;
+
+fn child_str(node: &Node, name: &str) -> Option<String> {
+    child(node, name)?.attributes_slice().first()?.as_str()
+}
+
+fn object_of_class(graph: &ObjectGraph, id: ObjectId, class: &str) -> Result<(Node, Properties), SceneError> {
+    let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+    if object.class() != class {
+        return Err(SceneError::UnexpectedObject(id, class.to_owned()));
+    }
+
+    Ok((object.node().clone(), graph.properties(id).unwrap_or_default()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    Clamp,
+}
+
+impl WrapMode {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            1 => WrapMode::Clamp,
+            _ => WrapMode::Repeat,
+        }
+    }
+}
+
+/// A `Video` object, the image a texture samples.
+#[derive(Debug, Clone)]
+pub struct Video {
+    id: ObjectId,
+    name: String,
+    file_name: String,
+    relative_file_name: String,
+    content: Option<Vec<u8>>,
+}
+
+impl Video {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Video, SceneError> {
+        let (node, properties) = object_of_class(graph, id, "Video")?;
+        let name = graph.object(id).map(|w| w.name().to_owned()).unwrap_or_default();
+
+        let file_name = child_str(&node, "Filename").or_else(|| child_str(&node, "FileName")).or_else(|| properties.get_str("Path").map(|w| w.to_owned()));
+        let content = child(&node, "Content").and_then(|w| w.attributes_slice().first()).and_then(|w| w.as_binary()).filter(|w| !w.is_empty());
+
+        Ok(Video {
+            id,
+            name,
+            file_name: file_name.unwrap_or_default(),
+            relative_file_name: child_str(&node, "RelativeFilename").unwrap_or_default(),
+            content,
+        })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn relative_file_name(&self) -> &str {
+        &self.relative_file_name
+    }
+
+    // image bytes embedded in `Content`, if any
+    pub fn content(&self) -> Option<&[u8]> {
+        self.content.as_deref()
+    }
+}
+
+/// A `Texture` object with its UV transform and the video it reads from.
+#[derive(Debug, Clone)]
+pub struct Texture {
+    id: ObjectId,
+    name: String,
+    file_name: String,
+    relative_file_name: String,
+    uv_set: String,
+    wrap_u: WrapMode,
+    wrap_v: WrapMode,
+    translation: Vector3,
+    rotation: Vector3,
+    scaling: Vector3,
+    video: Option<Video>,
+}
+
+impl Texture {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Texture, SceneError> {
+        let (node, properties) = object_of_class(graph, id, "Texture")?;
+        let name = graph.object(id).map(|w| w.name().to_owned()).unwrap_or_default();
+
+        let video = match graph.children(id).into_iter().find(|w| graph.object(*w).map(|o| o.class() == "Video").unwrap_or(false)) {
+            Some(video) => Some(Video::from_graph(graph, video)?),
+            None => None,
+        };
+
+        Ok(Texture {
+            id,
+            name,
+            file_name: child_str(&node, "FileName").unwrap_or_default(),
+            relative_file_name: child_str(&node, "RelativeFilename").unwrap_or_default(),
+            uv_set: properties.get_str("UVSet").unwrap_or("default").to_owned(),
+            wrap_u: WrapMode::from_fbx(properties.get_i64("WrapModeU").unwrap_or_default()),
+            wrap_v: WrapMode::from_fbx(properties.get_i64("WrapModeV").unwrap_or_default()),
+            translation: properties.get_vector3("Translation").unwrap_or_default(),
+            rotation: properties.get_vector3("Rotation").unwrap_or_default(),
+            scaling: properties.get_vector3("Scaling").unwrap_or([1.0; 3]),
+            video,
+        })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // usually an absolute path on the machine that wrote the file
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    // path relative to the FBX file
+    pub fn relative_file_name(&self) -> &str {
+        &self.relative_file_name
+    }
+
+    // name of the UV set to sample with, `default` for the mesh's first one
+    pub fn uv_set(&self) -> &str {
+        &self.uv_set
+    }
+
+    pub fn wrap_u(&self) -> WrapMode {
+        self.wrap_u
+    }
+
+    pub fn wrap_v(&self) -> WrapMode {
+        self.wrap_v
+    }
+
+    pub fn translation(&self) -> Vector3 {
+        self.translation
+    }
+
+    pub fn rotation(&self) -> Vector3 {
+        self.rotation
+    }
+
+    pub fn scaling(&self) -> Vector3 {
+        self.scaling
+    }
+
+    pub fn video(&self) -> Option<&Video> {
+        self.video.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShadingModel {
+    Lambert,
+    Phong,
+    Unknown(String),
+}
+
+impl ShadingModel {
+    pub fn parse(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "lambert" => ShadingModel::Lambert,
+            "phong" => ShadingModel::Phong,
+            _ => ShadingModel::Unknown(value.to_owned()),
+        }
+    }
+}
+
+/// A texture connected to a material property, e.g. `DiffuseColor` or `NormalMap`.
+#[derive(Debug, Clone)]
+pub struct MaterialTexture {
+    pub property: String,
+    pub texture: Texture,
+}
+
+/// A `Material` object reduced to the values renderers commonly use.
+#[derive(Debug, Clone)]
+pub struct Material {
+    id: ObjectId,
+    name: String,
+    shading_model: ShadingModel,
+    diffuse: Vector3,
+    specular: Vector3,
+    emissive: Vector3,
+    ambient: Vector3,
+    shininess: f64,
+    bump_factor: f64,
+    opacity: f64,
+    textures: Vec<MaterialTexture>,
+    properties: Properties,
+}
+
+impl Material {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Material, SceneError> {
+        let (node, properties) = object_of_class(graph, id, "Material")?;
+        let name = graph.object(id).map(|w| w.name().to_owned()).unwrap_or_default();
+
+        let shading_model = child_str(&node, "ShadingModel").or_else(|| properties.get_str("ShadingModel").map(|w| w.to_owned())).unwrap_or_default();
+
+        // colors are scaled by their factor, older files only write the legacy `Diffuse`-style names
+        let color = |name: &str, legacy: &str, default: Vector3| {
+            let color = properties.get_vector3(name).or_else(|| properties.get_vector3(legacy)).unwrap_or(default);
+            let factor = properties.get_f64(&format!("{}Factor", name.trim_end_matches("Color"))).unwrap_or(1.0);
+            [color[0] * factor, color[1] * factor, color[2] * factor]
+        };
+
+        // `Opacity` is only written by some exporters, otherwise it follows from the transparency
+        let opacity = match properties.get_f64("Opacity") {
+            Some(opacity) => opacity,
+            None => {
+                let color = properties.get_vector3("TransparentColor").unwrap_or([1.0; 3]);
+                let factor = properties.get_f64("TransparencyFactor").unwrap_or_default();
+                1.0 - factor * (color[0] + color[1] + color[2]) / 3.0
+            }
+        };
+
+        let mut textures = Vec::new();
+        for connection in graph.property_connections(id) {
+            let is_texture = graph.object(connection.child()).map(|w| w.class() == "Texture").unwrap_or(false);
+
+            if connection.kind() == ConnectionKind::ObjectProperty && is_texture {
+                let property = connection.parent_property().unwrap_or_default().to_owned();
+                textures.push(MaterialTexture { property, texture: Texture::from_graph(graph, connection.child())? });
+            }
+        }
+
+        Ok(Material {
+            id,
+            name,
+            shading_model: ShadingModel::parse(&shading_model),
+            diffuse: color("DiffuseColor", "Diffuse", [0.8; 3]),
+            specular: color("SpecularColor", "Specular", [0.2; 3]),
+            emissive: color("EmissiveColor", "Emissive", [0.0; 3]),
+            ambient: color("AmbientColor", "Ambient", [0.0; 3]),
+            shininess: properties.get_f64("ShininessExponent").or_else(|| properties.get_f64("Shininess")).unwrap_or(20.0),
+            bump_factor: properties.get_f64("BumpFactor").unwrap_or(1.0),
+            opacity: opacity.clamp(0.0, 1.0),
+            textures,
+            properties,
+        })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn shading_model(&self) -> &ShadingModel {
+        &self.shading_model
+    }
+
+    // diffuse color multiplied by `DiffuseFactor`
+    pub fn diffuse(&self) -> Vector3 {
+        self.diffuse
+    }
+
+    pub fn specular(&self) -> Vector3 {
+        self.specular
+    }
+
+    pub fn emissive(&self) -> Vector3 {
+        self.emissive
+    }
+
+    pub fn ambient(&self) -> Vector3 {
+        self.ambient
+    }
+
+    pub fn shininess(&self) -> f64 {
+        self.shininess
+    }
+
+    pub fn bump_factor(&self) -> f64 {
+        self.bump_factor
+    }
+
+    // 1 is fully opaque
+    pub fn opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    pub fn textures(&self) -> &[MaterialTexture] {
+        &self.textures
+    }
+
+    // first texture connected to `property`, e.g. `DiffuseColor`, `NormalMap` or `Bump`
+    pub fn texture(&self, property: &str) -> Option<&Texture> {
+        self.textures.iter().find(|w| w.property == property).map(|w| &w.texture)
+    }
+
+    pub fn diffuse_texture(&self) -> Option<&Texture> {
+        self.texture("DiffuseColor")
+    }
+
+    pub fn normal_texture(&self) -> Option<&Texture> {
+        self.texture("NormalMap")
+    }
+
+    pub fn bump_texture(&self) -> Option<&Texture> {
+        self.texture("Bump")
+    }
+
+    // every effective property, for values not covered above
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+}
+
+// materials of a model in connection order, which is the order material layer indices refer to
+pub fn model_materials(graph: &ObjectGraph, model: ObjectId) -> Result<Vec<Material>, SceneError> {
+    let mut materials = Vec::new();
+
+    for child in graph.children(model) {
+        if graph.object(child).map(|w| w.class() == "Material").unwrap_or(false) {
+            materials.push(Material::from_graph(graph, child)?);
+        }
+    }
+
+    Ok(materials)
+}
+
+// materials of the first model a geometry is attached to
+pub fn geometry_materials(graph: &ObjectGraph, geometry: ObjectId) -> Result<Vec<Material>, SceneError> {
+    let model = graph.parents(geometry).into_iter().find(|w| graph.object(*w).map(|o| o.class() == "Model").unwrap_or(false));
+
+    match model {
+        Some(model) => model_materials(graph, model),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn material(id: ObjectId, properties: Vec<Node>) -> Node {
+        object("Material", id, "", "", vec![text_node("ShadingModel", "phong"), properties70(properties)])
+    }
+
+    fn opacity(properties: Vec<Node>) -> f64 {
+        Material::from_graph(&graph(vec![material(1, properties)], vec![]), 1).unwrap().opacity()
+    }
+
+    #[test]
+    fn resolves_textures_connected_to_properties() {
+        let diffuse = object("Texture", 2, "wood", "", vec![
+            text_node("FileName", "C:/textures/wood.png"),
+            text_node("RelativeFilename", "textures/wood.png"),
+            properties70(vec![
+                property("UVSet", "KString", vec![string("detail")]),
+                int_property("WrapModeU", "enum", 1),
+                vector_property("Translation", "Vector", [0.5, 0.25, 0.0]),
+                vector_property("Scaling", "Vector", [2.0, 2.0, 1.0]),
+            ]),
+        ]);
+        let objects = vec![
+            object("Model", 1, "table", "Mesh", vec![]),
+            object("Geometry", 20, "table", "Mesh", vec![]),
+            material(10, vec![vector_property("DiffuseColor", "Color", [1.0, 0.5, 0.0]), number_property("DiffuseFactor", 0.5)]),
+            diffuse,
+            object("Texture", 3, "bumps", "", vec![]),
+            object("Texture", 4, "unused", "", vec![]),
+            object("Video", 5, "wood", "Clip", vec![text_node("Filename", "C:/textures/wood.png")]),
+        ];
+        let connections = vec![oo(20, 1), oo(10, 1), op(2, 10, "DiffuseColor"), op(3, 10, "NormalMap"), oo(4, 10), oo(5, 2)];
+        let graph = graph(objects, connections);
+
+        let materials = geometry_materials(&graph, 20).unwrap();
+        assert_eq!(materials.len(), 1);
+        assert_eq!(model_materials(&graph, 1).unwrap()[0].id(), 10);
+
+        let material = &materials[0];
+        assert_eq!(material.shading_model(), &ShadingModel::Phong);
+        assert_all_close(&material.diffuse(), &[0.5, 0.25, 0.0], 1e-12);
+
+        // only object-property connections to a named property are texture slots
+        assert_eq!(material.textures().iter().map(|w| w.property.as_str()).collect::<Vec<_>>(), ["DiffuseColor", "NormalMap"]);
+        assert_eq!(material.normal_texture().unwrap().name(), "bumps");
+        assert!(material.bump_texture().is_none());
+
+        let texture = material.diffuse_texture().unwrap();
+        assert_eq!((texture.file_name(), texture.relative_file_name()), ("C:/textures/wood.png", "textures/wood.png"));
+        assert_eq!(texture.uv_set(), "detail");
+        assert_eq!((texture.wrap_u(), texture.wrap_v()), (WrapMode::Clamp, WrapMode::Repeat));
+        assert_eq!(texture.translation(), [0.5, 0.25, 0.0]);
+        assert_eq!(texture.rotation(), [0.0; 3]);
+        assert_eq!(texture.scaling(), [2.0, 2.0, 1.0]);
+        assert_eq!(texture.video().unwrap().file_name(), "C:/textures/wood.png");
+
+        let defaults = material.normal_texture().unwrap();
+        assert_eq!((defaults.uv_set(), defaults.wrap_u(), defaults.scaling()), ("default", WrapMode::Repeat, [1.0; 3]));
+        assert!(defaults.video().is_none());
+    }
+
+    #[test]
+    fn falls_back_to_legacy_colors_and_defaults() {
+        let graph = graph(vec![material(1, vec![vector_property("Diffuse", "Vector", [0.1, 0.2, 0.3]), number_property("Shininess", 8.0)])], vec![]);
+        let material = Material::from_graph(&graph, 1).unwrap();
+
+        assert_eq!(material.diffuse(), [0.1, 0.2, 0.3]);
+        assert_eq!(material.specular(), [0.2; 3]);
+        assert_eq!(material.emissive(), [0.0; 3]);
+        assert_eq!(material.shininess(), 8.0);
+        assert_eq!(material.opacity(), 1.0);
+
+        assert!(matches!(Material::from_graph(&graph, 2), Err(SceneError::MissingObject(2))));
+    }
+
+    #[test]
+    fn derives_opacity_from_transparency() {
+        assert_close(opacity(vec![number_property("TransparencyFactor", 0.25)]), 0.75, 1e-12);
+        assert_close(opacity(vec![vector_property("TransparentColor", "Color", [1.0, 0.0, 0.5]), number_property("TransparencyFactor", 1.0)]), 0.5, 1e-12);
+
+        // an explicit `Opacity` wins and the result is clamped
+        assert_close(opacity(vec![number_property("Opacity", 0.3), number_property("TransparencyFactor", 1.0)]), 0.3, 1e-12);
+        assert_close(opacity(vec![number_property("TransparencyFactor", 2.0)]), 0.0, 1e-12);
+    }
+}
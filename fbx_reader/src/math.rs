@@ -0,0 +1,300 @@
+use std::ops::Mul;
+
+pub type Vector3 = [f64; 3];
+
+pub fn add(a: Vector3, b: Vector3) -> Vector3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub fn sub(a: Vector3, b: Vector3) -> Vector3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn scale(a: Vector3, s: f64) -> Vector3 {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+pub fn dot(a: Vector3, b: Vector3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: Vector3, b: Vector3) -> Vector3 {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+pub fn length(a: Vector3) -> f64 {
+    dot(a, a).sqrt()
+}
+
+pub fn normalize(a: Vector3) -> Vector3 {
+    let l = length(a);
+    if l > 0.0 { scale(a, 1.0 / l) } else { a }
+}
+
+/// Euler rotation order as stored in the `RotationOrder` enum property; `Xyz` rotates around X first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationOrder {
+    Xyz,
+    Xzy,
+    Yzx,
+    Yxz,
+    Zxy,
+    Zyx,
+    SphericXyz,
+}
+
+impl RotationOrder {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            1 => RotationOrder::Xzy,
+            2 => RotationOrder::Yzx,
+            3 => RotationOrder::Yxz,
+            4 => RotationOrder::Zxy,
+            5 => RotationOrder::Zyx,
+            6 => RotationOrder::SphericXyz,
+            _ => RotationOrder::Xyz,
+        }
+    }
+
+    pub fn to_fbx(self) -> i32 {
+        match self {
+            RotationOrder::Xyz => 0,
+            RotationOrder::Xzy => 1,
+            RotationOrder::Yzx => 2,
+            RotationOrder::Yxz => 3,
+            RotationOrder::Zxy => 4,
+            RotationOrder::Zyx => 5,
+            RotationOrder::SphericXyz => 6,
+        }
+    }
+
+    // axis indices in the order they are applied
+    pub fn axes(self) -> [usize; 3] {
+        match self {
+            RotationOrder::Xyz | RotationOrder::SphericXyz => [0, 1, 2],
+            RotationOrder::Xzy => [0, 2, 1],
+            RotationOrder::Yzx => [1, 2, 0],
+            RotationOrder::Yxz => [1, 0, 2],
+            RotationOrder::Zxy => [2, 0, 1],
+            RotationOrder::Zyx => [2, 1, 0],
+        }
+    }
+
+    pub fn from_axes(axes: [usize; 3]) -> Option<Self> {
+        match axes {
+            [0, 1, 2] => Some(RotationOrder::Xyz),
+            [0, 2, 1] => Some(RotationOrder::Xzy),
+            [1, 2, 0] => Some(RotationOrder::Yzx),
+            [1, 0, 2] => Some(RotationOrder::Yxz),
+            [2, 0, 1] => Some(RotationOrder::Zxy),
+            [2, 1, 0] => Some(RotationOrder::Zyx),
+            _ => None,
+        }
+    }
+}
+
+/// 4x4 matrix stored column-major, the same layout FBX uses for its 16 double arrays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix4 {
+    m: [f64; 16],
+}
+
+impl Matrix4 {
+    pub const IDENTITY: Matrix4 = Matrix4 { m: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0] };
+
+    pub fn from_array(m: [f64; 16]) -> Self {
+        Matrix4 { m }
+    }
+
+    pub fn from_slice(values: &[f64]) -> Option<Self> {
+        Some(Matrix4 { m: values.get(..16)?.try_into().ok()? })
+    }
+
+    pub fn as_array(&self) -> &[f64; 16] {
+        &self.m
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> f64 {
+        self.m[column * 4 + row]
+    }
+
+    pub fn set(&mut self, row: usize, column: usize, value: f64) {
+        self.m[column * 4 + row] = value;
+    }
+
+    pub fn translation(v: Vector3) -> Self {
+        let mut m = Matrix4::IDENTITY;
+        m.set(0, 3, v[0]);
+        m.set(1, 3, v[1]);
+        m.set(2, 3, v[2]);
+        m
+    }
+
+    pub fn scaling(v: Vector3) -> Self {
+        let mut m = Matrix4::IDENTITY;
+        m.set(0, 0, v[0]);
+        m.set(1, 1, v[1]);
+        m.set(2, 2, v[2]);
+        m
+    }
+
+    // rotation around a single axis (0 = X, 1 = Y, 2 = Z), angle in degrees
+    pub fn rotation_axis(axis: usize, degrees: f64) -> Self {
+        let (s, c) = degrees.to_radians().sin_cos();
+        let (i, j) = ((axis + 1) % 3, (axis + 2) % 3);
+        let mut m = Matrix4::IDENTITY;
+        m.set(i, i, c);
+        m.set(i, j, -s);
+        m.set(j, i, s);
+        m.set(j, j, c);
+        m
+    }
+
+    // euler angles in degrees, applied in the given order
+    pub fn from_euler(degrees: Vector3, order: RotationOrder) -> Self {
+        let [a, b, c] = order.axes();
+        Matrix4::rotation_axis(c, degrees[c]) * Matrix4::rotation_axis(b, degrees[b]) * Matrix4::rotation_axis(a, degrees[a])
+    }
+
+    // decomposes the rotation part (which must be orthonormal) into euler angles in degrees
+    pub fn to_euler(&self, order: RotationOrder) -> Vector3 {
+        let [i, j, k] = order.axes();
+        let parity = if (j + 3 - i) % 3 == 1 { 1.0 } else { -1.0 };
+        let r = |row: usize, column: usize| self.get(row, column);
+        let mut angles = [0.0; 3];
+
+        let sin_b = (-parity * r(k, i)).clamp(-1.0, 1.0);
+        angles[j] = sin_b.asin();
+
+        if sin_b.abs() < 1.0 - 1e-12 {
+            angles[i] = (parity * r(k, j)).atan2(r(k, k));
+            angles[k] = (parity * r(j, i)).atan2(r(i, i));
+        } else {
+            // gimbal lock, the first and last rotations share an axis
+            angles[i] = (-parity * r(j, k)).atan2(r(j, j));
+            angles[k] = 0.0;
+        }
+
+        [angles[0].to_degrees(), angles[1].to_degrees(), angles[2].to_degrees()]
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = Matrix4::IDENTITY;
+        for row in 0..4 {
+            for column in 0..4 {
+                m.set(row, column, self.get(column, row));
+            }
+        }
+        m
+    }
+
+    pub fn determinant(&self) -> f64 {
+        let cofactors = self.cofactors();
+        (0..4).map(|column| self.get(0, column) * cofactors[column * 4]).sum()
+    }
+
+    // cofactor matrix, column-major
+    fn cofactors(&self) -> [f64; 16] {
+        let mut cofactors = [0.0; 16];
+
+        for row in 0..4 {
+            for column in 0..4 {
+                let mut minor = [0.0; 9];
+                let mut n = 0;
+
+                for r in (0..4).filter(|r| *r != row) {
+                    for c in (0..4).filter(|c| *c != column) {
+                        minor[n] = self.get(r, c);
+                        n += 1;
+                    }
+                }
+
+                let det = minor[0] * (minor[4] * minor[8] - minor[5] * minor[7]) - minor[1] * (minor[3] * minor[8] - minor[5] * minor[6])
+                    + minor[2] * (minor[3] * minor[7] - minor[4] * minor[6]);
+                let sign = if (row + column) % 2 == 0 { 1.0 } else { -1.0 };
+                cofactors[column * 4 + row] = sign * det;
+            }
+        }
+
+        cofactors
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        let cofactors = self.cofactors();
+        let det: f64 = (0..4).map(|column| self.get(0, column) * cofactors[column * 4]).sum();
+
+        if det.abs() < 1e-300 {
+            return None;
+        }
+
+        // the inverse is the transposed cofactor matrix divided by the determinant
+        let mut m = Matrix4::IDENTITY;
+        for row in 0..4 {
+            for column in 0..4 {
+                m.set(row, column, cofactors[row * 4 + column] / det);
+            }
+        }
+
+        Some(m)
+    }
+
+    pub fn transform_point(&self, v: Vector3) -> Vector3 {
+        let mut result = [0.0; 3];
+        for (row, slot) in result.iter_mut().enumerate() {
+            *slot = self.get(row, 0) * v[0] + self.get(row, 1) * v[1] + self.get(row, 2) * v[2] + self.get(row, 3);
+        }
+        result
+    }
+
+    pub fn transform_vector(&self, v: Vector3) -> Vector3 {
+        let mut result = [0.0; 3];
+        for (row, slot) in result.iter_mut().enumerate() {
+            *slot = self.get(row, 0) * v[0] + self.get(row, 1) * v[1] + self.get(row, 2) * v[2];
+        }
+        result
+    }
+
+    pub fn get_translation(&self) -> Vector3 {
+        [self.get(0, 3), self.get(1, 3), self.get(2, 3)]
+    }
+
+    pub fn column(&self, column: usize) -> Vector3 {
+        [self.get(0, column), self.get(1, column), self.get(2, column)]
+    }
+
+    // splits an affine matrix without shear into translation, rotation and scaling
+    pub fn decompose(&self) -> (Vector3, Matrix4, Vector3) {
+        let mut scaling = [length(self.column(0)), length(self.column(1)), length(self.column(2))];
+
+        if self.determinant() < 0.0 {
+            scaling[0] = -scaling[0];
+        }
+
+        let mut rotation = Matrix4::IDENTITY;
+        for (column, s) in scaling.iter().enumerate() {
+            for row in 0..3 {
+                rotation.set(row, column, if *s != 0.0 { self.get(row, column) / s } else { 0.0 });
+            }
+        }
+
+        (self.get_translation(), rotation, scaling)
+    }
+
+    pub fn approx_eq(&self, other: &Matrix4, tolerance: f64) -> bool {
+        self.m.iter().zip(other.m.iter()).all(|(a, b)| (a - b).abs() <= tolerance)
+    }
+}
+
+impl Mul for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, rhs: Matrix4) -> Matrix4 {
+        let mut m = [0.0; 16];
+        for column in 0..4 {
+            for row in 0..4 {
+                m[column * 4 + row] = (0..4).map(|k| self.get(row, k) * rhs.get(k, column)).sum();
+            }
+        }
+        Matrix4 { m }
+    }
+}
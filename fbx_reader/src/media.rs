@@ -0,0 +1,371 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Component, Path, PathBuf};
+
+use fbx::format::{Node, Object, Type};
+
+use crate::error::SceneError;
+use crate::graph::ObjectId;
+use crate::mesh::child;
+use crate::paths::{TexturePaths, TextureResolver};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageType {
+    Png,
+    Jpeg,
+    Tga,
+    Bmp,
+    Gif,
+    Tiff,
+    Dds,
+    Psd,
+    Hdr,
+    Exr,
+    Unknown,
+}
+
+impl ImageType {
+    // detects the type from the magic bytes, TGA has none and is only recognized by its extension
+    pub fn detect(bytes: &[u8], file_name: &str) -> Self {
+        let image_type = match bytes {
+            [0x89, b'P', b'N', b'G', ..] => ImageType::Png,
+            [0xff, 0xd8, 0xff, ..] => ImageType::Jpeg,
+            [b'B', b'M', ..] => ImageType::Bmp,
+            [b'G', b'I', b'F', b'8', ..] => ImageType::Gif,
+            [b'I', b'I', 0x2a, 0x00, ..] | [b'M', b'M', 0x00, 0x2a, ..] => ImageType::Tiff,
+            [b'D', b'D', b'S', b' ', ..] => ImageType::Dds,
+            [b'8', b'B', b'P', b'S', ..] => ImageType::Psd,
+            [b'#', b'?', ..] => ImageType::Hdr,
+            [0x76, 0x2f, 0x31, 0x01, ..] => ImageType::Exr,
+            _ => ImageType::Unknown,
+        };
+
+        if image_type == ImageType::Unknown && file_name.to_ascii_lowercase().ends_with(".tga") {
+            return ImageType::Tga;
+        }
+
+        image_type
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageType::Png => "png",
+            ImageType::Jpeg => "jpg",
+            ImageType::Tga => "tga",
+            ImageType::Bmp => "bmp",
+            ImageType::Gif => "gif",
+            ImageType::Tiff => "tif",
+            ImageType::Dds => "dds",
+            ImageType::Psd => "psd",
+            ImageType::Hdr => "hdr",
+            ImageType::Exr => "exr",
+            ImageType::Unknown => "bin",
+        }
+    }
+}
+
+// last component of a path written on any platform
+pub fn file_name_of(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+// true for names that stay inside the directory they are joined to
+fn is_plain_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) && !name.contains([':', '\0'])
+}
+
+fn video_id(node: &Node) -> Option<ObjectId> {
+    node.attributes_slice().first().and_then(|w| w.as_int64())
+}
+
+fn child_str(node: &Node, name: &str) -> Option<String> {
+    child(node, name)?.attributes_slice().first()?.as_str()
+}
+
+fn content(node: &Node) -> Option<&[u8]> {
+    child(node, "Content")?.attributes_slice().first()?.as_binary_slice().filter(|w| !w.is_empty())
+}
+
+fn videos(object: &Object) -> impl Iterator<Item = &Node> {
+    object.children_slice().iter().filter(|w| w.name_str() == "Objects").flat_map(|w| w.children_slice()).filter(|w| w.name_str() == "Video")
+}
+
+fn videos_mut(object: &mut Object) -> impl Iterator<Item = &mut Node> {
+    object.children_mut().iter_mut().filter(|w| w.name_str() == "Objects").flat_map(|w| w.children_mut().iter_mut()).filter(|w| w.name_str() == "Video")
+}
+
+/// Image bytes stored in the `Content` of a `Video` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedMedia {
+    pub video: ObjectId,
+    pub file_name: String,
+    pub relative_file_name: String,
+    pub size: usize,
+    pub image_type: ImageType,
+}
+
+impl EmbeddedMedia {
+    // file name to extract to, taken from the stored paths or made up from the video ID when they end in no plain name
+    pub fn output_name(&self) -> String {
+        let name = [&self.relative_file_name, &self.file_name].into_iter().map(|w| file_name_of(w)).find(|w| is_plain_file_name(w));
+
+        match name {
+            Some(name) => name.to_owned(),
+            None => format!("video_{}.{}", self.video, self.image_type.extension()),
+        }
+    }
+}
+
+pub fn list_embedded_media(object: &Object) -> Vec<EmbeddedMedia> {
+    videos(object)
+        .filter_map(|node| {
+            let bytes = content(node)?;
+            let file_name = child_str(node, "Filename").or_else(|| child_str(node, "FileName")).unwrap_or_default();
+            let image_type = ImageType::detect(bytes, &file_name);
+
+            Some(EmbeddedMedia {
+                video: video_id(node)?,
+                relative_file_name: child_str(node, "RelativeFilename").unwrap_or_default(),
+                file_name,
+                size: bytes.len(),
+                image_type,
+            })
+        })
+        .collect()
+}
+
+// writes every embedded image into `directory`, names used twice are prefixed with the video ID, and a counter if still taken
+pub fn extract_embedded_media(object: &Object, directory: &Path) -> Result<Vec<(EmbeddedMedia, PathBuf)>, SceneError> {
+    std::fs::create_dir_all(directory)?;
+
+    let contents: HashMap<ObjectId, &[u8]> = videos(object).filter_map(|w| Some((video_id(w)?, content(w)?))).collect();
+    let mut used: HashSet<String> = HashSet::new();
+    let mut extracted = Vec::new();
+
+    for media in list_embedded_media(object) {
+        let output_name = media.output_name();
+        let mut name = output_name.clone();
+        let mut attempt = 0;
+
+        while !used.insert(name.clone()) {
+            attempt += 1;
+            name = match attempt {
+                1 => format!("{}_{}", media.video, output_name),
+                _ => format!("{}_{}_{}", media.video, attempt, output_name),
+            };
+        }
+
+        let path = directory.join(name);
+        std::fs::write(&path, contents[&media.video])?;
+        extracted.push((media, path));
+    }
+
+    Ok(extracted)
+}
+
+/// Outcome of `embed_media`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmbedReport {
+    // videos that got their file embedded, with the file that was read
+    pub embedded: Vec<(ObjectId, PathBuf)>,
+    // videos whose file could not be found, with the path stored in the scene
+    pub missing: Vec<(ObjectId, String)>,
+    // videos removed because another one holds the same bytes, with the video kept instead
+    pub deduplicated: Vec<(ObjectId, ObjectId)>,
+}
+
+fn set_content(node: &mut Node, bytes: Vec<u8>) {
+    let position = node.children_slice().iter().position(|w| w.name_str() == "Content");
+
+    match position {
+        Some(position) => *node.children_mut()[position].attributes_mut() = vec![Type::VecRaw(bytes)],
+        None => node.children_mut().push(Node::new("Content".to_owned(), vec![Type::VecRaw(bytes)], vec![])),
+    }
+}
+
+fn connection_key(connection: &Node) -> String {
+    connection.attributes_slice().iter().map(|w| w.to_string()).collect::<Vec<_>>().join(",")
+}
+
+// makes connections to `from` point to `to`, dropping redirected ones that duplicate another connection wherever it is
+fn redirect_connections(object: &mut Object, from: ObjectId, to: ObjectId) {
+    for connections in object.children_mut().iter_mut().filter(|w| w.name_str() == "Connections") {
+        let mut redirected: Vec<bool> = Vec::new();
+
+        for connection in connections.children_mut().iter_mut() {
+            let mut changed = false;
+
+            for attribute in connection.attributes_mut().iter_mut() {
+                if let Type::Int64(id) = attribute {
+                    if *id == from {
+                        *id = to;
+                        changed = true;
+                    }
+                }
+            }
+
+            redirected.push(changed);
+        }
+
+        // connections that were not redirected are all kept, so they are seen before any redirected one
+        let mut seen: HashSet<String> = connections.children_slice().iter().zip(redirected.iter()).filter(|w| !*w.1).map(|w| connection_key(w.0)).collect();
+        let mut redirected = redirected.into_iter();
+
+        connections.children_mut().retain(|connection| !redirected.next().unwrap_or(false) || seen.insert(connection_key(connection)));
+    }
+}
+
+// keeps `Definitions/ObjectType: "Video"/Count` in line with the remaining videos
+fn update_video_count(object: &mut Object) {
+    let count = videos(object).count() as i32;
+    let definitions = object.children_mut().iter_mut().filter(|w| w.name_str() == "Definitions").flat_map(|w| w.children_mut().iter_mut());
+
+    for object_type in definitions.filter(|w| w.name_str() == "ObjectType" && w.attributes_slice().first().and_then(|a| a.as_str_ref()) == Some("Video")) {
+        if let Some(node) = object_type.children_mut().iter_mut().find(|w| w.name_str() == "Count") {
+            *node.attributes_mut() = vec![Type::Int32(count)];
+        }
+    }
+}
+
+// embeds the files of videos without content, resolved from `base_directory`, then
+// removes videos whose bytes match an earlier one and points their textures at the one kept
+pub fn embed_media(object: &mut Object, base_directory: &Path) -> Result<EmbedReport, SceneError> {
+    let mut report = EmbedReport::default();
+    let resolver = TextureResolver::new(base_directory, &[]);
+
+    for node in videos_mut(object) {
+        let id = match video_id(node) {
+            Some(id) => id,
+            None => continue,
+        };
+
+        if content(node).is_some() {
+            continue;
+        }
+
+        match resolver.resolve(&TexturePaths::read(node)) {
+            Some((path, _)) => {
+                set_content(node, std::fs::read(&path)?);
+                report.embedded.push((id, path));
+            }
+            None => {
+                let stored = child_str(node, "RelativeFilename").filter(|w| !w.is_empty()).or_else(|| child_str(node, "Filename")).unwrap_or_default();
+                report.missing.push((id, stored));
+            }
+        }
+    }
+
+    // bytes are only compared within the same hash bucket
+    let mut buckets: HashMap<u64, Vec<(ObjectId, &[u8])>> = HashMap::new();
+    for node in videos(object) {
+        if let (Some(id), Some(bytes)) = (video_id(node), content(node)) {
+            let mut hasher = DefaultHasher::new();
+            bytes.hash(&mut hasher);
+
+            let bucket = buckets.entry(hasher.finish()).or_default();
+            match bucket.iter().find(|(_, kept)| *kept == bytes) {
+                Some((kept, _)) => report.deduplicated.push((id, *kept)),
+                None => bucket.push((id, bytes)),
+            }
+        }
+    }
+
+    for (removed, kept) in report.deduplicated.iter() {
+        for objects in object.children_mut().iter_mut().filter(|w| w.name_str() == "Objects") {
+            objects.children_mut().retain(|w| !(w.name_str() == "Video" && video_id(w) == Some(*removed)));
+        }
+
+        redirect_connections(object, *removed, *kept);
+    }
+
+    if !report.deduplicated.is_empty() {
+        update_video_count(object);
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::ObjectGraph;
+    use crate::testing::*;
+
+    const PNG: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+    fn video(id: ObjectId, file_name: &str, relative_file_name: &str) -> Node {
+        let content = node("Content", vec![Type::VecRaw(PNG.to_vec())], vec![]);
+        object("Video", id, "video", "Clip", vec![text_node("Filename", file_name), text_node("RelativeFilename", relative_file_name), content])
+    }
+
+    #[test]
+    fn output_names_stay_inside_the_directory() {
+        let names = |file_name: &str, relative_file_name: &str| list_embedded_media(&scene(vec![], vec![video(7, file_name, relative_file_name)], vec![]))[0].output_name();
+
+        assert_eq!(names("C:\\textures\\wood.png", "textures/wood.png"), "wood.png");
+        assert_eq!(names("/textures/wood.png", ".."), "wood.png");
+        assert_eq!(names("..", "textures/."), "video_7.png");
+        assert_eq!(names("C:", ""), "video_7.png");
+    }
+
+    #[test]
+    fn extracts_with_unique_names() {
+        let object = scene(vec![], vec![video(1, "a/wood.png", ""), video(2, "b/wood.png", ""), video(3, "..", ".."), video(4, "c/2_wood.png", "")], vec![]);
+        let directory = std::env::temp_dir().join(format!("fbx-media-{}", std::process::id()));
+
+        let extracted = extract_embedded_media(&object, &directory).unwrap();
+        let names: Vec<PathBuf> = extracted.iter().map(|w| w.1.strip_prefix(&directory).unwrap().to_owned()).collect();
+        let bytes = std::fs::read(&extracted[2].1).unwrap();
+        let _ = std::fs::remove_dir_all(&directory);
+
+        // the fourth name is taken by the prefixed second one
+        assert_eq!(names, [PathBuf::from("wood.png"), PathBuf::from("2_wood.png"), PathBuf::from("video_3.png"), PathBuf::from("4_2_wood.png")]);
+        assert_eq!(bytes, PNG);
+        assert!(extracted.iter().all(|w| w.0.image_type == ImageType::Png));
+    }
+
+    #[test]
+    fn prefixes_until_the_name_is_unused() {
+        let object = scene(vec![], vec![video(1, "a/wood.png", ""), video(2, "b/5_wood.png", ""), video(5, "c/wood.png", "")], vec![]);
+        let directory = std::env::temp_dir().join(format!("fbx-media-prefix-{}", std::process::id()));
+
+        let extracted = extract_embedded_media(&object, &directory).unwrap();
+        let names: Vec<PathBuf> = extracted.iter().map(|w| w.1.strip_prefix(&directory).unwrap().to_owned()).collect();
+        let _ = std::fs::remove_dir_all(&directory);
+
+        assert_eq!(names, [PathBuf::from("wood.png"), PathBuf::from("5_wood.png"), PathBuf::from("5_2_wood.png")]);
+    }
+
+    #[test]
+    fn removes_duplicate_videos_and_redirects_their_connections() {
+        let definitions = vec![node("ObjectType", vec![string("Video")], vec![node("Count", vec![Type::Int32(3)], vec![])])];
+        let other = object("Video", 3, "video", "Clip", vec![node("Content", vec![Type::VecRaw(vec![1, 2, 3])], vec![])]);
+        let objects = vec![
+            object("Texture", 10, "a", "", vec![]),
+            object("Texture", 11, "b", "", vec![]),
+            object("Material", 12, "c", "", vec![]),
+            video(1, "wood.png", ""),
+            video(2, "copy.png", ""),
+            other,
+        ];
+        // the connection redirected to 1 comes before the identical one already there
+        let connections = vec![oo(2, 10), oo(1, 10), oo(2, 11), op(2, 12, "DiffuseColor"), oo(3, 12)];
+        let mut object = scene(definitions, objects, connections);
+
+        let report = embed_media(&mut object, &std::env::temp_dir()).unwrap();
+        assert_eq!(report, EmbedReport { deduplicated: vec![(2, 1)], ..EmbedReport::default() });
+
+        let graph = ObjectGraph::from(&object);
+        assert!(graph.object(2).is_none());
+        assert_eq!(graph.objects_of_class("Video").map(|w| w.id()).collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(graph.children(10), [1]);
+        assert_eq!(graph.children(11), [1]);
+        assert_eq!(graph.property_sources(12, "DiffuseColor"), [1]);
+        assert_eq!(graph.connections().len(), 4);
+
+        let count = &object.children_slice()[0].children_slice()[0].children_slice()[0];
+        assert_eq!(count.attributes_slice()[0].as_int32(), Some(2));
+    }
+}
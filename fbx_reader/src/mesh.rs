@@ -0,0 +1,187 @@
+use fbx::format::Node;
+
+use crate::error::SceneError;
+use crate::graph::{ObjectGraph, ObjectId};
+use crate::math::Vector3;
+
+pub(crate) fn child<'a>(node: &'a Node, name: &str) -> Option<&'a Node> {
+    node.children_slice().iter().find(|w| w.name_str() == name)
+}
+
+// first attribute of the child `name` widened to f64, `None` if the child does not exist
+pub(crate) fn child_f64_array(node: &Node, name: &str) -> Option<Vec<f64>> {
+    child(node, name)?.attributes_slice().first()?.to_f64_vec()
+}
+
+pub(crate) fn child_i32_array(node: &Node, name: &str) -> Option<Vec<i32>> {
+    child(node, name)?.attributes_slice().first()?.as_int32_array()
+}
+
+pub(crate) fn to_vectors(name: &str, values: &[f64]) -> Result<Vec<Vector3>, SceneError> {
+    if !values.len().is_multiple_of(3) {
+        return Err(SceneError::InvalidArrayLength(name.to_owned(), values.len()));
+    }
+
+    Ok(values.chunks_exact(3).map(|w| [w[0], w[1], w[2]]).collect())
+}
+
+/// Polygon mesh decoded from a `Geometry` object of subclass `Mesh`.
+#[derive(Debug, Clone)]
+pub struct Mesh {
+    positions: Vec<Vector3>,
+    polygon_vertices: Vec<u32>,
+    polygon_starts: Vec<usize>,
+    edges: Vec<i32>,
+    node: Node,
+}
+
+impl Mesh {
+    pub fn from_node(node: &Node) -> Result<Mesh, SceneError> {
+        let vertices = child_f64_array(node, "Vertices").ok_or(SceneError::MissingNode("Vertices".to_owned()))?;
+        let positions = to_vectors("Vertices", &vertices)?;
+        let indices = child_i32_array(node, "PolygonVertexIndex").ok_or(SceneError::MissingNode("PolygonVertexIndex".to_owned()))?;
+
+        let mut polygon_vertices: Vec<u32> = Vec::with_capacity(indices.len());
+        let mut polygon_starts: Vec<usize> = vec![0];
+
+        for index in indices.iter() {
+            // the last index of every polygon is stored as `-index - 1`
+            let (vertex, last) = if *index < 0 { (-(*index as i64) - 1, true) } else { (*index as i64, false) };
+
+            if vertex as usize >= positions.len() {
+                return Err(SceneError::IndexOutOfRange(vertex, positions.len()));
+            }
+
+            polygon_vertices.push(vertex as u32);
+
+            if last {
+                polygon_starts.push(polygon_vertices.len());
+            }
+        }
+
+        if polygon_starts.last() != Some(&polygon_vertices.len()) {
+            return Err(SceneError::UnterminatedPolygon);
+        }
+
+        let edges = child_i32_array(node, "Edges").unwrap_or_default();
+        for edge in edges.iter() {
+            if *edge < 0 || *edge as usize >= polygon_vertices.len() {
+                return Err(SceneError::IndexOutOfRange(*edge as i64, polygon_vertices.len()));
+            }
+        }
+
+        Ok(Mesh { positions, polygon_vertices, polygon_starts, edges, node: node.clone() })
+    }
+
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Mesh, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if object.class() != "Geometry" || object.subclass() != "Mesh" {
+            return Err(SceneError::UnexpectedObject(id, "Geometry::Mesh".to_owned()));
+        }
+
+        Mesh::from_node(object.node())
+    }
+
+    // the `Geometry` node the mesh was decoded from
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    pub fn positions(&self) -> &[Vector3] {
+        &self.positions
+    }
+
+    pub fn control_point_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn polygon_count(&self) -> usize {
+        self.polygon_starts.len() - 1
+    }
+
+    // control point indices of every polygon vertex, polygons stored one after another
+    pub fn polygon_vertices(&self) -> &[u32] {
+        &self.polygon_vertices
+    }
+
+    pub fn polygon_vertex_count(&self) -> usize {
+        self.polygon_vertices.len()
+    }
+
+    // index of the first polygon vertex of `polygon`
+    pub fn polygon_start(&self, polygon: usize) -> usize {
+        self.polygon_starts[polygon]
+    }
+
+    pub fn polygon(&self, polygon: usize) -> &[u32] {
+        &self.polygon_vertices[self.polygon_starts[polygon]..self.polygon_starts[polygon + 1]]
+    }
+
+    pub fn polygons(&self) -> impl Iterator<Item = &[u32]> {
+        self.polygon_starts.windows(2).map(|w| &self.polygon_vertices[w[0]..w[1]])
+    }
+
+    // polygon index of every polygon vertex
+    pub fn polygon_vertex_polygons(&self) -> Vec<usize> {
+        let mut result = Vec::with_capacity(self.polygon_vertices.len());
+        for (polygon, range) in self.polygon_starts.windows(2).enumerate() {
+            result.extend(std::iter::repeat_n(polygon, range[1] - range[0]));
+        }
+        result
+    }
+
+    // polygon vertex following `polygon_vertex` in its polygon, wrapping around
+    pub fn next_polygon_vertex(&self, polygon_vertex: usize) -> usize {
+        let polygon = self.polygon_starts.partition_point(|w| *w <= polygon_vertex) - 1;
+        let (start, end) = (self.polygon_starts[polygon], self.polygon_starts[polygon + 1]);
+
+        if polygon_vertex + 1 < end { polygon_vertex + 1 } else { start }
+    }
+
+    // raw `Edges` array, each entry is the polygon vertex an edge starts at
+    pub fn edges(&self) -> &[i32] {
+        &self.edges
+    }
+
+    // control points of every edge
+    pub fn edge_vertices(&self) -> Vec<(u32, u32)> {
+        self.edges
+            .iter()
+            .map(|w| {
+                let start = *w as usize;
+                (self.polygon_vertices[start], self.polygon_vertices[self.next_polygon_vertex(start)])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn geometry(vertices: &[f64], indices: &[i32], edges: &[i32]) -> Node {
+        object("Geometry", 1, "mesh", "Mesh", vec![f64_array("Vertices", vertices), i32_array("PolygonVertexIndex", indices), i32_array("Edges", edges)])
+    }
+
+    #[test]
+    fn splits_polygons_and_edges() {
+        let vertices = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0, 2.0, 0.0, 0.0];
+        let mesh = Mesh::from_node(&geometry(&vertices, &[0, 1, 2, -4, 1, 4, -3], &[0, 3, 5])).unwrap();
+
+        assert_eq!(mesh.polygon_count(), 2);
+        assert_eq!(mesh.polygon(0), [0, 1, 2, 3]);
+        assert_eq!(mesh.polygon(1), [1, 4, 2]);
+        assert_eq!(mesh.polygon_vertex_polygons(), [0, 0, 0, 0, 1, 1, 1]);
+        assert_eq!(mesh.next_polygon_vertex(6), 4);
+        assert_eq!(mesh.edge_vertices(), [(0, 1), (3, 0), (4, 2)]);
+    }
+
+    #[test]
+    fn rejects_malformed_arrays() {
+        assert!(matches!(Mesh::from_node(&geometry(&[0.0, 1.0], &[], &[])), Err(SceneError::InvalidArrayLength(..))));
+        assert!(matches!(Mesh::from_node(&geometry(&[0.0; 3], &[0, 0, 1], &[])), Err(SceneError::IndexOutOfRange(1, 1))));
+        assert!(matches!(Mesh::from_node(&geometry(&[0.0; 3], &[0, 0, 0], &[])), Err(SceneError::UnterminatedPolygon)));
+    }
+}
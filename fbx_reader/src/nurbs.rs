@@ -0,0 +1,529 @@
+use fbx::format::Node;
+
+use crate::error::SceneError;
+use crate::graph::{is_class, ObjectGraph, ObjectId};
+use crate::math::{add, cross, normalize, sub, Vector3};
+use crate::mesh::{child, child_f64_array, child_i32_array, to_vectors};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NurbsForm {
+    Open,
+    // the ends meet, but the curve is not smooth there
+    Closed,
+    // the first `order - 1` control points repeat after the last one
+    Periodic,
+}
+
+impl NurbsForm {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "Closed" => NurbsForm::Closed,
+            "Periodic" => NurbsForm::Periodic,
+            _ => NurbsForm::Open,
+        }
+    }
+}
+
+fn text(node: &Node, name: &str) -> Option<String> {
+    child(node, name)?.attributes_slice().first()?.as_str()
+}
+
+fn integers(node: &Node, name: &str) -> Vec<i64> {
+    child(node, name).map(|w| w.attributes_slice().iter().filter_map(|a| a.to_f64()).map(|a| a as i64).collect()).unwrap_or_default()
+}
+
+// `Points` as homogeneous `[x * w, y * w, z * w, w]`, the file stores `[x, y, z, w]`
+fn homogeneous_points(node: &Node) -> Result<Vec<[f64; 4]>, SceneError> {
+    let values = child_f64_array(node, "Points").ok_or(SceneError::MissingNode("Points".to_owned()))?;
+
+    if values.len() % 4 != 0 {
+        return Err(SceneError::InvalidArrayLength("Points".to_owned(), values.len()));
+    }
+
+    Ok(values.chunks_exact(4).map(|w| [w[0] * w[3], w[1] * w[3], w[2] * w[3], w[3]]).collect())
+}
+
+fn project(point: [f64; 4]) -> Vector3 {
+    if point[3] != 0.0 { [point[0] / point[3], point[1] / point[3], point[2] / point[3]] } else { [point[0], point[1], point[2]] }
+}
+
+// number of control points including the periodic wrap, checked against the knot vector
+fn wrapped_count(name: &str, count: usize, knots: &[f64], order: usize, form: NurbsForm) -> Result<usize, SceneError> {
+    if order == 0 || count < order {
+        return Err(SceneError::InvalidArrayLength("Points".to_owned(), count * 4));
+    }
+
+    if knots.len() == count + order {
+        return Ok(count);
+    }
+
+    if form == NurbsForm::Periodic && knots.len() == count + 2 * order - 1 {
+        return Ok(count + order - 1);
+    }
+
+    Err(SceneError::InvalidArrayLength(name.to_owned(), knots.len()))
+}
+
+// de Boor's algorithm on homogeneous points
+fn de_boor(points: &[[f64; 4]], knots: &[f64], order: usize, t: f64) -> [f64; 4] {
+    let degree = order - 1;
+    let mut span = degree;
+
+    while span + 1 < points.len() && knots[span + 1] <= t {
+        span += 1;
+    }
+
+    let mut d: Vec<[f64; 4]> = points[span - degree..=span].to_vec();
+
+    for r in 1..=degree {
+        for j in (r..=degree).rev() {
+            let i = j + span - degree;
+            let denominator = knots[i + degree + 1 - r] - knots[i];
+            let alpha = if denominator > 0.0 { (t - knots[i]) / denominator } else { 0.0 };
+
+            let previous = d[j - 1];
+            for (value, previous) in d[j].iter_mut().zip(previous) {
+                *value = (1.0 - alpha) * previous + alpha * *value;
+            }
+        }
+    }
+
+    d[degree]
+}
+
+// parameters sampling every non-empty knot span of the domain `segments` times, both ends included
+fn samples(knots: &[f64], order: usize, count: usize, segments: usize) -> Vec<f64> {
+    let segments = segments.max(1);
+    let mut parameters = Vec::new();
+
+    for span in order - 1..count {
+        let (start, end) = (knots[span], knots[span + 1]);
+
+        if end > start {
+            parameters.extend((0..segments).map(|w| start + (end - start) * w as f64 / segments as f64));
+        }
+    }
+
+    parameters.push(knots[count]);
+    parameters
+}
+
+/// A `Geometry` of subclass `NurbsCurve`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NurbsCurve {
+    id: ObjectId,
+    order: usize,
+    dimension: usize,
+    form: NurbsForm,
+    rational: bool,
+    points: Vec<[f64; 4]>,
+    knots: Vec<f64>,
+}
+
+impl NurbsCurve {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<NurbsCurve, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if !is_class(graph, id, "Geometry", "NurbsCurve") {
+            return Err(SceneError::UnexpectedObject(id, "Geometry::NurbsCurve".to_owned()));
+        }
+
+        let node = object.node();
+        let order = integers(node, "Order").first().copied().unwrap_or(4).max(1) as usize;
+        let form = NurbsForm::parse(&text(node, "Form").unwrap_or_default());
+        let knots = child_f64_array(node, "KnotVector").ok_or(SceneError::MissingNode("KnotVector".to_owned()))?;
+        let points = homogeneous_points(node)?;
+        let count = wrapped_count("KnotVector", points.len(), &knots, order, form)?;
+        let points = (0..count).map(|w| points[w % points.len()]).collect();
+
+        Ok(NurbsCurve {
+            id,
+            order,
+            dimension: integers(node, "Dimension").first().copied().unwrap_or(3) as usize,
+            form,
+            rational: integers(node, "Rational").first().map(|w| *w != 0).unwrap_or(false),
+            points,
+            knots,
+        })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    // degree + 1
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    // 2 for planar curves, 3 otherwise
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    pub fn form(&self) -> NurbsForm {
+        self.form
+    }
+
+    pub fn rational(&self) -> bool {
+        self.rational
+    }
+
+    pub fn knots(&self) -> &[f64] {
+        &self.knots
+    }
+
+    // control points including the periodic wrap
+    pub fn control_points(&self) -> Vec<Vector3> {
+        self.points.iter().map(|w| project(*w)).collect()
+    }
+
+    pub fn weights(&self) -> Vec<f64> {
+        self.points.iter().map(|w| w[3]).collect()
+    }
+
+    // the parameter range the curve is defined on
+    pub fn domain(&self) -> (f64, f64) {
+        (self.knots[self.order - 1], self.knots[self.points.len()])
+    }
+
+    pub fn evaluate(&self, t: f64) -> Vector3 {
+        let (start, end) = self.domain();
+        project(de_boor(&self.points, &self.knots, self.order, t.clamp(start, end)))
+    }
+
+    // polyline through `segments` points per knot span
+    pub fn tessellate(&self, segments: usize) -> Vec<Vector3> {
+        samples(&self.knots, self.order, self.points.len(), segments).into_iter().map(|t| self.evaluate(t)).collect()
+    }
+}
+
+/// Triangles sampled from a NURBS surface.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SurfaceMesh {
+    pub positions: Vec<Vector3>,
+    pub normals: Vec<Vector3>,
+    // surface parameters normalised to 0..1
+    pub uvs: Vec<[f64; 2]>,
+    pub triangles: Vec<[u32; 3]>,
+}
+
+/// A `Geometry` of subclass `NurbsSurface`; control points are stored row by row with U varying fastest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NurbsSurface {
+    id: ObjectId,
+    order: [usize; 2],
+    count: [usize; 2],
+    forms: [NurbsForm; 2],
+    steps: [usize; 2],
+    points: Vec<[f64; 4]>,
+    knots: [Vec<f64>; 2],
+    flip_normals: bool,
+}
+
+impl NurbsSurface {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<NurbsSurface, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if !is_class(graph, id, "Geometry", "NurbsSurface") {
+            return Err(SceneError::UnexpectedObject(id, "Geometry::NurbsSurface".to_owned()));
+        }
+
+        let node = object.node();
+        let pair = |name: &str, default: i64| {
+            let values = integers(node, name);
+            [values.first().copied().unwrap_or(default).max(1) as usize, values.get(1).copied().unwrap_or(default).max(1) as usize]
+        };
+
+        let order = pair("NurbsSurfaceOrder", 4);
+        let dimensions = pair("Dimensions", 0);
+        let steps = pair("Step", 4);
+        let forms = child(node, "Form").map(|w| w.attributes_slice().iter().map(|a| NurbsForm::parse(&a.as_str().unwrap_or_default())).collect::<Vec<_>>()).unwrap_or_default();
+        let forms = [forms.first().copied().unwrap_or(NurbsForm::Open), forms.get(1).copied().unwrap_or(NurbsForm::Open)];
+
+        let knots_u = child_f64_array(node, "KnotVectorU").ok_or(SceneError::MissingNode("KnotVectorU".to_owned()))?;
+        let knots_v = child_f64_array(node, "KnotVectorV").ok_or(SceneError::MissingNode("KnotVectorV".to_owned()))?;
+
+        let points = homogeneous_points(node)?;
+        if points.len() != dimensions[0] * dimensions[1] {
+            return Err(SceneError::InvalidArrayLength("Points".to_owned(), points.len() * 4));
+        }
+
+        let count = [
+            wrapped_count("KnotVectorU", dimensions[0], &knots_u, order[0], forms[0])?,
+            wrapped_count("KnotVectorV", dimensions[1], &knots_v, order[1], forms[1])?,
+        ];
+
+        // periodic directions repeat their first rows or columns
+        let mut wrapped = Vec::with_capacity(count[0] * count[1]);
+        for v in 0..count[1] {
+            for u in 0..count[0] {
+                wrapped.push(points[(v % dimensions[1]) * dimensions[0] + u % dimensions[0]]);
+            }
+        }
+
+        Ok(NurbsSurface {
+            id,
+            order,
+            count,
+            forms,
+            steps,
+            points: wrapped,
+            knots: [knots_u, knots_v],
+            flip_normals: integers(node, "FlipNormals").first().map(|w| *w != 0).unwrap_or(false),
+        })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn order(&self) -> [usize; 2] {
+        self.order
+    }
+
+    // control points along U and V, including periodic wraps
+    pub fn count(&self) -> [usize; 2] {
+        self.count
+    }
+
+    pub fn forms(&self) -> [NurbsForm; 2] {
+        self.forms
+    }
+
+    // the tessellation the file asks for, segments per span along U and V
+    pub fn steps(&self) -> [usize; 2] {
+        self.steps
+    }
+
+    pub fn knots_u(&self) -> &[f64] {
+        &self.knots[0]
+    }
+
+    pub fn knots_v(&self) -> &[f64] {
+        &self.knots[1]
+    }
+
+    pub fn control_points(&self) -> Vec<Vector3> {
+        self.points.iter().map(|w| project(*w)).collect()
+    }
+
+    pub fn weights(&self) -> Vec<f64> {
+        self.points.iter().map(|w| w[3]).collect()
+    }
+
+    pub fn domain_u(&self) -> (f64, f64) {
+        (self.knots[0][self.order[0] - 1], self.knots[0][self.count[0]])
+    }
+
+    pub fn domain_v(&self) -> (f64, f64) {
+        (self.knots[1][self.order[1] - 1], self.knots[1][self.count[1]])
+    }
+
+    pub fn evaluate(&self, u: f64, v: f64) -> Vector3 {
+        let (u_start, u_end) = self.domain_u();
+        let (v_start, v_end) = self.domain_v();
+        let (u, v) = (u.clamp(u_start, u_end), v.clamp(v_start, v_end));
+
+        let column: Vec<[f64; 4]> = self.points.chunks_exact(self.count[0]).map(|row| de_boor(row, &self.knots[0], self.order[0], u)).collect();
+        project(de_boor(&column, &self.knots[1], self.order[1], v))
+    }
+
+    // a grid of `segments` quads per knot span and direction, split into triangles
+    pub fn tessellate(&self, segments_u: usize, segments_v: usize) -> SurfaceMesh {
+        let us = samples(&self.knots[0], self.order[0], self.count[0], segments_u);
+        let vs = samples(&self.knots[1], self.order[1], self.count[1], segments_v);
+        let ((u_start, u_end), (v_start, v_end)) = (self.domain_u(), self.domain_v());
+        let normalise = |value: f64, start: f64, end: f64| if end > start { (value - start) / (end - start) } else { 0.0 };
+
+        let mut mesh = SurfaceMesh::default();
+
+        for v in vs.iter() {
+            for u in us.iter() {
+                mesh.positions.push(self.evaluate(*u, *v));
+                mesh.uvs.push([normalise(*u, u_start, u_end), normalise(*v, v_start, v_end)]);
+            }
+        }
+
+        let width = us.len() as u32;
+        for row in 0..vs.len() as u32 - 1 {
+            for column in 0..width - 1 {
+                let (a, b, c, d) = (row * width + column, row * width + column + 1, (row + 1) * width + column + 1, (row + 1) * width + column);
+
+                if self.flip_normals {
+                    mesh.triangles.extend([[a, c, b], [a, d, c]]);
+                } else {
+                    mesh.triangles.extend([[a, b, c], [a, c, d]]);
+                }
+            }
+        }
+
+        // area weighted vertex normals, degenerate triangles at poles add nothing
+        let mut normals = vec![[0.0; 3]; mesh.positions.len()];
+        for triangle in mesh.triangles.iter() {
+            let [a, b, c] = triangle.map(|w| mesh.positions[w as usize]);
+            let normal = cross(sub(b, a), sub(c, a));
+
+            for index in triangle.iter() {
+                normals[*index as usize] = add(normals[*index as usize], normal);
+            }
+        }
+
+        mesh.normals = normals.into_iter().map(normalize).collect();
+        mesh
+    }
+}
+
+/// A `Geometry` of subclass `Line`: polylines through shared points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    id: ObjectId,
+    points: Vec<Vector3>,
+    strips: Vec<Vec<u32>>,
+}
+
+impl Line {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Line, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if !is_class(graph, id, "Geometry", "Line") {
+            return Err(SceneError::UnexpectedObject(id, "Geometry::Line".to_owned()));
+        }
+
+        let node = object.node();
+        let points = to_vectors("Points", &child_f64_array(node, "Points").ok_or(SceneError::MissingNode("Points".to_owned()))?)?;
+        let indices = child_i32_array(node, "PointsIndex").unwrap_or_else(|| (0..points.len() as i32).collect());
+
+        // like polygons, the last index of every strip is stored as `-index - 1`; an unterminated last strip is kept
+        let mut strips = Vec::new();
+        let mut strip = Vec::new();
+
+        for index in indices {
+            let (point, last) = if index < 0 { (-(index as i64) - 1, true) } else { (index as i64, false) };
+
+            if point as usize >= points.len() {
+                return Err(SceneError::IndexOutOfRange(point, points.len()));
+            }
+
+            strip.push(point as u32);
+
+            if last {
+                strips.push(std::mem::take(&mut strip));
+            }
+        }
+
+        if !strip.is_empty() {
+            strips.push(strip);
+        }
+
+        Ok(Line { id, points, strips })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn points(&self) -> &[Vector3] {
+        &self.points
+    }
+
+    // point indices of every strip
+    pub fn strips(&self) -> &[Vec<u32>] {
+        &self.strips
+    }
+
+    pub fn polylines(&self) -> Vec<Vec<Vector3>> {
+        self.strips.iter().map(|w| w.iter().map(|i| self.points[*i as usize]).collect()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{dot, length};
+    use crate::testing::*;
+    use fbx::format::Type;
+
+    const HALF_SQRT_2: f64 = std::f64::consts::FRAC_1_SQRT_2;
+
+    // the unit circle in the XY plane as a rational quadratic with four quarter arcs, `[x, y, z, w]` per point
+    fn circle(z: f64) -> Vec<f64> {
+        let corners = [[1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [-1.0, 1.0], [-1.0, 0.0], [-1.0, -1.0], [0.0, -1.0], [1.0, -1.0], [1.0, 0.0]];
+        corners.iter().enumerate().flat_map(|(i, [x, y])| [*x, *y, z, if i % 2 == 1 { HALF_SQRT_2 } else { 1.0 }]).collect()
+    }
+
+    const CIRCLE_KNOTS: [f64; 12] = [0.0, 0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 4.0, 4.0, 4.0];
+
+    fn integer(name: &str, values: &[i32]) -> fbx::format::Node {
+        node(name, values.iter().map(|w| Type::Int32(*w)).collect(), vec![])
+    }
+
+    #[test]
+    fn evaluates_a_rational_circle() {
+        let children = vec![integer("Order", &[3]), integer("Dimension", &[3]), text_node("Form", "Closed"), integer("Rational", &[1]), f64_array("Points", &circle(0.0)), f64_array("KnotVector", &CIRCLE_KNOTS)];
+        let curve = NurbsCurve::from_graph(&graph(vec![object("Geometry", 1, "circle", "NurbsCurve", children)], vec![]), 1).unwrap();
+
+        assert_eq!(curve.domain(), (0.0, 4.0));
+        assert!(curve.rational());
+
+        // the middle of every quarter arc lies at 45 degrees, the knots at the axes
+        assert_all_close(&curve.evaluate(0.5), &[HALF_SQRT_2, HALF_SQRT_2, 0.0], 1e-12);
+        assert_all_close(&curve.evaluate(1.0), &[0.0, 1.0, 0.0], 1e-12);
+        assert_all_close(&curve.evaluate(2.5), &[-HALF_SQRT_2, -HALF_SQRT_2, 0.0], 1e-12);
+        assert_all_close(&curve.evaluate(4.0), &[1.0, 0.0, 0.0], 1e-12);
+
+        let points = curve.tessellate(8);
+        assert_eq!(points.len(), 33);
+        for point in points {
+            assert_close(length(point), 1.0, 1e-12);
+        }
+    }
+
+    #[test]
+    fn tessellates_a_rational_cylinder() {
+        let points: Vec<f64> = [circle(0.0), circle(1.0)].concat();
+        let children = vec![
+            integer("NurbsSurfaceOrder", &[3, 2]),
+            integer("Dimensions", &[9, 2]),
+            integer("Step", &[4, 1]),
+            node("Form", vec![string("Closed"), string("Open")], vec![]),
+            f64_array("Points", &points),
+            f64_array("KnotVectorU", &CIRCLE_KNOTS),
+            f64_array("KnotVectorV", &[0.0, 0.0, 1.0, 1.0]),
+        ];
+        let surface = NurbsSurface::from_graph(&graph(vec![object("Geometry", 1, "cylinder", "NurbsSurface", children)], vec![]), 1).unwrap();
+
+        assert_all_close(&surface.evaluate(0.5, 0.25), &[HALF_SQRT_2, HALF_SQRT_2, 0.25], 1e-12);
+
+        let mesh = surface.tessellate(4, 1);
+        assert_eq!((mesh.positions.len(), mesh.triangles.len()), (17 * 2, 16 * 2));
+        assert_eq!(mesh.uvs[17 + 8], [0.5, 1.0]);
+
+        // normals are horizontal and, up to the faceting, point along the radius, all to the same side
+        let mut sides = Vec::new();
+        for (position, normal) in mesh.positions.iter().zip(mesh.normals.iter()) {
+            assert_close(length([position[0], position[1], 0.0]), 1.0, 1e-12);
+            assert_close(normal[2], 0.0, 1e-12);
+            sides.push(dot(*normal, [position[0], position[1], 0.0]));
+        }
+
+        assert!(sides.iter().all(|w| w.abs() > 0.95 && w.signum() == sides[0].signum()), "{:?}", sides);
+    }
+
+    #[test]
+    fn rejects_knot_vectors_of_the_wrong_length() {
+        let children = vec![integer("Order", &[3]), f64_array("Points", &circle(0.0)), f64_array("KnotVector", &CIRCLE_KNOTS[1..])];
+        let result = NurbsCurve::from_graph(&graph(vec![object("Geometry", 1, "circle", "NurbsCurve", children)], vec![]), 1);
+
+        assert!(matches!(result, Err(SceneError::InvalidArrayLength(_, 11))));
+    }
+
+    #[test]
+    fn splits_lines_into_strips() {
+        let children = vec![f64_array("Points", &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0]), i32_array("PointsIndex", &[0, -2, 1, 2])];
+        let line = Line::from_graph(&graph(vec![object("Geometry", 1, "line", "Line", children)], vec![]), 1).unwrap();
+
+        assert_eq!(line.strips(), [vec![0, 1], vec![1, 2]]);
+        assert_eq!(line.polylines()[1], [[1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]);
+    }
+}
@@ -0,0 +1,321 @@
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use fbx::format::{Node, Object, Type};
+
+use crate::graph::ObjectId;
+use crate::media::file_name_of;
+use crate::mesh::child;
+
+// searching stops this many directories deep, so symlink loops cannot run forever
+const MAX_SEARCH_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolveMethod {
+    // `RelativeFilename` relative to the FBX file
+    Relative,
+    // `FileName` exists as written
+    Absolute,
+    // `RelativeFilename` relative to one of the search directories
+    SearchDirectory,
+    // a file with the same name, ignoring case, somewhere below the FBX file or a search directory
+    Basename,
+}
+
+/// File paths stored on a `Texture` or `Video` object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TexturePaths {
+    pub file_name: String,
+    pub relative_file_name: String,
+}
+
+impl TexturePaths {
+    pub(crate) fn read(node: &Node) -> Self {
+        let text = |name: &str| child(node, name).and_then(|w| w.attributes_slice().first()).and_then(|w| w.as_str());
+
+        TexturePaths {
+            file_name: text("FileName").or_else(|| text("Filename")).unwrap_or_default(),
+            relative_file_name: text("RelativeFilename").unwrap_or_default(),
+        }
+    }
+
+    // both paths with forward slashes
+    pub fn with_forward_slashes(&self) -> TexturePaths {
+        TexturePaths { file_name: self.file_name.replace('\\', "/"), relative_file_name: self.relative_file_name.replace('\\', "/") }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution {
+    pub id: ObjectId,
+    // `Texture` or `Video`
+    pub class: String,
+    pub paths: TexturePaths,
+    pub resolved: Option<(PathBuf, ResolveMethod)>,
+}
+
+impl Resolution {
+    pub fn is_missing(&self) -> bool {
+        self.resolved.is_none()
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.resolved.as_ref().map(|w| w.0.as_path())
+    }
+}
+
+fn index_directory(directory: &Path, depth: usize, index: &mut HashMap<String, Vec<PathBuf>>) {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            if depth < MAX_SEARCH_DEPTH {
+                index_directory(&path, depth + 1, index);
+            }
+        } else if let Some(name) = path.file_name().and_then(|w| w.to_str()) {
+            index.entry(name.to_lowercase()).or_default().push(path);
+        }
+    }
+}
+
+/// Finds the files textures refer to, whatever platform the FBX was written on.
+#[derive(Debug, Clone)]
+pub struct TextureResolver {
+    fbx_directory: PathBuf,
+    search_directories: Vec<PathBuf>,
+    // built by the first lookup that needs it, most scenes resolve without walking the directories
+    basenames: OnceCell<HashMap<String, Vec<PathBuf>>>,
+}
+
+impl TextureResolver {
+    pub fn new(fbx_directory: &Path, search_directories: &[PathBuf]) -> Self {
+        TextureResolver { fbx_directory: fbx_directory.to_owned(), search_directories: search_directories.to_vec(), basenames: OnceCell::new() }
+    }
+
+    // every file below `fbx_directory` and `search_directories` by lowercase name, indexed on first use
+    fn basenames(&self) -> &HashMap<String, Vec<PathBuf>> {
+        self.basenames.get_or_init(|| {
+            let mut basenames = HashMap::new();
+
+            for directory in std::iter::once(self.fbx_directory.as_path()).chain(self.search_directories.iter().map(|w| w.as_path())) {
+                index_directory(directory, 0, &mut basenames);
+            }
+
+            basenames
+        })
+    }
+
+    // tries, in order, the relative path, the absolute path, the search directories and the basename
+    pub fn resolve(&self, paths: &TexturePaths) -> Option<(PathBuf, ResolveMethod)> {
+        let relative = paths.relative_file_name.replace('\\', "/");
+
+        if !relative.is_empty() {
+            let candidate = self.fbx_directory.join(&relative);
+            if candidate.is_file() {
+                return Some((candidate, ResolveMethod::Relative));
+            }
+        }
+
+        if !paths.file_name.is_empty() && Path::new(&paths.file_name).is_file() {
+            return Some((PathBuf::from(&paths.file_name), ResolveMethod::Absolute));
+        }
+
+        if !relative.is_empty() {
+            if let Some(candidate) = self.search_directories.iter().map(|w| w.join(&relative)).find(|w| w.is_file()) {
+                return Some((candidate, ResolveMethod::SearchDirectory));
+            }
+        }
+
+        // the first file found wins when several share the name
+        for path in [&paths.relative_file_name, &paths.file_name] {
+            let name = file_name_of(path).to_lowercase();
+            if name.is_empty() {
+                continue;
+            }
+
+            if let Some(candidate) = self.basenames().get(&name).and_then(|w| w.first()) {
+                return Some((candidate.clone(), ResolveMethod::Basename));
+            }
+        }
+
+        None
+    }
+
+    // resolves every `Texture` and `Video` object of the scene that names a file
+    pub fn resolve_scene(&self, object: &Object) -> Vec<Resolution> {
+        texture_nodes(object)
+            .filter_map(|node| {
+                let id = node.attributes_slice().first().and_then(|w| w.as_int64())?;
+                let paths = TexturePaths::read(node);
+
+                if paths.file_name.is_empty() && paths.relative_file_name.is_empty() {
+                    return None;
+                }
+
+                Some(Resolution { id, class: node.name(), resolved: self.resolve(&paths), paths })
+            })
+            .collect()
+    }
+}
+
+fn texture_nodes(object: &Object) -> impl Iterator<Item = &Node> {
+    object
+        .children_slice()
+        .iter()
+        .filter(|w| w.name_str() == "Objects")
+        .flat_map(|w| w.children_slice())
+        .filter(|w| w.name_str() == "Texture" || w.name_str() == "Video")
+}
+
+fn set_child_str(node: &mut Node, name: &str, value: &str) {
+    match node.children_mut().iter_mut().find(|w| w.name_str() == name) {
+        Some(child) => *child.attributes_mut() = vec![Type::String(value.to_owned())],
+        None => node.children_mut().push(Node::new(name.to_owned(), vec![Type::String(value.to_owned())], vec![])),
+    }
+}
+
+fn write_paths(node: &mut Node, paths: &TexturePaths) {
+    // textures spell it `FileName`, videos `Filename`
+    let file_name = if node.name_str() == "Video" { "Filename" } else { "FileName" };
+    set_child_str(node, file_name, &paths.file_name);
+    set_child_str(node, "RelativeFilename", &paths.relative_file_name);
+
+    // videos also keep the absolute path in the `Path` property
+    let properties = node.children_mut().iter_mut().filter(|w| w.name_str() == "Properties70").flat_map(|w| w.children_mut().iter_mut());
+    for property in properties.filter(|w| w.name_str() == "P" && w.attributes_slice().first().and_then(|a| a.as_str_ref()) == Some("Path")) {
+        if let Some(value) = property.attributes_mut().get_mut(4) {
+            *value = Type::String(paths.file_name.clone());
+        }
+    }
+}
+
+// calls `rewrite` with the paths of every `Texture` and `Video`, replacing them when it returns new ones;
+// returns the number of objects changed
+pub fn rewrite_paths(object: &mut Object, mut rewrite: impl FnMut(ObjectId, &TexturePaths) -> Option<TexturePaths>) -> usize {
+    let mut changed = 0;
+    let objects = object.children_mut().iter_mut().filter(|w| w.name_str() == "Objects").flat_map(|w| w.children_mut().iter_mut());
+
+    for node in objects.filter(|w| w.name_str() == "Texture" || w.name_str() == "Video") {
+        let id = match node.attributes_slice().first().and_then(|w| w.as_int64()) {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let paths = TexturePaths::read(node);
+        if let Some(new_paths) = rewrite(id, &paths).filter(|w| *w != paths) {
+            write_paths(node, &new_paths);
+            changed += 1;
+        }
+    }
+
+    changed
+}
+
+// `path` made absolute, with symlinks and `..` resolved through its closest existing ancestor
+fn absolute_path(path: &Path) -> PathBuf {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_owned());
+
+    for ancestor in path.ancestors() {
+        if let Ok(canonical) = ancestor.canonicalize() {
+            return canonical.join(path.strip_prefix(ancestor).unwrap_or(Path::new("")));
+        }
+    }
+
+    path
+}
+
+// `path` relative to `base` with forward slashes, `None` if they share no root
+pub fn relative_path(path: &Path, base: &Path) -> Option<String> {
+    let (path, base) = (absolute_path(path), absolute_path(base));
+    if path.is_absolute() != base.is_absolute() {
+        return None;
+    }
+
+    let path: Vec<Component> = path.components().filter(|w| *w != Component::CurDir).collect();
+    let base: Vec<Component> = base.components().filter(|w| *w != Component::CurDir).collect();
+
+    // paths on different drives
+    if matches!(path.first(), Some(Component::Prefix(_))) && path.first() != base.first() {
+        return None;
+    }
+
+    let common = path.iter().zip(base.iter()).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = std::iter::repeat_n("..".to_owned(), base.len() - common).collect();
+    parts.extend(path[common..].iter().map(|w| w.as_os_str().to_string_lossy().into_owned()));
+
+    Some(parts.join("/"))
+}
+
+// points every resolved texture at its file relative to `fbx_directory`, with forward slashes in both paths;
+// unresolved ones only get their slashes turned around
+pub fn make_paths_relative(object: &mut Object, resolutions: &[Resolution], fbx_directory: &Path) -> usize {
+    let resolved: HashMap<ObjectId, &Path> = resolutions.iter().filter_map(|w| Some((w.id, w.path()?))).collect();
+
+    rewrite_paths(object, |id, paths| {
+        let relative = resolved.get(&id).and_then(|w| relative_path(w, fbx_directory));
+
+        match relative {
+            Some(relative) => Some(TexturePaths { file_name: relative.clone(), relative_file_name: relative }),
+            None => Some(paths.with_forward_slashes()),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a fresh directory below the system temporary directory, removed by the caller
+    fn temp_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("fbx-paths-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn paths(file_name: &str, relative_file_name: &str) -> TexturePaths {
+        TexturePaths { file_name: file_name.to_owned(), relative_file_name: relative_file_name.to_owned() }
+    }
+
+    #[test]
+    fn resolves_in_order_and_indexes_only_on_a_miss() {
+        let directory = temp_directory("resolve");
+        std::fs::create_dir_all(directory.join("textures/deep")).unwrap();
+        std::fs::write(directory.join("textures/wood.png"), b"").unwrap();
+        std::fs::write(directory.join("textures/deep/Stone.PNG"), b"").unwrap();
+
+        let resolver = TextureResolver::new(&directory, &[]);
+        let relative = resolver.resolve(&paths("C:\\work\\textures\\wood.png", "textures\\wood.png"));
+        let indexed = resolver.basenames.get().is_some();
+        let basename = resolver.resolve(&paths("C:\\work\\stone.png", "stone.png"));
+        let missing = resolver.resolve(&paths("C:\\work\\grass.png", ""));
+        let _ = std::fs::remove_dir_all(&directory);
+
+        assert_eq!(relative, Some((directory.join("textures/wood.png"), ResolveMethod::Relative)));
+        assert!(!indexed);
+        assert_eq!(basename, Some((directory.join("textures/deep/Stone.PNG"), ResolveMethod::Basename)));
+        assert_eq!(missing, None);
+    }
+
+    #[test]
+    fn relative_paths_between_relative_and_absolute_paths() {
+        let directory = temp_directory("relative");
+        std::fs::create_dir_all(directory.join("scene")).unwrap();
+        let current = std::env::current_dir().unwrap();
+
+        let inside = relative_path(&directory.join("scene/textures/wood.png"), &directory.join("scene"));
+        let beside = relative_path(&directory.join("textures/wood.png"), &directory.join("scene/."));
+        let mixed = relative_path(Path::new("textures/wood.png"), &current);
+        let _ = std::fs::remove_dir_all(&directory);
+
+        assert_eq!(inside.as_deref(), Some("textures/wood.png"));
+        assert_eq!(beside.as_deref(), Some("../textures/wood.png"));
+        assert_eq!(mixed.as_deref(), Some("textures/wood.png"));
+    }
+}
@@ -0,0 +1,279 @@
+use crate::error::SceneError;
+use crate::graph::{ObjectGraph, ObjectId};
+use crate::hierarchy::SceneHierarchy;
+use crate::math::Matrix4;
+use crate::mesh::{child, child_f64_array};
+use crate::skin::geometry_skins;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoseType {
+    BindPose,
+    RestPose,
+    Other(String),
+}
+
+impl PoseType {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "BindPose" => PoseType::BindPose,
+            "RestPose" => PoseType::RestPose,
+            other => PoseType::Other(other.to_owned()),
+        }
+    }
+}
+
+/// A `PoseNode` entry: the matrix of one model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoseNode {
+    pub node: ObjectId,
+    // global for bind poses, local for rest poses that set `Local`
+    pub matrix: Matrix4,
+    pub local: bool,
+}
+
+/// A `Pose` object with its `PoseNode` entries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pose {
+    id: ObjectId,
+    name: String,
+    pose_type: PoseType,
+    nodes: Vec<PoseNode>,
+}
+
+impl Pose {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Pose, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if object.class() != "Pose" {
+            return Err(SceneError::UnexpectedObject(id, "Pose".to_owned()));
+        }
+
+        // the `Type` child is authoritative, the subclass attribute repeats it in most files
+        let node = object.node();
+        let pose_type = child(node, "Type").and_then(|w| w.attributes_slice().first()).and_then(|w| w.as_str()).unwrap_or_else(|| object.subclass().to_owned());
+
+        let mut nodes = Vec::new();
+        for pose_node in node.children_slice().iter().filter(|w| w.name_str() == "PoseNode") {
+            let target = child(pose_node, "Node").and_then(|w| w.attributes_slice().first()).and_then(|w| w.as_int64()).ok_or(SceneError::MissingNode("Node".to_owned()))?;
+            let values = child_f64_array(pose_node, "Matrix").ok_or(SceneError::MissingNode("Matrix".to_owned()))?;
+            let matrix = Matrix4::from_slice(&values).ok_or(SceneError::InvalidArrayLength("Matrix".to_owned(), values.len()))?;
+            let local = child(pose_node, "Local").and_then(|w| w.attributes_slice().first()).and_then(|w| w.to_f64()).map(|w| w != 0.0).unwrap_or(false);
+
+            nodes.push(PoseNode { node: target, matrix, local });
+        }
+
+        Ok(Pose { id, name: object.name().to_owned(), pose_type: PoseType::parse(&pose_type), nodes })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn pose_type(&self) -> &PoseType {
+        &self.pose_type
+    }
+
+    pub fn nodes(&self) -> &[PoseNode] {
+        &self.nodes
+    }
+
+    // the matrix stored for a model
+    pub fn matrix(&self, node: ObjectId) -> Option<Matrix4> {
+        self.nodes.iter().find(|w| w.node == node).map(|w| w.matrix)
+    }
+}
+
+// every pose of the scene, in file order
+pub fn poses(graph: &ObjectGraph) -> Result<Vec<Pose>, SceneError> {
+    let ids: Vec<ObjectId> = graph.objects_of_class("Pose").map(|w| w.id()).collect();
+    let mut poses = Vec::with_capacity(ids.len());
+
+    for id in ids {
+        poses.push(Pose::from_graph(graph, id)?);
+    }
+
+    Ok(poses)
+}
+
+pub fn bind_poses(graph: &ObjectGraph) -> Result<Vec<Pose>, SceneError> {
+    Ok(poses(graph)?.into_iter().filter(|w| w.pose_type == PoseType::BindPose).collect())
+}
+
+/// The bind matrices of the bones of one skeleton.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkeletonBindPose {
+    pub root: ObjectId,
+    // the pose covering most bones of the skeleton
+    pub pose: Option<ObjectId>,
+    // global bind matrix of every bone the pose contains, in depth-first order
+    pub matrices: Vec<(ObjectId, Matrix4)>,
+    // bones the pose does not contain
+    pub missing: Vec<ObjectId>,
+}
+
+// bind pose of the skeleton starting at `root`, see `SceneHierarchy::skeleton_roots`
+pub fn skeleton_bind_pose(hierarchy: &SceneHierarchy, poses: &[Pose], root: ObjectId) -> SkeletonBindPose {
+    let bones: Vec<ObjectId> = hierarchy.descendants(root).map(|(_, w)| w).filter(|w| w.model_type().is_skeleton()).map(|w| w.id()).collect();
+
+    // maya writes one bind pose per skinned mesh, so pick the one that knows the most bones
+    let coverage = |pose: &Pose| bones.iter().filter(|w| pose.matrix(**w).is_some()).count();
+    let pose = poses.iter().filter(|w| w.pose_type == PoseType::BindPose && coverage(w) > 0).max_by_key(|w| coverage(w));
+
+    let mut matrices = Vec::new();
+    let mut missing = Vec::new();
+
+    for bone in bones {
+        match pose.and_then(|w| w.matrix(bone)) {
+            Some(matrix) => matrices.push((bone, matrix)),
+            None => missing.push(bone),
+        }
+    }
+
+    SkeletonBindPose { root, pose: pose.map(|w| w.id), matrices, missing }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindPoseIssue {
+    // a cluster's bone appears in no bind pose
+    MissingBone { cluster: ObjectId, bone: ObjectId },
+    // a bind pose disagrees with the cluster's `TransformLink`
+    BoneMismatch { cluster: ObjectId, bone: ObjectId, pose: ObjectId, difference: f64 },
+    // a bind pose disagrees with the mesh matrix the cluster implies, `TransformLink * Transform`
+    MeshMismatch { cluster: ObjectId, mesh: ObjectId, pose: ObjectId, difference: f64 },
+}
+
+// largest difference between two matrices, element by element
+fn difference(a: &Matrix4, b: &Matrix4) -> f64 {
+    a.as_array().iter().zip(b.as_array().iter()).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max)
+}
+
+// compares every skin cluster against every bind pose containing its bone or mesh; `tolerance` is the
+// largest accepted difference of any matrix element, in scene units for the translations
+pub fn check_bind_poses(graph: &ObjectGraph, tolerance: f64) -> Result<Vec<BindPoseIssue>, SceneError> {
+    let poses = bind_poses(graph)?;
+    let mut issues = Vec::new();
+
+    let geometries: Vec<ObjectId> = graph.objects_of_class("Geometry").map(|w| w.id()).collect();
+
+    for geometry in geometries {
+        let mesh = graph.parents(geometry).into_iter().find(|w| graph.object(*w).map(|o| o.class() == "Model").unwrap_or(false));
+
+        for skin in geometry_skins(graph, geometry)? {
+            for cluster in skin.clusters() {
+                let bone = match cluster.bone() {
+                    Some(bone) => bone,
+                    None => continue,
+                };
+
+                if !poses.iter().any(|w| w.matrix(bone).is_some()) {
+                    issues.push(BindPoseIssue::MissingBone { cluster: cluster.id(), bone });
+                }
+
+                for pose in poses.iter() {
+                    if let Some(matrix) = pose.matrix(bone) {
+                        let difference = difference(&matrix, &cluster.transform_link());
+                        if difference > tolerance {
+                            issues.push(BindPoseIssue::BoneMismatch { cluster: cluster.id(), bone, pose: pose.id, difference });
+                        }
+                    }
+
+                    if let Some((mesh, matrix)) = mesh.and_then(|w| Some((w, pose.matrix(w)?))) {
+                        let difference = difference(&matrix, &cluster.mesh_bind_matrix());
+                        if difference > tolerance {
+                            issues.push(BindPoseIssue::MeshMismatch { cluster: cluster.id(), mesh, pose: pose.id, difference });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use fbx::format::{Node, Type};
+
+    fn pose_node(id: ObjectId, matrix: Matrix4) -> Node {
+        node("PoseNode", vec![], vec![node("Node", vec![Type::Int64(id)], vec![]), f64_array("Matrix", matrix.as_array())])
+    }
+
+    fn pose(id: ObjectId, pose_type: &str, nodes: Vec<Node>) -> Node {
+        let mut children = vec![text_node("Type", pose_type)];
+        children.extend(nodes);
+        object("Pose", id, pose_type, pose_type, children)
+    }
+
+    fn cluster(id: ObjectId, link: Matrix4) -> Node {
+        let transform = link.inverse().unwrap();
+        object("Deformer", id, "cluster", "Cluster", vec![f64_array("Transform", transform.as_array()), f64_array("TransformLink", link.as_array())])
+    }
+
+    // a mesh skinned to hips, spine and an unposed tail; the bind pose puts the spine one unit too high
+    fn skinned() -> ObjectGraph {
+        let (hips, spine) = (Matrix4::translation([0.0, 1.0, 0.0]), Matrix4::translation([0.0, 2.0, 0.0]));
+
+        let objects = vec![
+            object("Model", 10, "body", "Mesh", vec![]),
+            object("Geometry", 11, "body", "Mesh", vec![]),
+            object("Deformer", 12, "skin", "Skin", vec![]),
+            cluster(13, hips),
+            cluster(14, spine),
+            cluster(15, Matrix4::IDENTITY),
+            object("Model", 20, "hips", "LimbNode", vec![]),
+            object("Model", 21, "spine", "LimbNode", vec![]),
+            object("Model", 22, "tail", "LimbNode", vec![]),
+            pose(30, "BindPose", vec![pose_node(10, Matrix4::IDENTITY), pose_node(20, hips), pose_node(21, Matrix4::translation([0.0, 3.0, 0.0]))]),
+            pose(31, "RestPose", vec![pose_node(20, hips), pose_node(21, spine), pose_node(22, Matrix4::IDENTITY)]),
+            pose(32, "BindPose", vec![pose_node(20, hips)]),
+        ];
+        let connections = vec![oo(10, 0), oo(11, 10), oo(12, 11), oo(13, 12), oo(14, 12), oo(15, 12), oo(20, 0), oo(21, 20), oo(22, 20), oo(20, 13), oo(21, 14), oo(22, 15)];
+
+        graph(objects, connections)
+    }
+
+    #[test]
+    fn decodes_pose_nodes() {
+        let poses = poses(&skinned()).unwrap();
+
+        assert_eq!(poses.iter().map(|w| w.pose_type().clone()).collect::<Vec<_>>(), [PoseType::BindPose, PoseType::RestPose, PoseType::BindPose]);
+        assert_eq!(poses[0].nodes().len(), 3);
+        assert_eq!(poses[0].matrix(20), Some(Matrix4::translation([0.0, 1.0, 0.0])));
+        assert_eq!(poses[0].matrix(22), None);
+        assert_eq!(bind_poses(&skinned()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn picks_the_bind_pose_covering_most_bones() {
+        let graph = skinned();
+        let hierarchy = SceneHierarchy::from_graph(&graph).unwrap();
+        let bind_pose = skeleton_bind_pose(&hierarchy, &poses(&graph).unwrap(), 20);
+
+        assert_eq!(bind_pose.pose, Some(30));
+        assert_eq!(bind_pose.matrices.iter().map(|w| w.0).collect::<Vec<_>>(), [20, 21]);
+        assert_eq!(bind_pose.missing, [22]);
+    }
+
+    #[test]
+    fn reports_poses_disagreeing_with_clusters() {
+        let issues = check_bind_poses(&skinned(), 1e-6).unwrap();
+
+        assert_eq!(issues, [BindPoseIssue::BoneMismatch { cluster: 14, bone: 21, pose: 30, difference: 1.0 }, BindPoseIssue::MissingBone { cluster: 15, bone: 22 }]);
+    }
+
+    #[test]
+    fn rejects_malformed_pose_nodes() {
+        let broken = pose(30, "BindPose", vec![node("PoseNode", vec![], vec![node("Node", vec![Type::Int64(1)], vec![]), f64_array("Matrix", &[1.0; 12])])]);
+        assert!(matches!(Pose::from_graph(&graph(vec![broken], vec![]), 30), Err(SceneError::InvalidArrayLength(_, 12))));
+
+        let broken = pose(30, "BindPose", vec![node("PoseNode", vec![], vec![f64_array("Matrix", Matrix4::IDENTITY.as_array())])]);
+        assert!(matches!(Pose::from_graph(&graph(vec![broken], vec![]), 30), Err(SceneError::MissingNode(_))));
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+
+use fbx::format::{Node, Object, Type};
+
+#[derive(Debug, Clone)]
+pub enum PropertyValue {
+    Bool(bool),
+    Integer(i64),
+    Enum(i32),
+    Number(f64),
+    Color([f64; 3]),
+    ColorAlpha([f64; 4]),
+    Vector3([f64; 3]),
+    String(String),
+    Time(i64),
+    Blob(Vec<u8>),
+    Compound,
+    Other(Vec<Type>),
+}
+
+impl PropertyValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            PropertyValue::Bool(b) => Some(*b),
+            PropertyValue::Integer(i) => Some(*i != 0),
+            PropertyValue::Enum(i) => Some(*i != 0),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            PropertyValue::Bool(b) => Some(*b as i64),
+            PropertyValue::Integer(i) => Some(*i),
+            PropertyValue::Enum(i) => Some(*i as i64),
+            PropertyValue::Time(t) => Some(*t),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            PropertyValue::Number(f) => Some(*f),
+            PropertyValue::Integer(i) => Some(*i as f64),
+            PropertyValue::Enum(i) => Some(*i as f64),
+            PropertyValue::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    pub fn as_vector3(&self) -> Option<[f64; 3]> {
+        match self {
+            PropertyValue::Vector3(v) => Some(*v),
+            PropertyValue::Color(v) => Some(*v),
+            PropertyValue::ColorAlpha(v) => Some([v[0], v[1], v[2]]),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropertyValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_blob(&self) -> Option<&[u8]> {
+        match self {
+            PropertyValue::Blob(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PropertyFlags {
+    pub animatable: bool,
+    pub animated: bool,
+    pub user_defined: bool,
+    pub hidden: bool,
+    pub locked: bool,
+}
+
+impl PropertyFlags {
+    pub fn parse(flags: &str) -> Self {
+        PropertyFlags {
+            animatable: flags.contains('A'),
+            animated: flags.contains('+'),
+            user_defined: flags.contains('U'),
+            hidden: flags.contains('H'),
+            locked: flags.contains('L'),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Property {
+    name: String,
+    type_name: String,
+    label: String,
+    flags: PropertyFlags,
+    value: PropertyValue,
+    inherited: bool,
+}
+
+impl Property {
+    pub fn new(name: String, type_name: String, label: String, flags: PropertyFlags, value: PropertyValue) -> Self {
+        Property { name, type_name, label, flags, value, inherited: false }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // declared type, e.g. `Lcl Translation`, `ColorRGB` or `KString`
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn flags(&self) -> PropertyFlags {
+        self.flags
+    }
+
+    pub fn value(&self) -> &PropertyValue {
+        &self.value
+    }
+
+    // true if the value comes from the `Definitions` template rather than the object itself
+    pub fn is_inherited(&self) -> bool {
+        self.inherited
+    }
+}
+
+fn number(value: &Type) -> Option<f64> {
+    match value {
+        Type::Int64(i) => Some(*i as f64),
+        Type::Float64(f) => Some(*f),
+        value => value.to_f64(),
+    }
+}
+
+fn numbers<const N: usize>(values: &[Type]) -> Option<[f64; N]> {
+    let mut result = [0.0; N];
+
+    for (i, slot) in result.iter_mut().enumerate() {
+        *slot = number(values.get(i)?)?;
+    }
+
+    Some(result)
+}
+
+fn integer(values: &[Type]) -> Option<i64> {
+    match values.first()? {
+        Type::Bool(b) => Some(*b as i64),
+        Type::Float32(f) => Some(*f as i64),
+        Type::Float64(f) => Some(*f as i64),
+        value => value.to_i64(),
+    }
+}
+
+fn decode_value(type_name: &str, values: &[Type], node: &Node) -> PropertyValue {
+    let decoded = match type_name.to_ascii_lowercase().as_str() {
+        "bool" | "boolean" => integer(values).map(|w| PropertyValue::Bool(w != 0)),
+        "int" | "integer" | "ulonglong" | "longlong" | "visibility inheritance" => integer(values).map(PropertyValue::Integer),
+        "enum" => integer(values).map(|w| PropertyValue::Enum(w as i32)),
+        "double" | "number" | "float" | "visibility" | "fieldofview" | "fieldofviewx" | "fieldofviewy" => values.first().and_then(number).map(PropertyValue::Number),
+        "color" | "colorrgb" => numbers::<3>(values).map(PropertyValue::Color),
+        "colorandalpha" | "colorrgba" => numbers::<4>(values).map(PropertyValue::ColorAlpha),
+        "vector" | "vector3" | "vector3d" | "lcl translation" | "lcl rotation" | "lcl scaling" => numbers::<3>(values).map(PropertyValue::Vector3),
+        "kstring" | "string" | "datetime" | "url" | "xrefurl" => values.first().and_then(|w| w.as_str()).map(PropertyValue::String),
+        "ktime" | "time" => values.first().and_then(|w| w.to_i64()).map(PropertyValue::Time),
+        "blob" => {
+            let attribute = values.iter().find_map(|w| w.as_binary());
+            let child = node.children_slice().iter().find_map(|w| w.attributes_slice().first().and_then(|a| a.as_binary()));
+            Some(PropertyValue::Blob(attribute.or(child).unwrap_or_default()))
+        }
+        "compound" => Some(PropertyValue::Compound),
+        _ => None,
+    };
+
+    if let Some(value) = decoded {
+        return value;
+    }
+
+    // unknown (or mistyped) declarations are decoded from the shape of their values
+    match values {
+        [] => PropertyValue::Compound,
+        [Type::String(s)] => PropertyValue::String(s.to_owned()),
+        [Type::Float32(_) | Type::Float64(_)] => PropertyValue::Number(number(&values[0]).unwrap_or_default()),
+        [Type::Int16(_) | Type::Int32(_) | Type::Int64(_)] => PropertyValue::Integer(integer(values).unwrap_or_default()),
+        [Type::Bool(b)] => PropertyValue::Bool(*b),
+        [_, _, _] => numbers::<3>(values).map(PropertyValue::Vector3).unwrap_or_else(|| PropertyValue::Other(values.to_vec())),
+        _ => PropertyValue::Other(values.to_vec()),
+    }
+}
+
+// decodes a `P` entry of a `Properties70` node
+pub fn parse_property(node: &Node) -> Option<Property> {
+    let attributes = node.attributes_slice();
+    let text = |i: usize| attributes.get(i).and_then(|w| w.as_str());
+
+    let name = text(0)?;
+    let type_name = text(1).unwrap_or_default();
+    let label = text(2).unwrap_or_default();
+    let flags = PropertyFlags::parse(&text(3).unwrap_or_default());
+    let values = attributes.get(4..).unwrap_or_default();
+    let value = decode_value(&type_name, values, node);
+
+    Some(Property::new(name, type_name, label, flags, value))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Properties {
+    properties: Vec<Property>,
+    indices: HashMap<String, usize>,
+}
+
+impl Properties {
+    pub fn new() -> Self {
+        Properties::default()
+    }
+
+    // parses the `Properties70` child of an object node
+    pub fn from_node(node: &Node) -> Self {
+        match node.children_slice().iter().find(|w| w.name_str() == "Properties70") {
+            Some(properties) => Properties::parse(properties),
+            None => Properties::new(),
+        }
+    }
+
+    // parses the `P` children of a `Properties70` node
+    pub fn parse(properties70: &Node) -> Self {
+        let mut properties = Properties::new();
+
+        for property in properties70.children_slice().iter().filter(|w| w.name_str() == "P").filter_map(parse_property) {
+            properties.insert(property);
+        }
+
+        properties
+    }
+
+    pub fn insert(&mut self, property: Property) {
+        match self.indices.get(&property.name) {
+            Some(&i) => self.properties[i] = property,
+            None => {
+                self.indices.insert(property.name.clone(), self.properties.len());
+                self.properties.push(property);
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Property> {
+        self.properties.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.properties.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Property> {
+        self.indices.get(name).map(|w| &self.properties[*w])
+    }
+
+    pub fn value(&self, name: &str) -> Option<&PropertyValue> {
+        self.get(name).map(|w| w.value())
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.value(name).and_then(|w| w.as_bool())
+    }
+
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        self.value(name).and_then(|w| w.as_i64())
+    }
+
+    pub fn get_f64(&self, name: &str) -> Option<f64> {
+        self.value(name).and_then(|w| w.as_f64())
+    }
+
+    pub fn get_vector3(&self, name: &str) -> Option<[f64; 3]> {
+        self.value(name).and_then(|w| w.as_vector3())
+    }
+
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.value(name).and_then(|w| w.as_str())
+    }
+
+    // returns the properties with every entry of `template` that is not set on `self` added as inherited
+    pub fn with_template(&self, template: &Properties) -> Properties {
+        let mut properties = self.clone();
+
+        for property in template.iter() {
+            if properties.get(&property.name).is_none() {
+                let mut inherited = property.clone();
+                inherited.inherited = true;
+                properties.insert(inherited);
+            }
+        }
+
+        properties
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PropertyTemplate {
+    name: String,
+    properties: Properties,
+}
+
+impl PropertyTemplate {
+    // template name, e.g. `FbxNode` or `FbxSurfacePhong`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+}
+
+/// The `Definitions/ObjectType/PropertyTemplate` defaults, keyed by object class and template name.
+#[derive(Debug, Clone, Default)]
+pub struct PropertyTemplates {
+    // templates of each class in file order, e.g. `FbxCamera`, `FbxLight` and `FbxSkeleton` for `NodeAttribute`
+    templates: HashMap<String, Vec<PropertyTemplate>>,
+}
+
+impl PropertyTemplates {
+    pub fn get(&self, class: &str, name: &str) -> Option<&PropertyTemplate> {
+        self.class_templates(class).iter().find(|w| w.name == name)
+    }
+
+    pub fn class_templates(&self, class: &str) -> &[PropertyTemplate] {
+        self.templates.get(class).map(|w| w.as_slice()).unwrap_or_default()
+    }
+
+    // the template an object inherits from; node attributes, geometries and deformers pick theirs by subclass
+    // and materials by `ShadingModel`, other classes have a single template
+    pub fn select(&self, class: &str, subclass: &str, node: &Node) -> Option<&PropertyTemplate> {
+        match class {
+            "NodeAttribute" | "Geometry" | "Deformer" => {
+                let name = match subclass {
+                    "LimbNode" | "Limb" | "Root" => "FbxSkeleton".to_owned(),
+                    subclass => format!("Fbx{}", subclass),
+                };
+
+                self.get(class, &name)
+            }
+            "Material" => {
+                let shading = node.children_slice().iter().find(|w| w.name_str() == "ShadingModel").and_then(|w| w.attributes_slice().first()).and_then(|w| w.as_str());
+
+                match shading.map(|w| w.to_ascii_lowercase()).as_deref() {
+                    Some("lambert") => self.get(class, "FbxSurfaceLambert"),
+                    _ => self.get(class, "FbxSurfacePhong"),
+                }
+            }
+            _ => self.class_templates(class).first(),
+        }
+    }
+}
+
+impl From<&Object> for PropertyTemplates {
+    fn from(object: &Object) -> Self {
+        let mut templates = PropertyTemplates::default();
+        let definitions = object.children_slice().iter().filter(|w| w.name_str() == "Definitions");
+
+        for object_type in definitions.flat_map(|w| w.children_slice()).filter(|w| w.name_str() == "ObjectType") {
+            let class = match object_type.attributes_slice().first().and_then(|w| w.as_str()) {
+                Some(class) => class,
+                None => continue,
+            };
+
+            let entry: &mut Vec<PropertyTemplate> = templates.templates.entry(class).or_default();
+
+            for template in object_type.children_slice().iter().filter(|w| w.name_str() == "PropertyTemplate") {
+                let name = template.attributes_slice().first().and_then(|w| w.as_str()).unwrap_or_default();
+
+                // a repeated template name keeps its first declaration
+                if !entry.iter().any(|w| w.name == name) {
+                    entry.push(PropertyTemplate { name, properties: Properties::from_node(template) });
+                }
+            }
+        }
+
+        templates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::ObjectGraph;
+    use crate::testing::*;
+
+    #[test]
+    fn decodes_declared_types() {
+        let node = properties70(vec![
+            int_property("Visible", "bool", 1),
+            int_property("Order", "enum", 2),
+            vector_property("Lcl Translation", "Lcl Translation", [1.0, 2.0, 3.0]),
+            property("Path", "KString", vec![string("a.png")]),
+            property("Start", "KTime", vec![Type::Int64(46186158000)]),
+            property("Odd", "Unknown", vec![Type::Float32(0.5)]),
+        ]);
+        let properties = Properties::parse(&node);
+
+        assert_eq!(properties.get_bool("Visible"), Some(true));
+        assert_eq!(properties.get_i64("Order"), Some(2));
+        assert_eq!(properties.get_vector3("Lcl Translation"), Some([1.0, 2.0, 3.0]));
+        assert_eq!(properties.get_str("Path"), Some("a.png"));
+        assert_eq!(properties.get_i64("Start"), Some(46186158000));
+        assert_eq!(properties.get_f64("Odd"), Some(0.5));
+        assert!(properties.get("Visible").unwrap().flags().animatable);
+    }
+
+    fn template(name: &str, children: Vec<Node>) -> Node {
+        node("PropertyTemplate", vec![string(name)], vec![properties70(children)])
+    }
+
+    fn object_type(class: &str, templates: Vec<Node>) -> Node {
+        node("ObjectType", vec![string(class)], templates)
+    }
+
+    #[test]
+    fn templates_are_selected_per_subclass_and_shading_model() {
+        let definitions = vec![
+            object_type("NodeAttribute", vec![template("FbxSkeleton", vec![vector_property("Color", "ColorRGB", [0.8; 3])]), template("FbxLight", vec![vector_property("Color", "ColorRGB", [1.0; 3]), number_property("Intensity", 100.0)])]),
+            object_type("Material", vec![template("FbxSurfaceLambert", vec![number_property("DiffuseFactor", 1.0)]), template("FbxSurfacePhong", vec![number_property("DiffuseFactor", 1.0), number_property("Shininess", 20.0)])]),
+            object_type("Model", vec![template("FbxNode", vec![int_property("InheritType", "enum", 0)])]),
+        ];
+        let objects = vec![
+            object("NodeAttribute", 1, "light", "Light", vec![properties70(vec![number_property("Intensity", 50.0)])]),
+            object("NodeAttribute", 2, "bone", "LimbNode", vec![]),
+            object("NodeAttribute", 3, "null", "Null", vec![]),
+            object("Material", 4, "matte", "", vec![text_node("ShadingModel", "lambert")]),
+            object("Material", 5, "shiny", "", vec![text_node("ShadingModel", "Phong")]),
+            object("Model", 6, "model", "Mesh", vec![]),
+        ];
+        let graph = ObjectGraph::from(&scene(definitions, objects, vec![]));
+
+        let light = graph.properties(1).unwrap();
+        assert_eq!(light.get_vector3("Color"), Some([1.0; 3]));
+        assert_eq!(light.get_f64("Intensity"), Some(50.0));
+        assert!(light.get("Color").unwrap().is_inherited() && !light.get("Intensity").unwrap().is_inherited());
+
+        assert_eq!(graph.properties(2).unwrap().get_vector3("Color"), Some([0.8; 3]));
+        assert!(graph.properties(3).unwrap().is_empty());
+        assert_eq!(graph.properties(4).unwrap().get_f64("Shininess"), None);
+        assert_eq!(graph.properties(5).unwrap().get_f64("Shininess"), Some(20.0));
+        assert_eq!(graph.properties(6).unwrap().get_i64("InheritType"), Some(0));
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use crate::error::SceneError;
+use crate::layer::LayerElements;
+use crate::math::{add, cross, Vector3};
+use crate::mesh::Mesh;
+
+// normal of a (possibly non planar) polygon by Newell's method, not normalized
+fn polygon_normal(points: &[Vector3]) -> Vector3 {
+    let mut normal = [0.0; 3];
+
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        normal = add(normal, cross(*a, b));
+    }
+
+    normal
+}
+
+fn cross_2d(o: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+fn in_triangle(p: [f64; 2], a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> bool {
+    let (d1, d2, d3) = (cross_2d(a, b, p), cross_2d(b, c, p), cross_2d(c, a, p));
+    let negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(negative && positive)
+}
+
+/// Splits a polygon into triangles by ear clipping, returned as indices into `points` with the polygon's winding.
+pub fn triangulate_polygon(points: &[Vector3]) -> Vec<[usize; 3]> {
+    match points.len() {
+        0..=2 => return Vec::new(),
+        3 => return vec![[0, 1, 2]],
+        _ => {}
+    }
+
+    // project onto the plane of the axis the normal points along the most
+    let normal = polygon_normal(points);
+    let axis = (0..3).max_by(|a, b| normal[*a].abs().total_cmp(&normal[*b].abs())).unwrap_or(2);
+    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+    let projected: Vec<[f64; 2]> = points.iter().map(|w| [w[u], w[v]]).collect();
+
+    // makes counter clockwise polygons have positive corners
+    let orientation = if normal[axis] < 0.0 { -1.0 } else { 1.0 };
+
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut triangles = Vec::with_capacity(points.len() - 2);
+
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let is_ear = |i: usize| {
+            let (a, b, c) = (remaining[(i + count - 1) % count], remaining[i], remaining[(i + 1) % count]);
+            let (pa, pb, pc) = (projected[a], projected[b], projected[c]);
+
+            if orientation * cross_2d(pa, pb, pc) <= 0.0 {
+                return false;
+            }
+
+            // no other corner may lie in the ear, corners at the same place as the ear's do not count
+            !remaining.iter().any(|w| {
+                let p = projected[*w];
+                *w != a && *w != b && *w != c && p != pa && p != pb && p != pc && in_triangle(p, pa, pb, pc)
+            })
+        };
+
+        // degenerate polygons have no ear, their first corner is clipped so the loop always ends
+        let ear = (0..count).find(|w| is_ear(*w)).unwrap_or(0);
+
+        triangles.push([remaining[(ear + count - 1) % count], remaining[ear], remaining[(ear + 1) % count]]);
+        remaining.remove(ear);
+    }
+
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderVertex {
+    pub position: Vector3,
+    pub normal: Option<Vector3>,
+    // one slot per UV set of the mesh, see `RenderMesh::uv_sets`; `None` for sets without a value per polygon vertex
+    pub uvs: Vec<Option<[f64; 2]>>,
+    pub color: Option<[f64; 4]>,
+}
+
+impl RenderVertex {
+    // bit patterns of every component, so only exactly identical vertices are welded
+    fn key(&self) -> Vec<u64> {
+        let mut key: Vec<u64> = self.position.iter().map(|w| w.to_bits()).collect();
+
+        if let Some(normal) = self.normal {
+            key.extend(normal.iter().map(|w| w.to_bits()));
+        }
+
+        for uv in self.uvs.iter() {
+            match uv {
+                Some(uv) => key.extend(uv.iter().map(|w| w.to_bits())),
+                None => key.push(u64::MAX),
+            }
+        }
+
+        if let Some(color) = self.color {
+            key.extend(color.iter().map(|w| w.to_bits()));
+        }
+
+        key
+    }
+}
+
+/// Triangles of one material, as indices into the vertices of the `RenderMesh`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Submesh {
+    pub material: i32,
+    pub indices: Vec<u32>,
+}
+
+/// Indexed triangle lists with welded vertices, split by material.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderMesh {
+    vertices: Vec<RenderVertex>,
+    control_points: Vec<u32>,
+    submeshes: Vec<Submesh>,
+    uv_sets: Vec<String>,
+}
+
+impl RenderMesh {
+    // uses the first normal, color and material element and every UV set, in layer order
+    pub fn from_mesh(mesh: &Mesh, layers: &LayerElements) -> Result<RenderMesh, SceneError> {
+        let normals = match layers.first("LayerElementNormal") {
+            Some(element) => Some(element.decode::<3>(mesh)?),
+            None => None,
+        };
+
+        let colors = match layers.first("LayerElementColor") {
+            Some(element) => Some(element.decode::<4>(mesh)?),
+            None => None,
+        };
+
+        let materials = match layers.first("LayerElementMaterial") {
+            Some(element) => Some(element.decode_i32(mesh)?),
+            None => None,
+        };
+
+        let mut uvs = Vec::new();
+        let mut uv_sets = Vec::new();
+        for element in layers.uv_sets() {
+            uvs.push(element.decode::<2>(mesh)?);
+            uv_sets.push(element.name().to_owned());
+        }
+
+        // elements without mapping decode to nothing and are left out, UV sets keep their slot
+        let count = mesh.polygon_vertex_count();
+        let normals = normals.filter(|w| w.len() == count);
+        let colors = colors.filter(|w| w.len() == count);
+        let materials = materials.filter(|w| w.len() == count);
+        let uvs: Vec<Option<Vec<[f64; 2]>>> = uvs.into_iter().map(|w| Some(w).filter(|w| w.len() == count)).collect();
+
+        let mut render = RenderMesh { vertices: Vec::new(), control_points: Vec::new(), submeshes: Vec::new(), uv_sets };
+        let mut welded: HashMap<Vec<u64>, u32> = HashMap::new();
+        let mut submeshes: HashMap<i32, usize> = HashMap::new();
+
+        for (polygon, corners) in mesh.polygons().enumerate() {
+            let start = mesh.polygon_start(polygon);
+            let points: Vec<Vector3> = corners.iter().map(|w| mesh.positions()[*w as usize]).collect();
+            let material = materials.as_ref().map(|w| w[start]).unwrap_or_default();
+
+            let submesh = *submeshes.entry(material).or_insert_with(|| {
+                render.submeshes.push(Submesh { material, indices: Vec::new() });
+                render.submeshes.len() - 1
+            });
+
+            for triangle in triangulate_polygon(&points) {
+                for corner in triangle {
+                    let polygon_vertex = start + corner;
+                    let vertex = RenderVertex {
+                        position: points[corner],
+                        normal: normals.as_ref().map(|w| w[polygon_vertex]),
+                        uvs: uvs.iter().map(|w| w.as_ref().map(|w| w[polygon_vertex])).collect(),
+                        color: colors.as_ref().map(|w| w[polygon_vertex]),
+                    };
+
+                    // vertices of different control points stay apart, they may be deformed differently
+                    let mut key = vertex.key();
+                    key.push(corners[corner] as u64);
+
+                    let index = *welded.entry(key).or_insert_with(|| {
+                        render.vertices.push(vertex);
+                        render.control_points.push(corners[corner]);
+                        render.vertices.len() as u32 - 1
+                    });
+
+                    render.submeshes[submesh].indices.push(index);
+                }
+            }
+        }
+
+        Ok(render)
+    }
+
+    pub fn vertices(&self) -> &[RenderVertex] {
+        &self.vertices
+    }
+
+    // control point each vertex was made from, for applying skinning and blend shapes
+    pub fn control_points(&self) -> &[u32] {
+        &self.control_points
+    }
+
+    // names of the UV sets in the order of `RenderVertex::uvs`, as textures reference them through `UVSet`
+    pub fn uv_sets(&self) -> &[String] {
+        &self.uv_sets
+    }
+
+    // submeshes in the order their material first appears
+    pub fn submeshes(&self) -> &[Submesh] {
+        &self.submeshes
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.submeshes.iter().map(|w| w.indices.len() / 3).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use fbx::format::{Node, Type};
+
+    // area of the projected triangles, equal to the polygon's for a valid triangulation
+    fn area(points: &[Vector3], triangles: &[[usize; 3]]) -> f64 {
+        triangles.iter().map(|[a, b, c]| cross_2d([points[*a][0], points[*a][1]], [points[*b][0], points[*b][1]], [points[*c][0], points[*c][1]]) / 2.0).sum()
+    }
+
+    #[test]
+    fn triangulates_concave_polygons() {
+        // the reflex corner of the dart is at index 2, a fan from corner 0 would leave the polygon
+        let dart = [[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [1.0, 0.5, 0.0], [2.0, 2.0, 0.0]];
+        let triangles = triangulate_polygon(&dart);
+        assert_eq!(triangles.len(), 2);
+        assert_close(area(&dart, &triangles), 1.0, 1e-12);
+        assert!(triangles.iter().all(|[a, b, c]| cross_2d([dart[*a][0], dart[*a][1]], [dart[*b][0], dart[*b][1]], [dart[*c][0], dart[*c][1]]) > 0.0));
+
+        let l_shape = [[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [2.0, 1.0, 0.0], [1.0, 1.0, 0.0], [1.0, 2.0, 0.0], [0.0, 2.0, 0.0]];
+        let triangles = triangulate_polygon(&l_shape);
+        assert_eq!(triangles.len(), 4);
+        assert_close(area(&l_shape, &triangles), 3.0, 1e-12);
+
+        // clockwise input keeps its winding
+        let reversed: Vec<Vector3> = l_shape.iter().rev().copied().collect();
+        assert_close(area(&reversed, &triangulate_polygon(&reversed)), -3.0, 1e-12);
+    }
+
+    fn uv_element(index: i32, name: &str, mapping: &str, values: &[f64]) -> Node {
+        node("LayerElementUV", vec![Type::Int32(index)], vec![text_node("Name", name), text_node("MappingInformationType", mapping), text_node("ReferenceInformationType", "Direct"), f64_array("UV", values)])
+    }
+
+    // a quad split into two triangles sharing the control points 1 and 2
+    fn quad(elements: Vec<Node>) -> RenderMesh {
+        let mut children = vec![f64_array("Vertices", &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0]), i32_array("PolygonVertexIndex", &[0, 1, -3, 2, 1, -4])];
+        children.extend(elements);
+        let geometry = object("Geometry", 1, "quad", "Mesh", children);
+        RenderMesh::from_mesh(&Mesh::from_node(&geometry).unwrap(), &LayerElements::from_node(&geometry).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn welds_identical_corners() {
+        let render = quad(vec![uv_element(0, "map", "ByControlPoint", &[0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0])]);
+        assert_eq!(render.vertices().len(), 4);
+        assert_eq!(render.control_points(), [0, 1, 2, 3]);
+        assert_eq!(render.triangle_count(), 2);
+
+        // a UV seam along the shared edge keeps its corners apart
+        let seam = quad(vec![uv_element(0, "map", "ByPolygonVertex", &[0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.5, 1.0, 1.0, 0.5, 1.0, 1.0])]);
+        assert_eq!(seam.vertices().len(), 6);
+        assert_eq!(seam.control_points(), [0, 1, 2, 2, 1, 3]);
+    }
+
+    #[test]
+    fn uv_sets_keep_their_slots() {
+        let render = quad(vec![uv_element(0, "unmapped", "NoMapping", &[]), uv_element(1, "map", "ByControlPoint", &[0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0])]);
+
+        assert_eq!(render.uv_sets(), ["unmapped", "map"]);
+        assert_eq!(render.vertices()[3].uvs, [None, Some([1.0, 1.0])]);
+    }
+}
@@ -0,0 +1,219 @@
+use fbx::format::Object;
+
+use crate::error::SceneError;
+use crate::math::Matrix4;
+use crate::properties::Properties;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    // `None` for values other than 0, 1 and 2
+    pub fn from_fbx(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(Axis::X),
+            1 => Some(Axis::Y),
+            2 => Some(Axis::Z),
+            _ => None,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        match self {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedAxis {
+    pub axis: Axis,
+    pub positive: bool,
+}
+
+impl SignedAxis {
+    pub fn new(axis: Axis, positive: bool) -> Self {
+        SignedAxis { axis, positive }
+    }
+
+    pub fn sign(self) -> i32 {
+        if self.positive { 1 } else { -1 }
+    }
+
+    fn vector(self) -> [f64; 3] {
+        let mut v = [0.0; 3];
+        v[self.axis.index()] = self.sign() as f64;
+        v
+    }
+}
+
+/// Orientation of a scene given by its up, front and coord (right) axes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AxisSystem {
+    pub up: SignedAxis,
+    pub front: SignedAxis,
+    pub coord: SignedAxis,
+}
+
+impl AxisSystem {
+    // Y-up, right-handed (Maya, Unity after import, glTF)
+    pub const Y_UP: AxisSystem = AxisSystem {
+        up: SignedAxis { axis: Axis::Y, positive: true },
+        front: SignedAxis { axis: Axis::Z, positive: true },
+        coord: SignedAxis { axis: Axis::X, positive: true },
+    };
+
+    // Z-up, right-handed (Blender, 3ds Max)
+    pub const Z_UP: AxisSystem = AxisSystem {
+        up: SignedAxis { axis: Axis::Z, positive: true },
+        front: SignedAxis { axis: Axis::Y, positive: false },
+        coord: SignedAxis { axis: Axis::X, positive: true },
+    };
+
+    pub fn new(up: SignedAxis, front: SignedAxis, coord: SignedAxis) -> Self {
+        AxisSystem { up, front, coord }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.up.axis != self.front.axis && self.up.axis != self.coord.axis && self.front.axis != self.coord.axis
+    }
+
+    // matrix whose columns are the coord, up and front directions
+    fn basis(&self) -> Matrix4 {
+        let mut m = Matrix4::IDENTITY;
+
+        for (column, axis) in [self.coord, self.up, self.front].iter().enumerate() {
+            let v = axis.vector();
+            for (row, value) in v.iter().enumerate() {
+                m.set(row, column, *value);
+            }
+        }
+
+        m
+    }
+
+    // rotation (or reflection) taking vectors of this axis system into `target`
+    pub fn conversion_to(&self, target: &AxisSystem) -> Result<Matrix4, SceneError> {
+        if !self.is_valid() || !target.is_valid() {
+            return Err(SceneError::InvalidAxisSystem);
+        }
+
+        Ok(target.basis() * self.basis().transpose())
+    }
+}
+
+/// The `GlobalSettings` node; unit scale factors are in centimeters per scene unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobalSettings {
+    pub axis_system: AxisSystem,
+    pub original_up_axis: Option<SignedAxis>,
+    pub unit_scale_factor: f64,
+    pub original_unit_scale_factor: f64,
+}
+
+impl Default for GlobalSettings {
+    fn default() -> Self {
+        GlobalSettings {
+            axis_system: AxisSystem::Y_UP,
+            original_up_axis: None,
+            unit_scale_factor: 1.0,
+            original_unit_scale_factor: 1.0,
+        }
+    }
+}
+
+impl GlobalSettings {
+    pub fn from_properties(properties: &Properties) -> Result<Self, SceneError> {
+        let defaults = GlobalSettings::default();
+        let read_axis = |name: &str, value: i64| Axis::from_fbx(value).ok_or_else(|| SceneError::InvalidAxis(name.to_owned(), value));
+        let axis = |name: &str, sign: &str, default: SignedAxis| -> Result<SignedAxis, SceneError> {
+            let axis = match properties.get_i64(name) {
+                Some(value) => read_axis(name, value)?,
+                None => default.axis,
+            };
+            let positive = properties.get_i64(sign).map(|w| w >= 0).unwrap_or(default.positive);
+            Ok(SignedAxis::new(axis, positive))
+        };
+
+        // -1 stands for an unknown original up axis
+        let original_up_axis = match properties.get_i64("OriginalUpAxis") {
+            Some(axis) if axis >= 0 => {
+                let positive = properties.get_i64("OriginalUpAxisSign").map(|w| w >= 0).unwrap_or(true);
+                Some(SignedAxis::new(read_axis("OriginalUpAxis", axis)?, positive))
+            }
+            _ => None,
+        };
+
+        Ok(GlobalSettings {
+            axis_system: AxisSystem::new(
+                axis("UpAxis", "UpAxisSign", defaults.axis_system.up)?,
+                axis("FrontAxis", "FrontAxisSign", defaults.axis_system.front)?,
+                axis("CoordAxis", "CoordAxisSign", defaults.axis_system.coord)?,
+            ),
+            original_up_axis,
+            unit_scale_factor: properties.get_f64("UnitScaleFactor").unwrap_or(defaults.unit_scale_factor),
+            original_unit_scale_factor: properties.get_f64("OriginalUnitScaleFactor").unwrap_or(defaults.original_unit_scale_factor),
+        })
+    }
+
+    // reads the top level `GlobalSettings` node, falling back to the FBX defaults if it is missing
+    pub fn read(object: &Object) -> Result<Self, SceneError> {
+        match object.children_slice().iter().find(|w| w.name_str() == "GlobalSettings") {
+            Some(node) => GlobalSettings::from_properties(&Properties::from_node(node)),
+            None => Ok(GlobalSettings::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use fbx::format::Type;
+
+    fn settings(properties: Vec<fbx::format::Node>) -> Result<GlobalSettings, SceneError> {
+        GlobalSettings::from_properties(&Properties::from_node(&node("GlobalSettings", vec![], vec![properties70(properties)])))
+    }
+
+    #[test]
+    fn reads_axes_and_units() {
+        let settings = settings(vec![
+            int_property("UpAxis", "int", 2),
+            int_property("FrontAxis", "int", 1),
+            int_property("FrontAxisSign", "int", -1),
+            int_property("CoordAxis", "int", 0),
+            int_property("OriginalUpAxis", "int", -1),
+            property("UnitScaleFactor", "double", vec![Type::Float64(100.0)]),
+        ]).unwrap();
+
+        assert_eq!(settings.axis_system, AxisSystem::Z_UP);
+        assert_eq!(settings.original_up_axis, None);
+        assert_eq!(settings.unit_scale_factor, 100.0);
+        assert_eq!(settings.original_unit_scale_factor, 1.0);
+    }
+
+    #[test]
+    fn rejects_invalid_axes() {
+        assert!(matches!(settings(vec![int_property("UpAxis", "int", 3)]), Err(SceneError::InvalidAxis(name, 3)) if name == "UpAxis"));
+        assert!(matches!(settings(vec![int_property("OriginalUpAxis", "int", 7)]), Err(SceneError::InvalidAxis(name, 7)) if name == "OriginalUpAxis"));
+
+        let same = AxisSystem::new(SignedAxis::new(Axis::Y, true), SignedAxis::new(Axis::Y, true), SignedAxis::new(Axis::X, true));
+        assert!(matches!(same.conversion_to(&AxisSystem::Y_UP), Err(SceneError::InvalidAxisSystem)));
+    }
+
+    #[test]
+    fn converts_z_up_vectors_to_y_up() {
+        let m = AxisSystem::Z_UP.conversion_to(&AxisSystem::Y_UP).unwrap();
+
+        assert_eq!(m.transform_vector([0.0, 0.0, 1.0]), [0.0, 1.0, 0.0]);
+        assert_eq!(m.transform_vector([0.0, -1.0, 0.0]), [0.0, 0.0, 1.0]);
+        assert_eq!(m.transform_vector([1.0, 0.0, 0.0]), [1.0, 0.0, 0.0]);
+        assert!(AxisSystem::Y_UP.conversion_to(&AxisSystem::Y_UP).unwrap().approx_eq(&Matrix4::IDENTITY, 0.0));
+    }
+}
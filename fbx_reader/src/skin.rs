@@ -0,0 +1,307 @@
+use crate::error::SceneError;
+use crate::graph::{is_class, ObjectGraph, ObjectId};
+use crate::math::Matrix4;
+use crate::mesh::{child, child_f64_array, child_i32_array};
+
+fn matrix(graph: &ObjectGraph, id: ObjectId, name: &str) -> Result<Option<Matrix4>, SceneError> {
+    let node = graph.object(id).ok_or(SceneError::MissingObject(id))?.node();
+
+    match child_f64_array(node, name) {
+        Some(values) => Matrix4::from_slice(&values).map(Some).ok_or(SceneError::InvalidArrayLength(name.to_owned(), values.len())),
+        None => Ok(None),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkinningType {
+    Linear,
+    DualQuaternion,
+    Blend,
+    Rigid,
+}
+
+impl SkinningType {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "DualQuaternion" => SkinningType::DualQuaternion,
+            "Blend" => SkinningType::Blend,
+            "Rigid" => SkinningType::Rigid,
+            _ => SkinningType::Linear,
+        }
+    }
+}
+
+/// A `Deformer` of subclass `Cluster`: the control points one bone moves and how strongly.
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    id: ObjectId,
+    name: String,
+    bone: Option<ObjectId>,
+    indices: Vec<i32>,
+    weights: Vec<f64>,
+    transform: Matrix4,
+    transform_link: Matrix4,
+}
+
+impl Cluster {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Cluster, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if !is_class(graph, id, "Deformer", "Cluster") {
+            return Err(SceneError::UnexpectedObject(id, "Deformer::Cluster".to_owned()));
+        }
+
+        // clusters without influences may leave both arrays out
+        let indices = child_i32_array(object.node(), "Indexes").unwrap_or_default();
+        let weights = child_f64_array(object.node(), "Weights").unwrap_or_default();
+
+        if indices.len() != weights.len() {
+            return Err(SceneError::InvalidArrayLength("Weights".to_owned(), weights.len()));
+        }
+
+        let bone = graph.children(id).into_iter().find(|w| graph.object(*w).map(|o| o.class() == "Model").unwrap_or(false));
+
+        Ok(Cluster {
+            id,
+            name: object.name().to_owned(),
+            bone,
+            indices,
+            weights,
+            transform: matrix(graph, id, "Transform")?.unwrap_or(Matrix4::IDENTITY),
+            transform_link: matrix(graph, id, "TransformLink")?.unwrap_or(Matrix4::IDENTITY),
+        })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // the bone `Model` the cluster is linked to
+    pub fn bone(&self) -> Option<ObjectId> {
+        self.bone
+    }
+
+    // control point indices, parallel to `weights`
+    pub fn indices(&self) -> &[i32] {
+        &self.indices
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    // `Transform`, taking the mesh from its space at bind time into the bone's space
+    pub fn transform(&self) -> Matrix4 {
+        self.transform
+    }
+
+    // `TransformLink`, the bone's global transform at bind time
+    pub fn transform_link(&self) -> Matrix4 {
+        self.transform_link
+    }
+
+    // the matrix renderers call the inverse bind matrix, mesh space to bone space
+    pub fn inverse_bind_matrix(&self) -> Matrix4 {
+        self.transform
+    }
+
+    // the mesh's global transform at bind time
+    pub fn mesh_bind_matrix(&self) -> Matrix4 {
+        self.transform_link * self.transform
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Influence {
+    // index into the clusters of the skin
+    pub cluster: usize,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InfluenceOptions {
+    pub normalize: bool,
+    pub max_influences: Option<usize>,
+    // influences at or below this weight are dropped
+    pub min_weight: f64,
+}
+
+impl InfluenceOptions {
+    pub fn new(max_influences: Option<usize>) -> Self {
+        InfluenceOptions { normalize: true, max_influences, min_weight: 0.0 }
+    }
+}
+
+impl Default for InfluenceOptions {
+    fn default() -> Self {
+        InfluenceOptions::new(None)
+    }
+}
+
+/// A `Deformer` of subclass `Skin` with its clusters.
+#[derive(Debug, Clone)]
+pub struct Skin {
+    id: ObjectId,
+    name: String,
+    skinning_type: SkinningType,
+    clusters: Vec<Cluster>,
+}
+
+impl Skin {
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<Skin, SceneError> {
+        let object = graph.object(id).ok_or(SceneError::MissingObject(id))?;
+
+        if !is_class(graph, id, "Deformer", "Skin") {
+            return Err(SceneError::UnexpectedObject(id, "Deformer::Skin".to_owned()));
+        }
+
+        let skinning_type = child(object.node(), "SkinningType").and_then(|w| w.attributes_slice().first()).and_then(|w| w.as_str());
+
+        let mut clusters = Vec::new();
+        for cluster in graph.children(id).into_iter().filter(|w| is_class(graph, *w, "Deformer", "Cluster")) {
+            clusters.push(Cluster::from_graph(graph, cluster)?);
+        }
+
+        Ok(Skin { id, name: object.name().to_owned(), skinning_type: SkinningType::parse(&skinning_type.unwrap_or_default()), clusters })
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn skinning_type(&self) -> SkinningType {
+        self.skinning_type
+    }
+
+    pub fn clusters(&self) -> &[Cluster] {
+        &self.clusters
+    }
+
+    // bone of every cluster, in cluster order
+    pub fn bones(&self) -> Vec<Option<ObjectId>> {
+        self.clusters.iter().map(|w| w.bone).collect()
+    }
+
+    // inverse bind matrix of every cluster, in cluster order
+    pub fn inverse_bind_matrices(&self) -> Vec<Matrix4> {
+        self.clusters.iter().map(|w| w.inverse_bind_matrix()).collect()
+    }
+
+    // influences of every control point, strongest first
+    pub fn influences(&self, control_point_count: usize, options: &InfluenceOptions) -> Result<Vec<Vec<Influence>>, SceneError> {
+        let mut influences: Vec<Vec<Influence>> = vec![Vec::new(); control_point_count];
+
+        for (index, cluster) in self.clusters.iter().enumerate() {
+            for (control_point, weight) in cluster.indices.iter().zip(cluster.weights.iter()) {
+                if *control_point < 0 || *control_point as usize >= control_point_count {
+                    return Err(SceneError::IndexOutOfRange(*control_point as i64, control_point_count));
+                }
+
+                if *weight > options.min_weight {
+                    influences[*control_point as usize].push(Influence { cluster: index, weight: *weight });
+                }
+            }
+        }
+
+        for list in influences.iter_mut() {
+            list.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+
+            if let Some(max) = options.max_influences {
+                list.truncate(max);
+            }
+
+            if options.normalize {
+                let total: f64 = list.iter().map(|w| w.weight).sum();
+
+                if total > 0.0 {
+                    list.iter_mut().for_each(|w| w.weight /= total);
+                }
+            }
+        }
+
+        Ok(influences)
+    }
+}
+
+// skins deforming a geometry, in connection order
+pub fn geometry_skins(graph: &ObjectGraph, geometry: ObjectId) -> Result<Vec<Skin>, SceneError> {
+    let mut skins = Vec::new();
+
+    for skin in graph.children(geometry).into_iter().filter(|w| is_class(graph, *w, "Deformer", "Skin")) {
+        skins.push(Skin::from_graph(graph, skin)?);
+    }
+
+    Ok(skins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn cluster(id: ObjectId, indices: &[i32], weights: &[f64], transform: Matrix4) -> fbx::format::Node {
+        let link = Matrix4::translation([0.0, 2.0, 0.0]);
+        object("Deformer", id, "cluster", "Cluster", vec![i32_array("Indexes", indices), f64_array("Weights", weights), f64_array("Transform", transform.as_array()), f64_array("TransformLink", link.as_array())])
+    }
+
+    // a mesh with four control points bound to two bones
+    fn skinned() -> ObjectGraph {
+        let objects = vec![
+            object("Geometry", 1, "mesh", "Mesh", vec![]),
+            object("Deformer", 2, "skin", "Skin", vec![text_node("SkinningType", "DualQuaternion")]),
+            cluster(3, &[0, 1, 2], &[1.0, 0.25, 0.5], Matrix4::translation([0.0, -2.0, 0.0])),
+            cluster(4, &[1, 2], &[0.75, 0.5], Matrix4::IDENTITY),
+            object("Model", 5, "hips", "LimbNode", vec![]),
+            object("Model", 6, "spine", "LimbNode", vec![]),
+        ];
+
+        graph(objects, vec![oo(2, 1), oo(3, 2), oo(4, 2), oo(5, 3), oo(6, 4)])
+    }
+
+    #[test]
+    fn decodes_clusters_and_bind_matrices() {
+        let graph = skinned();
+        let skins = geometry_skins(&graph, 1).unwrap();
+
+        assert_eq!(skins.len(), 1);
+        assert_eq!(skins[0].skinning_type(), SkinningType::DualQuaternion);
+        assert_eq!(skins[0].bones(), [Some(5), Some(6)]);
+        assert_eq!(skins[0].clusters()[0].indices(), [0, 1, 2]);
+        assert!(skins[0].inverse_bind_matrices()[0].approx_eq(&Matrix4::translation([0.0, -2.0, 0.0]), 1e-12));
+        assert!(skins[0].clusters()[0].mesh_bind_matrix().approx_eq(&Matrix4::IDENTITY, 1e-12));
+    }
+
+    #[test]
+    fn limits_and_normalizes_influences() {
+        let skin = Skin::from_graph(&skinned(), 2).unwrap();
+
+        let raw = skin.influences(4, &InfluenceOptions { normalize: false, max_influences: None, min_weight: 0.0 }).unwrap();
+        assert_eq!(raw[1], [Influence { cluster: 1, weight: 0.75 }, Influence { cluster: 0, weight: 0.25 }]);
+        assert!(raw[3].is_empty());
+
+        let limited = skin.influences(4, &InfluenceOptions::new(Some(1))).unwrap();
+        assert_eq!(limited[1], [Influence { cluster: 1, weight: 1.0 }]);
+        // equal weights keep cluster order
+        assert_eq!(limited[2], [Influence { cluster: 0, weight: 1.0 }]);
+
+        let normalized = skin.influences(4, &InfluenceOptions::default()).unwrap();
+        assert_eq!(normalized[2], [Influence { cluster: 0, weight: 0.5 }, Influence { cluster: 1, weight: 0.5 }]);
+
+        assert!(matches!(skin.influences(2, &InfluenceOptions::default()), Err(SceneError::IndexOutOfRange(2, 2))));
+    }
+
+    #[test]
+    fn rejects_mismatched_arrays() {
+        let objects = vec![object("Deformer", 3, "cluster", "Cluster", vec![i32_array("Indexes", &[0, 1]), f64_array("Weights", &[1.0])])];
+
+        assert!(matches!(Cluster::from_graph(&graph(objects, vec![]), 3), Err(SceneError::InvalidArrayLength(_, 1))));
+    }
+}
@@ -0,0 +1,81 @@
+// builders for the small hand-written node trees the unit tests decode
+
+use fbx::format::{Node, Object, Type, Version};
+
+use crate::graph::{ObjectGraph, ObjectId};
+
+pub fn node(name: &str, attributes: Vec<Type>, children: Vec<Node>) -> Node {
+    Node::new(name.to_owned(), attributes, children)
+}
+
+pub fn string(value: &str) -> Type {
+    Type::String(value.to_owned())
+}
+
+// an `Objects` entry written the binary way, "Name\x00\x01Class"
+pub fn object(class: &str, id: ObjectId, name: &str, subclass: &str, children: Vec<Node>) -> Node {
+    node(class, vec![Type::Int64(id), string(&format!("{}\x00\x01{}", name, class)), string(subclass)], children)
+}
+
+pub fn property(name: &str, type_name: &str, values: Vec<Type>) -> Node {
+    let mut attributes = vec![string(name), string(type_name), string(""), string("A")];
+    attributes.extend(values);
+    node("P", attributes, vec![])
+}
+
+pub fn number_property(name: &str, value: f64) -> Node {
+    property(name, "Number", vec![Type::Float64(value)])
+}
+
+pub fn int_property(name: &str, type_name: &str, value: i32) -> Node {
+    property(name, type_name, vec![Type::Int32(value)])
+}
+
+pub fn vector_property(name: &str, type_name: &str, value: [f64; 3]) -> Node {
+    property(name, type_name, value.iter().map(|w| Type::Float64(*w)).collect())
+}
+
+pub fn properties70(children: Vec<Node>) -> Node {
+    node("Properties70", vec![], children)
+}
+
+pub fn f64_array(name: &str, values: &[f64]) -> Node {
+    node(name, vec![Type::VecFloat64(values.to_vec())], vec![])
+}
+
+pub fn i32_array(name: &str, values: &[i32]) -> Node {
+    node(name, vec![Type::VecInt32(values.to_vec())], vec![])
+}
+
+pub fn text_node(name: &str, value: &str) -> Node {
+    node(name, vec![string(value)], vec![])
+}
+
+pub fn oo(child: ObjectId, parent: ObjectId) -> Node {
+    node("C", vec![string("OO"), Type::Int64(child), Type::Int64(parent)], vec![])
+}
+
+pub fn op(child: ObjectId, parent: ObjectId, property: &str) -> Node {
+    node("C", vec![string("OP"), Type::Int64(child), Type::Int64(parent), string(property)], vec![])
+}
+
+pub fn scene(definitions: Vec<Node>, objects: Vec<Node>, connections: Vec<Node>) -> Object {
+    let sections = vec![node("Definitions", vec![], definitions), node("Objects", vec![], objects), node("Connections", vec![], connections)];
+    Object::new(Version::from(7, 4), sections, None)
+}
+
+pub fn graph(objects: Vec<Node>, connections: Vec<Node>) -> ObjectGraph {
+    ObjectGraph::from(&scene(vec![], objects, connections))
+}
+
+pub fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!((actual - expected).abs() <= tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+}
+
+pub fn assert_all_close(actual: &[f64], expected: &[f64], tolerance: f64) {
+    assert_eq!(actual.len(), expected.len(), "{:?} and {:?} differ in length", actual, expected);
+
+    for (a, e) in actual.iter().zip(expected.iter()) {
+        assert!((a - e).abs() <= tolerance, "{:?} is not within {} of {:?}", actual, tolerance, expected);
+    }
+}
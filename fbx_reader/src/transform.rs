@@ -0,0 +1,381 @@
+use std::collections::HashSet;
+
+use crate::animation::{AnimationStack, BlendMode};
+use crate::error::SceneError;
+use crate::graph::{ObjectGraph, ObjectId};
+use crate::math::{Matrix4, RotationOrder, Vector3};
+use crate::properties::Properties;
+
+const CHANNELS: [&str; 3] = ["d|X", "d|Y", "d|Z"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InheritType {
+    // the parent's scaling is applied along the child's axes, so rotated children never shear
+    RrSs,
+    // plain matrix product, a non-uniformly scaled parent shears its rotated children
+    RSrs,
+    // the parent's own scaling is not inherited, like Maya's segment scale compensation
+    Rrs,
+}
+
+impl InheritType {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            0 => InheritType::RrSs,
+            2 => InheritType::Rrs,
+            _ => InheritType::RSrs,
+        }
+    }
+}
+
+/// The transform properties of a `Model`, as stored or sampled from a take.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelTransform {
+    pub translation: Vector3,
+    // euler angles in degrees
+    pub rotation: Vector3,
+    pub scaling: Vector3,
+    pub rotation_order: RotationOrder,
+    // applied in XYZ order around the rotation, only when `RotationActive` is set
+    pub pre_rotation: Vector3,
+    pub post_rotation: Vector3,
+    pub rotation_offset: Vector3,
+    pub rotation_pivot: Vector3,
+    pub scaling_offset: Vector3,
+    pub scaling_pivot: Vector3,
+    pub inherit_type: InheritType,
+    // applied to the attached geometry only, not inherited by children
+    pub geometric_translation: Vector3,
+    pub geometric_rotation: Vector3,
+    pub geometric_scaling: Vector3,
+}
+
+impl ModelTransform {
+    pub fn from_properties(properties: &Properties) -> Self {
+        let vector = |name: &str, default: f64| properties.get_vector3(name).unwrap_or([default; 3]);
+
+        // the SDK ignores pre/post rotations and the rotation order unless `RotationActive` is set
+        let rotation_active = properties.get_bool("RotationActive").unwrap_or(false);
+        let (pre_rotation, post_rotation, rotation_order) = if rotation_active {
+            (vector("PreRotation", 0.0), vector("PostRotation", 0.0), RotationOrder::from_fbx(properties.get_i64("RotationOrder").unwrap_or(0)))
+        } else {
+            ([0.0; 3], [0.0; 3], RotationOrder::Xyz)
+        };
+
+        ModelTransform {
+            translation: vector("Lcl Translation", 0.0),
+            rotation: vector("Lcl Rotation", 0.0),
+            scaling: vector("Lcl Scaling", 1.0),
+            rotation_order,
+            pre_rotation,
+            post_rotation,
+            rotation_offset: vector("RotationOffset", 0.0),
+            rotation_pivot: vector("RotationPivot", 0.0),
+            scaling_offset: vector("ScalingOffset", 0.0),
+            scaling_pivot: vector("ScalingPivot", 0.0),
+            inherit_type: InheritType::from_fbx(properties.get_i64("InheritType").unwrap_or(0)),
+            geometric_translation: vector("GeometricTranslation", 0.0),
+            geometric_rotation: vector("GeometricRotation", 0.0),
+            geometric_scaling: vector("GeometricScaling", 1.0),
+        }
+    }
+
+    pub fn from_graph(graph: &ObjectGraph, id: ObjectId) -> Result<ModelTransform, SceneError> {
+        match graph.object(id) {
+            Some(object) if object.class() == "Model" => Ok(ModelTransform::from_properties(&graph.properties(id).unwrap_or_default())),
+            Some(_) => Err(SceneError::UnexpectedObject(id, "Model".to_owned())),
+            None => Err(SceneError::MissingObject(id)),
+        }
+    }
+
+    fn vector_mut(&mut self, property: &str) -> Option<&mut Vector3> {
+        match property {
+            "Lcl Translation" => Some(&mut self.translation),
+            "Lcl Rotation" => Some(&mut self.rotation),
+            "Lcl Scaling" => Some(&mut self.scaling),
+            "PreRotation" => Some(&mut self.pre_rotation),
+            "PostRotation" => Some(&mut self.post_rotation),
+            "RotationOffset" => Some(&mut self.rotation_offset),
+            "RotationPivot" => Some(&mut self.rotation_pivot),
+            "ScalingOffset" => Some(&mut self.scaling_offset),
+            "ScalingPivot" => Some(&mut self.scaling_pivot),
+            "GeometricTranslation" => Some(&mut self.geometric_translation),
+            "GeometricRotation" => Some(&mut self.geometric_rotation),
+            "GeometricScaling" => Some(&mut self.geometric_scaling),
+            _ => None,
+        }
+    }
+
+    // replaces the animated properties of `model` with their values at `time` in ticks; the first active layer
+    // blends over the stored values, additive layers add translations and rotations and multiply scalings
+    pub fn animate(&mut self, model: ObjectId, stack: &AnimationStack, time: i64) {
+        const PROPERTIES: [&str; 12] = [
+            "Lcl Translation",
+            "Lcl Rotation",
+            "Lcl Scaling",
+            "PreRotation",
+            "PostRotation",
+            "RotationOffset",
+            "RotationPivot",
+            "ScalingOffset",
+            "ScalingPivot",
+            "GeometricTranslation",
+            "GeometricRotation",
+            "GeometricScaling",
+        ];
+
+        for (index, layer) in stack.active_layers().into_iter().enumerate() {
+            let weight = layer.weight() / 100.0;
+
+            for property in PROPERTIES {
+                let curve_node = match layer.curve_node(model, property) {
+                    Some(curve_node) => curve_node,
+                    None => continue,
+                };
+
+                let slot = match self.vector_mut(property) {
+                    Some(slot) => slot,
+                    None => continue,
+                };
+
+                for (axis, name) in CHANNELS.iter().enumerate() {
+                    let value = match curve_node.channel(name) {
+                        Some(channel) => channel.evaluate(time),
+                        None => continue,
+                    };
+
+                    slot[axis] = match (index, layer.blend_mode()) {
+                        (0, _) | (_, BlendMode::Override) | (_, BlendMode::OverridePassthrough) => slot[axis] + (value - slot[axis]) * weight,
+                        (_, BlendMode::Additive) if property == "Lcl Scaling" || property == "GeometricScaling" => slot[axis] * value.powf(weight),
+                        (_, BlendMode::Additive) => slot[axis] + value * weight,
+                    };
+                }
+            }
+        }
+    }
+
+    // `PreRotation * Rotation * PostRotation^-1`
+    pub fn rotation_matrix(&self) -> Matrix4 {
+        let post_rotation = Matrix4::from_euler(self.post_rotation, RotationOrder::Xyz).transpose();
+        Matrix4::from_euler(self.pre_rotation, RotationOrder::Xyz) * Matrix4::from_euler(self.rotation, self.rotation_order) * post_rotation
+    }
+
+    // `T * Roff * Rp * Rpre * R * Rpost^-1 * Rp^-1 * Soff * Sp * S * Sp^-1`
+    pub fn local_matrix(&self) -> Matrix4 {
+        let negate = |v: Vector3| [-v[0], -v[1], -v[2]];
+
+        Matrix4::translation(self.translation)
+            * Matrix4::translation(self.rotation_offset)
+            * Matrix4::translation(self.rotation_pivot)
+            * self.rotation_matrix()
+            * Matrix4::translation(negate(self.rotation_pivot))
+            * Matrix4::translation(self.scaling_offset)
+            * Matrix4::translation(self.scaling_pivot)
+            * Matrix4::scaling(self.scaling)
+            * Matrix4::translation(negate(self.scaling_pivot))
+    }
+
+    // offset of the attached geometry from the model, `Tg * Rg * Sg`
+    pub fn geometric_matrix(&self) -> Matrix4 {
+        Matrix4::translation(self.geometric_translation)
+            * Matrix4::from_euler(self.geometric_rotation, RotationOrder::Xyz)
+            * Matrix4::scaling(self.geometric_scaling)
+    }
+}
+
+// world matrix of a model from its parent's world matrix and `Lcl Scaling`, following the FBX SDK
+fn inherit(parent: Matrix4, parent_scaling: Vector3, local: Matrix4, inherit_type: InheritType) -> Matrix4 {
+    if inherit_type == InheritType::RSrs {
+        return parent * local;
+    }
+
+    let (_, parent_rotation, _) = parent.decompose();
+    let mut parent_linear = parent;
+    for row in 0..3 {
+        parent_linear.set(row, 3, 0.0);
+    }
+
+    // whatever of the parent is not rotation, shear included
+    let parent_rest = parent_rotation.transpose() * parent_linear;
+    let (translation, rotation, scaling) = local.decompose();
+
+    let mut linear = parent_rotation * rotation * parent_rest;
+    if inherit_type == InheritType::Rrs {
+        linear = linear * Matrix4::scaling(parent_scaling.map(|w| if w != 0.0 { 1.0 / w } else { 0.0 }));
+    }
+
+    Matrix4::translation(parent.transform_point(translation)) * linear * Matrix4::scaling(scaling)
+}
+
+/// Evaluates model transforms through the hierarchy, at rest or at any time of one take.
+#[derive(Debug, Clone, Copy)]
+pub struct TransformEvaluator<'a> {
+    graph: &'a ObjectGraph,
+    stack: Option<&'a AnimationStack>,
+}
+
+impl<'a> TransformEvaluator<'a> {
+    // without a stack every time gives the stored transforms
+    pub fn new(graph: &'a ObjectGraph, stack: Option<&'a AnimationStack>) -> Self {
+        TransformEvaluator { graph, stack }
+    }
+
+    pub fn transform(&self, model: ObjectId, time: i64) -> Result<ModelTransform, SceneError> {
+        let mut transform = ModelTransform::from_graph(self.graph, model)?;
+
+        if let Some(stack) = self.stack {
+            transform.animate(model, stack, time);
+        }
+
+        Ok(transform)
+    }
+
+    // the parent `Model`, `None` for models under the scene root
+    pub fn parent(&self, model: ObjectId) -> Option<ObjectId> {
+        self.graph.parents(model).into_iter().find(|w| self.graph.object(*w).map(|o| o.class() == "Model").unwrap_or(false))
+    }
+
+    pub fn local_matrix(&self, model: ObjectId, time: i64) -> Result<Matrix4, SceneError> {
+        Ok(self.transform(model, time)?.local_matrix())
+    }
+
+    pub fn geometric_matrix(&self, model: ObjectId, time: i64) -> Result<Matrix4, SceneError> {
+        Ok(self.transform(model, time)?.geometric_matrix())
+    }
+
+    pub fn world_matrix(&self, model: ObjectId, time: i64) -> Result<Matrix4, SceneError> {
+        let mut ancestry = vec![model];
+        let mut visited = HashSet::from([model]);
+
+        while let Some(parent) = self.parent(*ancestry.last().unwrap_or(&model)) {
+            if !visited.insert(parent) {
+                return Err(SceneError::CyclicHierarchy(parent));
+            }
+
+            ancestry.push(parent);
+        }
+
+        let (mut world, mut parent_scaling) = (Matrix4::IDENTITY, [1.0; 3]);
+
+        for id in ancestry.into_iter().rev() {
+            let transform = self.transform(id, time)?;
+            world = inherit(world, parent_scaling, transform.local_matrix(), transform.inherit_type);
+            parent_scaling = transform.scaling;
+        }
+
+        Ok(world)
+    }
+
+    // where the vertices of the model's geometry end up, `World * Geometric`
+    pub fn geometry_world_matrix(&self, model: ObjectId, time: i64) -> Result<Matrix4, SceneError> {
+        Ok(self.world_matrix(model, time)? * self.geometric_matrix(model, time)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{seconds_to_ticks, AnimationStack, TICKS_PER_SECOND};
+    use crate::testing::*;
+    use fbx::format::{Node, Type};
+
+    fn model(id: ObjectId, properties: Vec<Node>) -> Node {
+        object("Model", id, &format!("model{}", id), "Null", vec![properties70(properties)])
+    }
+
+    fn evaluate(objects: Vec<Node>, connections: Vec<Node>, id: ObjectId, point: Vector3) -> Vector3 {
+        let graph = graph(objects, connections);
+        TransformEvaluator::new(&graph, None).world_matrix(id, 0).unwrap().transform_point(point)
+    }
+
+    #[test]
+    fn rotates_and_scales_around_pivots() {
+        let rotated = model(1, vec![vector_property("Lcl Rotation", "Lcl Rotation", [0.0, 0.0, 90.0]), vector_property("RotationPivot", "Vector3D", [1.0, 0.0, 0.0])]);
+        assert_all_close(&evaluate(vec![rotated], vec![oo(1, 0)], 1, [2.0, 0.0, 0.0]), &[1.0, 1.0, 0.0], 1e-9);
+
+        let scaled = model(1, vec![vector_property("Lcl Scaling", "Lcl Scaling", [2.0, 2.0, 2.0]), vector_property("ScalingPivot", "Vector3D", [1.0, 0.0, 0.0])]);
+        assert_all_close(&evaluate(vec![scaled], vec![], 1, [2.0, 0.0, 0.0]), &[3.0, 0.0, 0.0], 1e-9);
+
+        let offset = model(1, vec![vector_property("Lcl Translation", "Lcl Translation", [1.0, 0.0, 0.0]), vector_property("RotationOffset", "Vector3D", [0.0, 2.0, 0.0]), vector_property("ScalingOffset", "Vector3D", [0.0, 0.0, 3.0])]);
+        assert_all_close(&evaluate(vec![offset], vec![], 1, [0.0; 3]), &[1.0, 2.0, 3.0], 1e-9);
+    }
+
+    #[test]
+    fn pre_and_post_rotations_need_rotation_active() {
+        let rotations = || vec![vector_property("PreRotation", "Vector3D", [0.0, 0.0, 90.0]), vector_property("PostRotation", "Vector3D", [0.0, 0.0, 45.0]), int_property("RotationOrder", "enum", 5)];
+
+        let inactive = ModelTransform::from_graph(&graph(vec![model(1, rotations())], vec![]), 1).unwrap();
+        assert_eq!((inactive.pre_rotation, inactive.rotation_order), ([0.0; 3], RotationOrder::Xyz));
+
+        let mut active = rotations();
+        active.push(int_property("RotationActive", "bool", 1));
+        let transform = ModelTransform::from_graph(&graph(vec![model(1, active)], vec![]), 1).unwrap();
+        assert_eq!(transform.rotation_order, RotationOrder::from_fbx(5));
+
+        // Rpre * Rpost^-1 leaves a rotation of 45 degrees around Z
+        let expected = Matrix4::rotation_axis(2, 45.0);
+        assert!(transform.local_matrix().approx_eq(&expected, 1e-9));
+    }
+
+    #[test]
+    fn inherits_parent_scaling_by_inherit_type() {
+        let parent = model(1, vec![vector_property("Lcl Scaling", "Lcl Scaling", [2.0, 1.0, 1.0])]);
+        let child = |inherit_type: i32| model(2, vec![vector_property("Lcl Rotation", "Lcl Rotation", [0.0, 0.0, 90.0]), int_property("InheritType", "enum", inherit_type)]);
+        let axes = |inherit_type: i32| {
+            let graph = graph(vec![parent.clone(), child(inherit_type)], vec![oo(1, 0), oo(2, 1)]);
+            let world = TransformEvaluator::new(&graph, None).world_matrix(2, 0).unwrap();
+            [world.transform_vector([1.0, 0.0, 0.0]), world.transform_vector([0.0, 1.0, 0.0])].concat()
+        };
+
+        // RrSs scales along the child's rotated X axis, RSrs along the world X axis, Rrs drops the parent's scaling
+        assert_all_close(&axes(0), &[0.0, 2.0, 0.0, -1.0, 0.0, 0.0], 1e-9);
+        assert_all_close(&axes(1), &[0.0, 1.0, 0.0, -2.0, 0.0, 0.0], 1e-9);
+        assert_all_close(&axes(2), &[0.0, 1.0, 0.0, -1.0, 0.0, 0.0], 1e-9);
+    }
+
+    #[test]
+    fn chains_world_and_geometric_matrices() {
+        let parent = model(1, vec![vector_property("Lcl Translation", "Lcl Translation", [1.0, 0.0, 0.0]), vector_property("Lcl Rotation", "Lcl Rotation", [0.0, 0.0, 90.0])]);
+        let child = model(2, vec![vector_property("Lcl Translation", "Lcl Translation", [1.0, 0.0, 0.0]), vector_property("GeometricTranslation", "Vector3D", [0.0, 0.0, 5.0])]);
+        let graph = graph(vec![parent, child], vec![oo(1, 0), oo(2, 1)]);
+        let evaluator = TransformEvaluator::new(&graph, None);
+
+        assert_eq!(evaluator.parent(2), Some(1));
+        assert_all_close(&evaluator.world_matrix(2, 0).unwrap().get_translation(), &[1.0, 1.0, 0.0], 1e-9);
+        assert_all_close(&evaluator.geometry_world_matrix(2, 0).unwrap().get_translation(), &[1.0, 1.0, 5.0], 1e-9);
+    }
+
+    #[test]
+    fn samples_animated_properties() {
+        let curve = |id: ObjectId, values: Vec<f32>| {
+            let times = vec![0, TICKS_PER_SECOND];
+            object("AnimationCurve", id, "", "", vec![node("KeyTime", vec![Type::VecInt64(times)], vec![]), node("KeyValueFloat", vec![Type::VecFloat32(values)], vec![])])
+        };
+
+        let objects = vec![
+            model(1, vec![vector_property("Lcl Translation", "Lcl Translation", [0.0, 7.0, 0.0])]),
+            object("AnimationStack", 2, "Take 001", "", vec![]),
+            object("AnimationLayer", 3, "Base", "", vec![]),
+            object("AnimationCurveNode", 4, "T", "", vec![properties70(vec![number_property("d|X", 0.0)])]),
+            curve(5, vec![0.0, 10.0]),
+            object("AnimationLayer", 6, "Additive", "", vec![properties70(vec![int_property("BlendMode", "enum", 0), number_property("Weight", 50.0)])]),
+            object("AnimationCurveNode", 7, "T", "", vec![properties70(vec![number_property("d|Y", 0.0)])]),
+            curve(8, vec![2.0, 2.0]),
+        ];
+        let connections = vec![oo(3, 2), oo(6, 2), oo(4, 3), oo(7, 6), op(4, 1, "Lcl Translation"), op(7, 1, "Lcl Translation"), op(5, 4, "d|X"), op(8, 7, "d|Y")];
+        let graph = graph(objects, connections);
+        let stack = AnimationStack::from_graph(&graph, 2).unwrap();
+
+        let transform = TransformEvaluator::new(&graph, Some(&stack)).transform(1, seconds_to_ticks(0.5)).unwrap();
+        assert_all_close(&transform.translation, &[5.0, 8.0, 0.0], 1e-6);
+
+        let rest = TransformEvaluator::new(&graph, None).transform(1, seconds_to_ticks(0.5)).unwrap();
+        assert_eq!(rest.translation, [0.0, 7.0, 0.0]);
+    }
+
+    #[test]
+    fn rejects_cyclic_hierarchies() {
+        let graph = graph(vec![model(1, vec![]), model(2, vec![])], vec![oo(1, 2), oo(2, 1)]);
+        assert!(matches!(TransformEvaluator::new(&graph, None).world_matrix(1, 0), Err(SceneError::CyclicHierarchy(_))));
+    }
+}